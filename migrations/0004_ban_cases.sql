@@ -0,0 +1,31 @@
+-- 0004_ban_cases.sql
+-- System BAN – historia banów (perm/temp) + harmonogram zdejmowania banów tymczasowych.
+
+-- Upewnij się, że schemat istnieje
+CREATE SCHEMA IF NOT EXISTS tss;
+
+-- Pojedynczy ban (sprawa)
+CREATE TABLE IF NOT EXISTS tss.ban_cases (
+  id            BIGSERIAL PRIMARY KEY,
+  guild_id      BIGINT       NOT NULL,
+  user_id       BIGINT       NOT NULL,
+  moderator_id  BIGINT       NOT NULL,
+  kind          TEXT         NOT NULL CHECK (kind IN ('perma','temp')),
+  reason        TEXT         NOT NULL,
+  created_at    TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  expires_at    TIMESTAMPTZ  NULL,                 -- tylko dla 'temp'
+  unbanned_at   TIMESTAMPTZ  NULL,
+  unbanned_by   BIGINT       NULL,                 -- moderator albo bot (scheduler)
+  unban_source  TEXT         NULL,                 -- 'scheduler' | 'manual' | 'external'
+  unban_reason  TEXT         NULL,
+  deleted_at    TIMESTAMPTZ  NULL
+);
+
+-- Indeksy pod najczęstsze zapytania
+CREATE INDEX IF NOT EXISTS idx_ban_cases_gid_uid_created
+  ON tss.ban_cases (guild_id, user_id, created_at DESC);
+
+-- Scheduler: otwarte bany tymczasowe po dacie wygaśnięcia
+CREATE INDEX IF NOT EXISTS idx_ban_cases_open_expires
+  ON tss.ban_cases (expires_at)
+  WHERE unbanned_at IS NULL AND expires_at IS NOT NULL;
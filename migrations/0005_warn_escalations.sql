@@ -0,0 +1,24 @@
+-- 0005_warn_escalations.sql
+-- Automatyczne eskalacje z warnów (progi z tss.warn_config) – każda jako osobna sprawa,
+-- powiązana z warnem, który ją wywołał, oraz (jeśli dotyczy) ze sprawą mute/ban.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.warn_escalations (
+  id              BIGSERIAL PRIMARY KEY,
+  guild_id        BIGINT       NOT NULL,
+  user_id         BIGINT       NOT NULL,
+  warn_case_id    BIGINT       NOT NULL REFERENCES tss.warn_cases (id),
+  action          TEXT         NOT NULL CHECK (action IN ('timeout','kick','ban')),
+  threshold_pts   INTEGER      NOT NULL,  -- przekroczony próg
+  active_points   INTEGER      NOT NULL,  -- aktywne punkty po warnie
+  mute_case_id    BIGINT       NULL REFERENCES tss.mute_cases (id),
+  ban_case_id     BIGINT       NULL REFERENCES tss.ban_cases (id),
+  created_at      TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_warn_escalations_gid_uid
+  ON tss.warn_escalations (guild_id, user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_warn_escalations_warn
+  ON tss.warn_escalations (warn_case_id);
@@ -0,0 +1,16 @@
+-- 0006_warn_presets.sql
+-- Presety wagi warnów per gildia (np. "spam" = 1, "nekanie" = 3) + zapis presetu w sprawie.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.warn_presets (
+  guild_id    BIGINT       NOT NULL,
+  name        TEXT         NOT NULL,
+  points      INTEGER      NOT NULL CHECK (points > 0),
+  updated_at  TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  PRIMARY KEY (guild_id, name)
+);
+
+-- Z jakiego presetu pochodzi waga warna (NULL = ręcznie / domyślnie)
+ALTER TABLE tss.warn_cases
+  ADD COLUMN IF NOT EXISTS preset TEXT NULL;
@@ -0,0 +1,78 @@
+-- 0007_cases.sql
+-- Wspólny rejestr spraw moderacyjnych (warn/mute/timeout/kick/ban) z numeracją per gildia.
+-- Szczegóły akcji zostają w tabelach modułów (warn_cases/mute_cases/ban_cases) – tu jest
+-- wpis z numerem sprawy + odnośnik (ref_table, ref_id).
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+-- Licznik numerów spraw per gildia
+CREATE TABLE IF NOT EXISTS tss.case_counters (
+  guild_id  BIGINT PRIMARY KEY,
+  last_no   BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS tss.cases (
+  id             BIGSERIAL PRIMARY KEY,
+  guild_id       BIGINT       NOT NULL,
+  case_no        BIGINT       NOT NULL,
+  action         TEXT         NOT NULL
+                 CHECK (action IN ('WARN','MUTE','TIMEOUT','KICK','BAN','UNBAN','UNMUTE')),
+  user_id        BIGINT       NOT NULL,
+  moderator_id   BIGINT       NOT NULL,
+  reason         TEXT         NOT NULL,
+  evidence       TEXT         NULL,
+  source         TEXT         NULL,                 -- np. „Auto-eskalacja z warna #12”
+  ref_table      TEXT         NULL CHECK (ref_table IN ('warn_cases','mute_cases','ban_cases')),
+  ref_id         BIGINT       NULL,
+  created_at     TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  updated_at     TIMESTAMPTZ  NULL,
+  updated_by     BIGINT       NULL,
+  deleted_at     TIMESTAMPTZ  NULL,
+  deleted_by     BIGINT       NULL,
+  delete_reason  TEXT         NULL,
+  UNIQUE (guild_id, case_no)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cases_gid_uid_created
+  ON tss.cases (guild_id, user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_cases_gid_action_created
+  ON tss.cases (guild_id, action, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_cases_ref
+  ON tss.cases (ref_table, ref_id);
+
+-- Kolumny dodawane dotąd tylko w runtime (Warns::ensure_tables) – potrzebne do przeniesienia
+ALTER TABLE tss.warn_cases
+  ADD COLUMN IF NOT EXISTS deleted_at    TIMESTAMPTZ NULL,
+  ADD COLUMN IF NOT EXISTS deleted_by    BIGINT      NULL,
+  ADD COLUMN IF NOT EXISTS delete_reason TEXT        NULL;
+
+-- Przeniesienie istniejącej historii (kolejność wg created_at)
+INSERT INTO tss.cases
+  (guild_id, case_no, action, user_id, moderator_id, reason, evidence,
+   ref_table, ref_id, created_at, deleted_at, deleted_by, delete_reason)
+SELECT guild_id,
+       ROW_NUMBER() OVER (PARTITION BY guild_id ORDER BY created_at, ref_table, ref_id),
+       action, user_id, moderator_id, reason, evidence,
+       ref_table, ref_id, created_at, deleted_at, deleted_by, delete_reason
+FROM (
+  SELECT guild_id, 'WARN' AS action, user_id, moderator_id, reason, evidence,
+         'warn_cases' AS ref_table, id AS ref_id, created_at,
+         deleted_at, deleted_by, delete_reason
+  FROM tss.warn_cases
+  UNION ALL
+  SELECT guild_id, CASE WHEN method = 'timeout' THEN 'TIMEOUT' ELSE 'MUTE' END,
+         user_id, moderator_id, reason, evidence,
+         'mute_cases', id, created_at, NULL, NULL, NULL
+  FROM tss.mute_cases
+  UNION ALL
+  SELECT guild_id, 'BAN', user_id, moderator_id, reason, NULL,
+         'ban_cases', id, created_at, deleted_at, NULL, NULL
+  FROM tss.ban_cases
+) AS src
+WHERE NOT EXISTS (SELECT 1 FROM tss.cases);
+
+INSERT INTO tss.case_counters (guild_id, last_no)
+SELECT guild_id, MAX(case_no) FROM tss.cases GROUP BY guild_id
+ON CONFLICT (guild_id) DO UPDATE SET last_no = GREATEST(tss.case_counters.last_no, EXCLUDED.last_no);
@@ -0,0 +1,28 @@
+-- 0008_appeals.sql
+-- Odwołania od banów (przycisk w DM → modal → kanał odwołań → decyzja administracji).
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.appeals (
+  id            BIGSERIAL PRIMARY KEY,
+  guild_id      BIGINT       NOT NULL,
+  user_id       BIGINT       NOT NULL,
+  ban_case_id   BIGINT       NULL REFERENCES tss.ban_cases (id),
+  message       TEXT         NOT NULL,
+  status        TEXT         NOT NULL DEFAULT 'pending'
+                CHECK (status IN ('pending','accepted','denied')),
+  channel_id    BIGINT       NULL,                 -- wpis na kanale odwołań
+  message_id    BIGINT       NULL,
+  created_at    TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  decided_at    TIMESTAMPTZ  NULL,
+  decided_by    BIGINT       NULL
+);
+
+-- Cooldown: ostatnie odwołanie użytkownika w gildii
+CREATE INDEX IF NOT EXISTS idx_appeals_gid_uid_created
+  ON tss.appeals (guild_id, user_id, created_at DESC);
+
+-- Najwyżej jedno oczekujące odwołanie na użytkownika
+CREATE UNIQUE INDEX IF NOT EXISTS uq_appeals_pending
+  ON tss.appeals (guild_id, user_id)
+  WHERE status = 'pending';
@@ -0,0 +1,24 @@
+-- 0009_ban_panel_sessions.sql
+-- Sesje wieloetapowego panelu /ban (wcześniej tylko w pamięci) – przeżywają restart bota.
+-- Wygasłe sesje sprząta scheduler banów i oznacza ich panele jako wygasłe.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.ban_panel_sessions (
+  case_id           TEXT         PRIMARY KEY,      -- "<interaction_id>-<moderator_id>"
+  guild_id          BIGINT       NOT NULL,
+  moderator_id      BIGINT       NOT NULL,
+  target_id         BIGINT       NOT NULL,
+  kind              TEXT         NULL CHECK (kind IN ('perma','temp')),
+  duration_secs     BIGINT       NULL,
+  reason            TEXT         NULL,
+  panel_channel_id  BIGINT       NULL,
+  panel_message_id  BIGINT       NULL,
+  panel_token       TEXT         NULL,             -- token interakcji (edycja panelu ephemeral)
+  created_at        TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  updated_at        TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  expires_at        TIMESTAMPTZ  NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ban_panel_sessions_expires
+  ON tss.ban_panel_sessions (expires_at);
@@ -0,0 +1,8 @@
+-- 0010_ban_purge.sql
+-- Zakres usuniętej historii wiadomości przy banie (wybór w panelu /ban: brak/1h/24h/7d).
+
+ALTER TABLE tss.ban_cases
+  ADD COLUMN IF NOT EXISTS purge_secs INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE tss.ban_panel_sessions
+  ADD COLUMN IF NOT EXISTS purge_secs INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,23 @@
+-- 0011_guild_profiles.sql
+-- Profil gildii zamiast globalnego TSS_ENV: które stałe z registry.rs są fallbackiem.
+--   production  – stałe serwera głównego (roles::*, channels::prod)
+--   development – stałe serwera testowego (dev::*, channels::dev)
+--   custom      – bez stałych, tylko wpisy z tss.resource_registry
+-- Bez domyślnej wartości: istniejące wiersze dostają profil z ustawień procesu (TSS_ENV)
+-- przy pierwszym guilds::warmup, a do tego czasu NULL = profil domyślny.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+ALTER TABLE tss.guilds
+  ADD COLUMN IF NOT EXISTS profile TEXT NULL;
+
+DO $$
+BEGIN
+  IF NOT EXISTS (
+    SELECT 1 FROM pg_constraint WHERE conname = 'guilds_profile_check'
+  ) THEN
+    ALTER TABLE tss.guilds
+      ADD CONSTRAINT guilds_profile_check
+      CHECK (profile IN ('production','development','custom'));
+  END IF;
+END $$;
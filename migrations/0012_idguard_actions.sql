@@ -0,0 +1,31 @@
+-- 0012_idguard_actions.sql
+-- IdGuard w trybie Auto: akcje wykonane automatycznie przy BLOCK (rename/quarantine/kick)
+-- oraz kolejka przeglądu dla WATCH (action = 'review'). Każdy wpis da się cofnąć albo
+-- zamknąć przyciskiem z embeda logu; cofnięty/zatwierdzony użytkownik jest wyłączony z Auto.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.idg_actions (
+  id             BIGSERIAL PRIMARY KEY,
+  guild_id       BIGINT       NOT NULL,
+  user_id        BIGINT       NOT NULL,
+  action         TEXT         NOT NULL
+                 CHECK (action IN ('review','rename','quarantine','kick')),
+  score          SMALLINT     NOT NULL,
+  signals        JSONB        NOT NULL DEFAULT '[]'::jsonb,
+  previous_nick  TEXT         NULL,                 -- rename: nick do przywrócenia
+  role_id        BIGINT       NULL,                 -- quarantine: nadana rola
+  created_at     TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  resolved_at    TIMESTAMPTZ  NULL,
+  resolved_by    BIGINT       NULL,
+  resolution     TEXT         NULL
+                 CHECK (resolution IN ('reverted','dismissed','actioned'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_idg_actions_gid_uid_created
+  ON tss.idg_actions (guild_id, user_id, created_at DESC);
+
+-- otwarta kolejka przeglądu (/idguard queue)
+CREATE INDEX IF NOT EXISTS idx_idg_actions_open_reviews
+  ON tss.idg_actions (guild_id, created_at)
+  WHERE action = 'review' AND resolved_at IS NULL;
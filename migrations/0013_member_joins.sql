@@ -0,0 +1,22 @@
+-- 0013_member_joins.sql
+-- Historia wejść na serwer z zaproszeniem ustalonym przez diff liczników użyć
+-- (src/invites.rs). NULL w invite_code = nie dało się ustalić (vanity, brak uprawnień,
+-- kilka równoczesnych wejść).
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.member_joins (
+  id           BIGSERIAL PRIMARY KEY,
+  guild_id     BIGINT       NOT NULL,
+  user_id      BIGINT       NOT NULL,
+  invite_code  TEXT         NULL,
+  inviter_id   BIGINT       NULL,
+  joined_at    TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_member_joins_gid_uid_joined
+  ON tss.member_joins (guild_id, user_id, joined_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_member_joins_gid_invite
+  ON tss.member_joins (guild_id, invite_code)
+  WHERE invite_code IS NOT NULL;
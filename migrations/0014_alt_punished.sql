@@ -0,0 +1,22 @@
+-- 0014_alt_punished.sql
+-- Tożsamości ukaranych (ban / kick / długi mute) dla AltGuarda: nazwy i aHash avatara.
+-- Zasilają sygnały NameSimilarity i AvatarPHash; wpis obowiązuje do expires_at
+-- (TTL ustawia AltGuard), wygasłe są czyszczone przy warmupie gildii.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.alt_punished (
+  id           BIGSERIAL PRIMARY KEY,
+  guild_id     BIGINT       NOT NULL,
+  user_id      BIGINT       NOT NULL,
+  username     TEXT         NOT NULL,
+  global_name  TEXT         NULL,
+  avatar_hash  BIGINT       NULL,                 -- aHash 8×8 (u64 zapisany bit-w-bit)
+  action       TEXT         NOT NULL
+               CHECK (action IN ('ban','kick','mute','timeout')),
+  created_at   TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  expires_at   TIMESTAMPTZ  NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alt_punished_gid_expires
+  ON tss.alt_punished (guild_id, expires_at);
@@ -0,0 +1,48 @@
+-- 0015_altguard_tables.sql
+-- Tabele AltGuarda, z których moduł korzystał dotąd best-effort (bez migracji):
+-- konfiguracja per gildia (/altguard config), whitelist (/altguard whitelist)
+-- i historia wyników scoringu.
+-- Tabele założone wcześniej ręcznie mogą nie mieć nowszych kolumn – dokładamy je niżej.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.alt_config (
+  guild_id    BIGINT       PRIMARY KEY,
+  config      JSONB        NOT NULL,
+  updated_at  TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS tss.alt_whitelist (
+  guild_id    BIGINT       NOT NULL,
+  user_id     BIGINT       NOT NULL,
+  note        TEXT         NOT NULL DEFAULT '',
+  added_by    BIGINT       NULL,
+  created_at  TIMESTAMPTZ  NOT NULL DEFAULT now(),
+  PRIMARY KEY (guild_id, user_id)
+);
+
+ALTER TABLE tss.alt_config    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE tss.alt_whitelist ADD COLUMN IF NOT EXISTS note       TEXT        NOT NULL DEFAULT '';
+ALTER TABLE tss.alt_whitelist ADD COLUMN IF NOT EXISTS added_by   BIGINT      NULL;
+ALTER TABLE tss.alt_whitelist ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+-- ON CONFLICT (guild_id, user_id) w whitelist_add
+CREATE UNIQUE INDEX IF NOT EXISTS uq_alt_whitelist_gid_uid
+  ON tss.alt_whitelist (guild_id, user_id);
+
+CREATE TABLE IF NOT EXISTS tss.alt_scores (
+  id           BIGSERIAL PRIMARY KEY,
+  guild_id     BIGINT       NOT NULL,
+  user_id      BIGINT       NOT NULL,
+  score        INT          NOT NULL,
+  verdict      TEXT         NOT NULL,
+  top_signals  JSONB        NOT NULL DEFAULT '[]'::jsonb,
+  created_at   TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
+
+ALTER TABLE tss.alt_scores ADD COLUMN IF NOT EXISTS top_signals JSONB       NOT NULL DEFAULT '[]'::jsonb;
+ALTER TABLE tss.alt_scores ADD COLUMN IF NOT EXISTS created_at  TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS idx_alt_scores_gid_uid_created
+  ON tss.alt_scores (guild_id, user_id, created_at DESC);
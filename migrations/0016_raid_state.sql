@@ -0,0 +1,11 @@
+-- 0016_raid_state.sql
+-- Aktywny tryb raidu per gildia: co zmieniliśmy (poprzedni poziom weryfikacji, slowmode kanałów),
+-- żeby po restarcie bota dało się przywrócić serwer. Wiersz znika po zakończeniu raidu.
+
+CREATE SCHEMA IF NOT EXISTS tss;
+
+CREATE TABLE IF NOT EXISTS tss.raid_state (
+  guild_id    BIGINT       PRIMARY KEY,
+  state       JSONB        NOT NULL,
+  started_at  TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
@@ -1,19 +1,21 @@
 // src/admcheck.rs
 
-use std::collections::HashSet;
 use anyhow::Result;
 use serenity::all::*;
 use sqlx::{Pool, Postgres};
 
-use crate::AppContext;
-use crate::registry::roles::core::{WLASCICIEL, WSPOL_WLASCICIEL, TECHNIK_ZARZAD, OPIEKUN};
+use crate::discord::router::Routes;
+use crate::permissions::{self, Permission};
+use crate::{warn, AppContext};
 
 pub struct AdmCheck;
 
 impl AdmCheck {
-    pub async fn register_commands(ctx: &Context, gid: GuildId) -> Result<()> {
-        gid.create_command(
-            &ctx.http,
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["admcheck"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
             CreateCommand::new("admcheck")
                 .description("Podgląd danych administratora (tylko: Właściciel / Współwłaściciel / Technik / Opiekun).")
                 .add_option(
@@ -21,9 +23,7 @@ impl AdmCheck {
                         .required(true),
                 )
                 .default_member_permissions(Permissions::MODERATE_MEMBERS),
-        )
-        .await?;
-        Ok(())
+        ]
     }
 
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
@@ -46,13 +46,8 @@ async fn handle_admcheck(ctx: &Context, app: &AppContext, cmd: &CommandInteracti
         return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
     };
 
-    if !is_staff_whitelisted(ctx, gid, cmd.user.id).await {
-        return edit_ephemeral(
-            ctx,
-            cmd,
-            "⛔ Tylko właściciel / współwłaściciel / technik / opiekun administracji.",
-        )
-        .await;
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Admcheck) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
     // target
@@ -96,6 +91,8 @@ async fn handle_admcheck(ctx: &Context, app: &AppContext, cmd: &CommandInteracti
         fetch_points_and_photos(&app.db, gid.get(), uid.get()).await;
 
     let warns_given = count_warns_given(&app.db, gid.get(), uid.get()).await;
+    let decay_days  = warn::load_warn_config(&app.db, gid.get()).await.decay_days;
+    let warns_active = count_active_warns_given(&app.db, gid.get(), uid.get(), decay_days).await;
     let bans_given  = count_bans_given(&app.db, gid.get(), uid.get()).await;
 
     // ====== EMBED ======
@@ -123,6 +120,7 @@ async fn handle_admcheck(ctx: &Context, app: &AppContext, cmd: &CommandInteracti
         )
         .field("Zweryfikowane zdjęcia", photos_verified.to_string(), true)
         .field("Warny (nadane)", warns_given.to_string(), true)
+        .field(format!("Warny aktywne ({decay_days} dni)"), warns_active.to_string(), true)
         .field("Bany (nadane)", bans_given.to_string(), true)
         .field("Punkty admina", format!("{:.1}", points), true)
         .footer(CreateEmbedFooter::new("Tigris AdmCheck"));
@@ -346,7 +344,35 @@ async fn count_warns_given(db: &Pool<Postgres>, gid: u64, uid: u64) -> i64 {
     0
 }
 
+/// Warny nadane przez moderatora, które jeszcze nie wygasły (okres z `warn_config`).
+async fn count_active_warns_given(db: &Pool<Postgres>, gid: u64, uid: u64, decay_days: i32) -> i64 {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*)::bigint FROM tss.warn_cases \
+         WHERE guild_id=$1 AND moderator_id=$2 AND deleted_at IS NULL \
+           AND created_at >= now() - make_interval(days => $3)",
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .bind(decay_days)
+    .fetch_one(db)
+    .await
+    .unwrap_or(0)
+}
+
 async fn count_bans_given(db: &Pool<Postgres>, gid: u64, uid: u64) -> i64 {
+    // wspólny rejestr spraw (0007_cases.sql)
+    if pg_has_table(db, "tss.cases").await {
+        return sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*)::bigint FROM tss.cases \
+             WHERE guild_id=$1 AND moderator_id=$2 AND action='BAN' AND deleted_at IS NULL",
+        )
+        .bind(gid as i64)
+        .bind(uid as i64)
+        .fetch_one(db)
+        .await
+        .unwrap_or(0);
+    }
+
     if pg_has_table(db, "tss.ban_cases").await {
         let mod_col = if table_has_column(db, "tss", "ban_cases", "moderator_id").await {
             "moderator_id"
@@ -414,86 +440,7 @@ async fn count_bans_given(db: &Pool<Postgres>, gid: u64, uid: u64) -> i64 {
     0
 }
 
-/* ───────────────────────── permissions / misc ───────────────────────── */
-
-async fn is_staff_whitelisted(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    // owner zawsze może
-    if let Ok(g) = gid.to_partial_guild(&ctx.http).await {
-        if g.owner_id == uid {
-            return true;
-        }
-    }
-
-    let member = match gid.member(&ctx.http, uid).await {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
-
-    // role z registry (0 oznacza „nie ustawiono”)
-    let mut wanted: HashSet<RoleId> = HashSet::new();
-    for rid in [WLASCICIEL, WSPOL_WLASCICIEL, TECHNIK_ZARZAD, OPIEKUN] {
-        if rid != 0 {
-            wanted.insert(RoleId::new(rid));
-        }
-    }
-
-    // fallback po nazwach, gdy nie skonfigurowano ID
-    if wanted.is_empty() {
-        if let Ok(all) = gid.roles(&ctx.http).await {
-            for (rid, role) in all {
-                let name = normalize(&role.name);
-                if name.contains("wlasciciel")
-                    || name.contains("wspolwlasciciel")
-                    || name.contains("technik")
-                    || name.contains("zarzad")
-                    || name.contains("opiekunadministracji")
-                    || name == "opiekun"
-                {
-                    wanted.insert(rid);
-                }
-            }
-        }
-    }
-
-    if wanted.is_empty() {
-        return false;
-    }
-
-    let have: HashSet<RoleId> = member.roles.into_iter().collect();
-    if wanted.iter().any(|r| have.contains(r)) {
-        return true;
-    }
-
-    // opcjonalnie: administrator ma dostęp
-    if let Ok(perms) = gid.member(&ctx.http, uid).await.and_then(|m| m.permissions(&ctx.cache)) {
-        if perms.administrator() {
-            return true;
-        }
-    }
-
-    false
-}
-
-fn normalize(s: &str) -> String {
-    let lower = s.to_lowercase();
-    let mut out = String::with_capacity(lower.len());
-    for ch in lower.chars() {
-        let mapped = match ch {
-            'ą' => 'a',
-            'ć' => 'c',
-            'ę' => 'e',
-            'ł' => 'l',
-            'ń' => 'n',
-            'ó' => 'o',
-            'ś' => 's',
-            'ź' | 'ż' => 'z',
-            ' ' | '-' | '_' | '/' | '\\' | '.' => continue,
-            _ => ch,
-        };
-        out.push(mapped);
-    }
-    out
-}
+/* ───────────────────────── misc ───────────────────────── */
 
 async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
     cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
@@ -18,11 +18,13 @@ use serenity::all::{
     ComponentInteraction, ComponentInteractionDataKind, Context, CreateActionRow, CreateCommand,
     CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
     CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind,
-    CreateSelectMenuOption, GuildId, Interaction, Member, Timestamp, UserId,
+    CreateSelectMenuOption, GuildId, Interaction, Timestamp, UserId,
 };
 
+use crate::discord::router::Routes;
 use crate::{
-    registry::{channels, env_roles},
+    permissions::{self, Permission},
+    registry::{env_channels, env_roles},
     AppContext,
 };
 
@@ -47,7 +49,24 @@ PUBLIC API (moduł)
 
 pub struct AdminPoints;
 
+/// Ręczna korekta punktów (kto, komu, ile, dlaczego).
+pub struct ManualAdjust<'a> {
+    pub actor_id: u64,
+    /// Wynik `permissions::authorize(.., Permission::PunktyAdjust)` dla aktora.
+    pub actor_authorized: bool,
+    pub target_user_id: u64,
+    pub delta_points: f64,
+    pub reason: &'a str,
+}
+
 impl AdminPoints {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &[SLASH_NAME],
+        components: &[UI_SELECT_ID],
+        modals: &[],
+    };
+
     /* ---------- bootstrapping / migracje ---------- */
 
     pub async fn ensure_tables(db: &Pool<Postgres>) -> Result<()> {
@@ -144,27 +163,22 @@ impl AdminPoints {
         .await
     }
 
-    /// Ręczna modyfikacja (±) – Właściciel/Opiekun
+    /// Ręczna modyfikacja (±) – tylko z capability `punkty-adjust`
     pub async fn adjust_manual(
         db: &Pool<Postgres>,
-        env: &str,
-        actor_id: u64,
-        actor_roles: &[u64],
-        target_user_id: u64,
-        delta_points: f64,
-        reason: &str,
+        adj: &ManualAdjust<'_>,
     ) -> Result<f64> {
-        if !can_adjust_manually(env, actor_roles) {
+        if !adj.actor_authorized {
             anyhow::bail!("Brak uprawnień do ręcznej zmiany punktów.");
         }
-        let delta_milli = points_to_milli(delta_points);
+        let delta_milli = points_to_milli(adj.delta_points);
         Self::apply_delta(
             db,
-            target_user_id,
+            adj.target_user_id,
             delta_milli,
-            Some(actor_id),
+            Some(adj.actor_id),
             "MANUAL",
-            Some(reason),
+            Some(adj.reason),
         )
         .await
     }
@@ -172,105 +186,101 @@ impl AdminPoints {
     /* ---------- routing interakcji ---------- */
 
     /// Rejestracja /punkty (per-guild)
-    pub async fn register_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-        guild_id
-            .create_command(
-                &ctx.http,
-                CreateCommand::new(SLASH_NAME)
-                    .description("Punkty administracji (podgląd + add + remove + clear + profil).")
-                    // /punkty add user amount reason?
-                    .add_option(
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new(SLASH_NAME)
+                .description("Punkty administracji (podgląd + add + remove + clear + profil).")
+                // /punkty add user amount reason?
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "add",
+                        "Dodaj punkty administratorowi",
+                    )
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "add",
-                            "Dodaj punkty administratorowi",
-                        )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::User,
-                                "user",
-                                "Komu przyznać punkty",
-                            )
-                            .required(true),
+                            CommandOptionType::User,
+                            "user",
+                            "Komu przyznać punkty",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::Number,
-                                "amount",
-                                "Ile punktów dodać (np. 0.3)",
-                            )
-                            .required(true),
-                        )
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "reason",
-                            "Powód (opcjonalnie)",
-                        )),
+                        .required(true),
                     )
-                    // /punkty remove user amount reason?
-                    .add_option(
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "remove",
-                            "Odejmij punkty administratorowi",
+                            CommandOptionType::Number,
+                            "amount",
+                            "Ile punktów dodać (np. 0.3)",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::User,
-                                "user",
-                                "Komu odjąć punkty",
-                            )
-                            .required(true),
+                        .required(true),
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "reason",
+                        "Powód (opcjonalnie)",
+                    )),
+                )
+                // /punkty remove user amount reason?
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "remove",
+                        "Odejmij punkty administratorowi",
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::User,
+                            "user",
+                            "Komu odjąć punkty",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::Number,
-                                "amount",
-                                "Ile punktów odjąć (np. 0.3)",
-                            )
-                            .required(true),
+                        .required(true),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Number,
+                            "amount",
+                            "Ile punktów odjąć (np. 0.3)",
                         )
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "reason",
-                            "Powód (opcjonalnie)",
-                        )),
+                        .required(true),
                     )
-                    // /punkty clear user
-                    .add_option(
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "reason",
+                        "Powód (opcjonalnie)",
+                    )),
+                )
+                // /punkty clear user
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "clear",
+                        "Wyczyść punkty administratora do zera",
+                    )
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "clear",
-                            "Wyczyść punkty administratora do zera",
+                            CommandOptionType::User,
+                            "user",
+                            "Komu wyczyścić punkty",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::User,
-                                "user",
-                                "Komu wyczyścić punkty",
-                            )
-                            .required(true),
-                        ),
+                        .required(true),
+                    ),
+                )
+                // /punkty profil user
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "profil",
+                        "Pokaż profil punktowy wskazanego administratora",
                     )
-                    // /punkty profil user
-                    .add_option(
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "profil",
-                            "Pokaż profil punktowy wskazanego administratora",
+                            CommandOptionType::User,
+                            "user",
+                            "Kogo profil wyświetlić",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::User,
-                                "user",
-                                "Kogo profil wyświetlić",
-                            )
-                            .required(true),
-                        ),
+                        .required(true),
                     ),
-            )
-            .await?;
-        Ok(())
+                ),
+        ]
     }
 
     /// Router interakcji: slash + komponent select
@@ -401,31 +411,6 @@ fn points_to_milli(p: f64) -> i64 {
     (p * (POINTS_SCALE as f64)).round() as i64
 }
 
-/// Ręczne korekty – **tylko** Owner i Opiekun
-fn can_adjust_manually(env: &str, actor_roles: &[u64]) -> bool {
-    let allowed = [env_roles::owner_id(env), env_roles::opiekun_id(env)];
-    actor_roles.iter().any(|r| allowed.contains(r))
-}
-
-/// Uprawnienia do **oglądania profilu / UI**:
-/// wyłącznie: Administrator (permission) **lub** role: test_moderator / moderator / admin.
-async fn is_points_view_allowed(ctx: &Context, gid: GuildId, env: &str, user_id: UserId) -> bool {
-    if let Ok(member) = gid.member(&ctx.http, user_id).await {
-        // role
-        let eligible = [
-            env_roles::test_moderator_id(env),
-            env_roles::moderator_id(env),
-            env_roles::admin_id(env),
-        ];
-        if member.roles.iter().any(|r| eligible.contains(&r.get())) {
-            return true;
-        }
-        // Administrator (permission bit)
-        return has_administrator(ctx, gid, &member).await;
-    }
-    false
-}
-
 // ------------- ładny profil: kolor, progress bar, ostatnie zmiany -------------
 
 fn colour_hex_for_points(p: f64) -> u32 {
@@ -567,7 +552,7 @@ SLASH /points + komponent
 pub const SLASH_NAME: &str = "punkty";
 const UI_SELECT_ID: &str = "as:punkty:select";
 
-async fn handle_slash(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+async fn handle_slash(ctx: &Context, _app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
     let Some(gid) = cmd.guild_id else {
         cmd.create_response(
             &ctx.http,
@@ -581,9 +566,8 @@ async fn handle_slash(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         return Ok(());
     };
 
-    let env = app.env();
     // ⬇️ zawężone uprawnienia do UI/profilu
-    if !is_points_view_allowed(ctx, gid, &env, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Punkty) {
         cmd.create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
@@ -596,7 +580,7 @@ async fn handle_slash(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         return Ok(());
     }
 
-    let options = build_admin_select_options(ctx, gid, &env).await?;
+    let options = build_admin_select_options(ctx, gid).await?;
     if options.is_empty() {
         cmd.create_response(
             &ctx.http,
@@ -649,10 +633,8 @@ pub async fn handle_points_component(
         return Ok(());
     };
 
-    let env = std::env::var("TSS_ENV").unwrap_or_else(|_| "production".to_string());
-
     // ⬇️ zawężone uprawnienia do UI/profilu
-    if !is_points_view_allowed(ctx, gid, &env, comp.user.id).await {
+    if !permissions::authorize(gid, comp.member.as_ref(), Permission::Punkty) {
         let _ = comp
             .create_response(
                 &ctx.http,
@@ -691,7 +673,7 @@ pub async fn handle_points_component(
     let profile = build_profile_embed(ctx, db, user_id).await?;
 
     // Select – zostaje jak był
-    let options = build_admin_select_options(ctx, gid, &env).await?;
+    let options = build_admin_select_options(ctx, gid).await?;
     let menu = CreateSelectMenu::new(UI_SELECT_ID, CreateSelectMenuKind::String { options })
         .placeholder("Wybierz administratora")
         .min_values(1)
@@ -788,23 +770,17 @@ async fn handle_points_add(
         return Ok(());
     };
 
-    // role aktora
-    let actor_roles: Vec<u64> = match gid.member(&ctx.http, cmd.user.id).await {
-        Ok(m) => m.roles.iter().map(|r| r.get()).collect(),
-        Err(_) => vec![],
-    };
+    // capability aktora (sprawdzana ponownie w adjust_manual)
+    let actor_authorized = permissions::authorize(gid, cmd.member.as_deref(), Permission::PunktyAdjust);
 
-    let env = app.env();
-    match AdminPoints::adjust_manual(
-        &app.db,
-        &env,
-        cmd.user.id.get(),
-        &actor_roles,
-        target_id.get(),
-        amount,
-        reason.as_deref().unwrap_or("Ręczne dodanie punktów"),
-    )
-    .await
+    let adj = ManualAdjust {
+        actor_id: cmd.user.id.get(),
+        actor_authorized,
+        target_user_id: target_id.get(),
+        delta_points: amount,
+        reason: reason.as_deref().unwrap_or("Ręczne dodanie punktów"),
+    };
+    match AdminPoints::adjust_manual(&app.db, &adj).await
     {
         Ok(total) => {
             let text = format!(
@@ -828,15 +804,7 @@ async fn handle_points_add(
             .await?;
 
             // log na kanale ADMINS_POINTS (z nowym stanem)
-            log_points_adjustment(
-                ctx,
-                app,
-                cmd.user.id.get(),
-                target_id.get(),
-                amount,
-                Some(total),
-                reason.as_deref(),
-            )
+            log_points_adjustment(ctx, app, gid, &adj, Some(total))
             .await;
         }
         Err(e) => {
@@ -932,23 +900,17 @@ async fn handle_points_remove(
     };
     let amount_neg = -amount_pos;
 
-    // role aktora
-    let actor_roles: Vec<u64> = match gid.member(&ctx.http, cmd.user.id).await {
-        Ok(m) => m.roles.iter().map(|r| r.get()).collect(),
-        Err(_) => vec![],
-    };
+    // capability aktora (sprawdzana ponownie w adjust_manual)
+    let actor_authorized = permissions::authorize(gid, cmd.member.as_deref(), Permission::PunktyAdjust);
 
-    let env = app.env();
-    match AdminPoints::adjust_manual(
-        &app.db,
-        &env,
-        cmd.user.id.get(),
-        &actor_roles,
-        target_id.get(),
-        amount_neg,
-        reason.as_deref().unwrap_or("Ręczne odjęcie punktów"),
-    )
-    .await
+    let adj = ManualAdjust {
+        actor_id: cmd.user.id.get(),
+        actor_authorized,
+        target_user_id: target_id.get(),
+        delta_points: amount_neg,
+        reason: reason.as_deref().unwrap_or("Ręczne odjęcie punktów"),
+    };
+    match AdminPoints::adjust_manual(&app.db, &adj).await
     {
         Ok(total) => {
             let text = format!(
@@ -972,15 +934,7 @@ async fn handle_points_remove(
             .await?;
 
             // log na kanale ADMINS_POINTS (wartość ujemna) + nowy stan
-            log_points_adjustment(
-                ctx,
-                app,
-                cmd.user.id.get(),
-                target_id.get(),
-                amount_neg,
-                Some(total),
-                reason.as_deref(),
-            )
+            log_points_adjustment(ctx, app, gid, &adj, Some(total))
             .await;
         }
         Err(e) => {
@@ -1061,23 +1015,17 @@ async fn handle_points_clear(
         return Ok(());
     }
 
-    // role aktora
-    let actor_roles: Vec<u64> = match gid.member(&ctx.http, cmd.user.id).await {
-        Ok(m) => m.roles.iter().map(|r| r.get()).collect(),
-        Err(_) => vec![],
-    };
+    // capability aktora (sprawdzana ponownie w adjust_manual)
+    let actor_authorized = permissions::authorize(gid, cmd.member.as_deref(), Permission::PunktyAdjust);
 
-    let env = app.env();
-    match AdminPoints::adjust_manual(
-        &app.db,
-        &env,
-        cmd.user.id.get(),
-        &actor_roles,
-        target_id.get(),
-        -current,
-        "Wyzerowanie punktów",
-    )
-    .await
+    let adj = ManualAdjust {
+        actor_id: cmd.user.id.get(),
+        actor_authorized,
+        target_user_id: target_id.get(),
+        delta_points: -current,
+        reason: "Wyzerowanie punktów",
+    };
+    match AdminPoints::adjust_manual(&app.db, &adj).await
     {
         Ok(_) => {
             cmd.create_response(
@@ -1093,15 +1041,7 @@ async fn handle_points_clear(
             )
             .await?;
 
-            log_points_adjustment(
-                ctx,
-                app,
-                cmd.user.id.get(),
-                target_id.get(),
-                -current,
-                Some(0.0),
-                Some("Wyzerowanie punktów"),
-            )
+            log_points_adjustment(ctx, app, gid, &adj, Some(0.0))
             .await;
         }
         Err(e) => {
@@ -1142,8 +1082,7 @@ async fn handle_points_profil(
     };
 
     // ⬇️ zawężone uprawnienia do PROFILU
-    let env = app.env();
-    if !is_points_view_allowed(ctx, gid, &env, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Punkty) {
         cmd.create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
@@ -1181,7 +1120,7 @@ async fn handle_points_profil(
     };
 
     // ⬇️ HARDLOCK: profil tylko dla członków administracji (role: test/mod/admin)
-    if !is_target_admin_rank_only(ctx, gid, &env, target).await {
+    if !is_target_admin_rank_only(ctx, gid, target).await {
         cmd.create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
@@ -1212,23 +1151,9 @@ async fn handle_points_profil(
 uprawnienia i select
 ========================= */
 
-async fn has_administrator(ctx: &Context, gid: GuildId, member: &Member) -> bool {
-    if let Ok(roles_map) = gid.roles(&ctx.http).await {
-        for rid in &member.roles {
-            if let Some(role) = roles_map.get(rid) {
-                if role.permissions.administrator() {
-                    return true;
-                }
-            }
-        }
-    }
-    false
-}
-
 async fn build_admin_select_options(
     ctx: &Context,
     gid: GuildId,
-    env: &str,
 ) -> Result<Vec<CreateSelectMenuOption>> {
     let members = gid
         .members(&ctx.http, Some(1000), None)
@@ -1237,9 +1162,9 @@ async fn build_admin_select_options(
 
     // tylko osoby z tymi rolami będą widoczne na liście
     let eligible = [
-        env_roles::admin_id(env),
-        env_roles::moderator_id(env),
-        env_roles::test_moderator_id(env),
+        env_roles::admin_id(gid.get()),
+        env_roles::moderator_id(gid.get()),
+        env_roles::test_moderator_id(gid.get()),
     ];
 
     let mut rows: Vec<(String, String, String)> = members
@@ -1290,14 +1215,13 @@ pub fn is_points_component(comp: &ComponentInteraction) -> bool {
 async fn is_target_admin_rank_only(
     ctx: &Context,
     gid: GuildId,
-    env: &str,
     user_id: UserId,
 ) -> bool {
     if let Ok(member) = gid.member(&ctx.http, user_id).await {
         let eligible = [
-            env_roles::test_moderator_id(env),
-            env_roles::moderator_id(env),
-            env_roles::admin_id(env),
+            env_roles::test_moderator_id(gid.get()),
+            env_roles::moderator_id(gid.get()),
+            env_roles::admin_id(gid.get()),
         ];
         return member.roles.iter().any(|r| eligible.contains(&r.get()));
     }
@@ -1308,16 +1232,6 @@ async fn is_target_admin_rank_only(
 LOG kanał: ADMINS_POINTS
 ========================= */
 
-fn admin_points_log_channel_id(env: &str) -> u64 {
-    // Mapowanie per-ENV na stałą z registry.rs
-    let prod = env.eq_ignore_ascii_case("production") || env.eq_ignore_ascii_case("prod");
-    if prod {
-        channels::prod::ADMINS_POINTS
-    } else {
-        channels::dev::ADMINS_POINTS
-    }
-}
-
 fn colour_for_delta(delta: f64) -> u32 {
     if delta > 0.0 {
         0x2ecc71
@@ -1335,14 +1249,12 @@ fn colour_for_delta(delta: f64) -> u32 {
 async fn log_points_adjustment(
     ctx: &Context,
     app: &AppContext,
-    actor_id: u64,
-    target_id: u64,
-    delta_points: f64,
+    gid: GuildId,
+    adj: &ManualAdjust<'_>,
     new_total_opt: Option<f64>, // nowy stan (jeśli znany)
-    reason: Option<&str>,
 ) {
-    let env = app.env();
-    let chan_id = admin_points_log_channel_id(&env);
+    let (actor_id, target_id, delta_points) = (adj.actor_id, adj.target_user_id, adj.delta_points);
+    let chan_id = env_channels::logs::admin_points_id(gid.get());
     if chan_id == 0 {
         return;
     }
@@ -1395,10 +1307,8 @@ async fn log_points_adjustment(
         .field("Postęp", format!("`{}`", bar), false)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
-    if let Some(r) = reason {
-        if !r.trim().is_empty() {
-            embed = embed.field("Powód", format!("_{}_", r.trim()), false);
-        }
+    if !adj.reason.trim().is_empty() {
+        embed = embed.field("Powód", format!("_{}_", adj.reason.trim()), false);
     }
     if let Some(url) = target_ava {
         embed = embed.thumbnail(url);
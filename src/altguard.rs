@@ -6,19 +6,26 @@
 //!   historia banów, *avatar aHash 8×8*, *BehaviorPattern* pierwszych wiadomości)
 //! - Cache okien czasowych, join_times, buffory wiadomości
 //! - Whitelist (mem + DB), zapis wyników do DB (best-effort)
-//! - API: record_join, record_message, score_user, whitelist_{add,remove}, is_whitelisted, warmup_cache, push_punished_*
+//! - API: record_join, record_message, score_user, whitelist_{add,remove}, is_whitelisted, warmup_cache,
+//!   record_punished (ban/kick/długi mute → nazwy + aHash z TTL w bazie), push_punished_*
+//! - Egzekwowanie werdyktu przy JOIN (`enforce`): polityka per werdykt z `AltConfig::enforcement`,
+//!   log z przyciskami Zatwierdź/Kick/Ban/Whitelist (custom_id `altg_*`)
+//! - Profile polityki (lenient/balanced/strict): progi, mnożnik wag i min. sygnałów;
+//!   ręczne nadpisania (`AltConfig::overrides`) wygrywają z profilem – patrz `AltConfig::resolve`
+//! - `joins_last_60s` – próg fali wejść dla trybu raidu (lockdown w src/raid.rs, polityka `AltConfig::raid`)
 //!
-//! Wymagane tabele (best-effort; jeśli ich nie ma, logujemy i działamy dalej):
+//! Tabele (migracje 0014/0015; błędy zapisu logujemy i działamy dalej):
 //!   tss.alt_scores(guild_id BIGINT, user_id BIGINT, score INT, verdict TEXT, top_signals JSONB, created_at TIMESTAMPTZ)
 //!   tss.alt_whitelist(guild_id BIGINT, user_id BIGINT, note TEXT, added_by BIGINT, created_at TIMESTAMPTZ)
-//!   tss.alt_config(guild_id BIGINT PRIMARY KEY, config JSONB)
-//!   tss.cases(...)  -- używane do heurystyki historii i (opcjonalnie) wyciągania nazw
+//!   tss.alt_config(guild_id BIGINT PRIMARY KEY, config JSONB, updated_at TIMESTAMPTZ)
+//!   tss.alt_punished(guild_id, user_id, username, global_name, avatar_hash, action, expires_at)
+//!   tss.cases(...)  -- używane do heurystyki historii
 //!
 //! Uwaga: aHash liczymy z avatara (URL); jeśli brak/wykrzaczy się pobieranie, po prostu pomijamy ten sygnał.
 
 use std::{
     cmp::{max, min},
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -30,10 +37,19 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres, Row};
 use tokio::sync::Mutex;
+use serenity::all::{
+    ButtonStyle, ChannelId, Colour, ComponentInteraction, Context, CreateActionRow, CreateAllowedMentions,
+    CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+    CreateMessage, EditInteractionResponse, EditMember, GuildId, Http, Interaction, RoleId, UserId,
+};
 use tracing::debug;
 use url::Url;
 
-use crate::AppContext;
+use crate::cases::{self, CaseAction, NewCase};
+use crate::discord::router::Routes;
+use crate::permissions::{self, Permission};
+use crate::registry::{env_channels, env_roles};
+use crate::{ban, invites, kick, AppContext};
 
 /* ==============================
    Konfiguracja i typy publiczne
@@ -56,15 +72,90 @@ struct VerifiedFP {
     at: Instant,
 }
 
+/// Progi, mnożnik wag i `min_signals_for_auto` wynikają z `profile`; jawne `overrides`
+/// mają pierwszeństwo. Scoring czyta wartości z `resolve()`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredAltConfig")]
 pub struct AltConfig {
     pub enabled: bool,
-    pub thresholds: Thresholds,
-    pub weights: Weights,
-    pub min_signals_for_auto: u8,
+    pub weights: Weights, // bazowe – profil je skaluje
     pub raidaware_enabled: bool,
     pub raidaware_join_per_60s: u32,
     pub profile: PolicyProfile,
+    #[serde(default)]
+    pub overrides: Overrides,
+    #[serde(default)]
+    pub enforcement: Enforcement,
+    #[serde(default)]
+    pub raid: RaidPolicy,
+}
+
+/// Wiersz tss.alt_config w dowolnej wersji. Starsze zapisy trzymały progi i minimum
+/// sygnałów wprost (`thresholds`, `min_signals_for_auto`) – odbiegające od profilu
+/// przenosimy do `overrides`, żeby ręczne ustawienia przetrwały.
+#[derive(Deserialize)]
+struct StoredAltConfig {
+    enabled: bool,
+    weights: Weights,
+    raidaware_enabled: bool,
+    raidaware_join_per_60s: u32,
+    profile: PolicyProfile,
+    #[serde(default)]
+    overrides: Overrides,
+    #[serde(default)]
+    enforcement: Enforcement,
+    #[serde(default)]
+    raid: RaidPolicy,
+    #[serde(default)]
+    thresholds: Option<Thresholds>,
+    #[serde(default)]
+    min_signals_for_auto: Option<u8>,
+}
+
+impl From<StoredAltConfig> for AltConfig {
+    fn from(c: StoredAltConfig) -> Self {
+        let p = c.profile.preset();
+        let mut overrides = c.overrides;
+        if let Some(t) = c.thresholds {
+            if t.low != p.low {
+                overrides.low.get_or_insert(t.low);
+            }
+            if t.high != p.high {
+                overrides.high.get_or_insert(t.high);
+            }
+        }
+        if let Some(n) = c.min_signals_for_auto.filter(|n| *n != p.min_signals) {
+            overrides.min_signals.get_or_insert(n);
+        }
+        Self {
+            enabled: c.enabled,
+            weights: c.weights,
+            raidaware_enabled: c.raidaware_enabled,
+            raidaware_join_per_60s: c.raidaware_join_per_60s,
+            profile: c.profile,
+            overrides,
+            enforcement: c.enforcement,
+            raid: c.raid,
+        }
+    }
+}
+
+/// Pola ustawione ręcznie (`/altguard config set`) – wygrywają z profilem.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Overrides {
+    pub low: Option<u8>,
+    pub high: Option<u8>,
+    pub min_signals: Option<u8>,
+    pub weights: BTreeMap<String, i32>, // klucze z `Weights::NAMES`, wartość bez mnożnika
+}
+
+/// Efektywna polityka scoringu: profil + nadpisania.
+#[derive(Debug, Clone)]
+pub struct ResolvedPolicy {
+    pub thresholds: Thresholds,
+    pub weights: Weights,
+    pub min_signals_for_auto: u8,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,7 +164,7 @@ pub struct Thresholds {
     pub high: u8, // >= high => HIGH/quarantine
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Weights {
     // klasyczne
     pub account_age_max: i32,
@@ -89,18 +180,190 @@ pub struct Weights {
     pub behavior_pattern_max: i32, // wzorzec pierwszych wiadomości (0..15)
 }
 
+/// Automatyczna akcja po werdykcie przy wejściu na serwer (cofana przyciskiem „Zatwierdź” z logu).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AltAction {
+    Log,        // tylko log z przyciskami
+    Quarantine, // rola z rejestru `role.quarantine`
+    Timeout,    // `Enforcement::timeout_minutes`
+    Kick,
+}
+
+impl AltAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AltAction::Log => "log",
+            AltAction::Quarantine => "quarantine",
+            AltAction::Timeout => "timeout",
+            AltAction::Kick => "kick",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "log" | "none" => Some(AltAction::Log),
+            "quarantine" => Some(AltAction::Quarantine),
+            "timeout" => Some(AltAction::Timeout),
+            "kick" => Some(AltAction::Kick),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AltAction::Log => "📝 Tylko log",
+            AltAction::Quarantine => "🚧 Kwarantanna",
+            AltAction::Timeout => "⏳ Timeout",
+            AltAction::Kick => "👢 Kick",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct VerdictPolicy {
+    pub action: AltAction,
+    pub ping_staff: bool, // wzmianka roli moderatora w logu
+}
+
+/// Polityka per werdykt (LOW nie wywołuje akcji).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Enforcement {
+    pub medium: VerdictPolicy,
+    pub high: VerdictPolicy,
+    pub timeout_minutes: u32,
+}
+
+impl Default for Enforcement {
+    fn default() -> Self {
+        Self {
+            medium: VerdictPolicy { action: AltAction::Quarantine, ping_staff: true },
+            high: VerdictPolicy { action: AltAction::Timeout, ping_staff: true },
+            timeout_minutes: 60,
+        }
+    }
+}
+
+/// Tryb raidu (src/raid.rs): włącza się, gdy wejść w 60 s ≥ `raidaware_join_per_60s`.
+/// Każdy nowy członek trafia wtedy do kwarantanny; pozostałe kroki są opcjonalne.
+/// Domyślnie wyłączony – gildia włącza go sama (`/altguard config set raid.lockdown on`),
+/// a podniesienie poziomu weryfikacji (telefon) to osobna zgoda.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RaidPolicy {
+    pub lockdown: bool,      // automatyczny start trybu raidu
+    pub verification: bool,  // podniesienie poziomu weryfikacji gildii
+    pub pause_verify: bool,  // wstrzymanie panelu weryfikacji
+    pub slowmode_secs: u16,  // slowmode na czatach publicznych (0 = bez zmian)
+    pub quiet_minutes: u32,  // cisza (bez wejść), po której zdejmujemy lockdown
+}
+
+impl Default for RaidPolicy {
+    fn default() -> Self {
+        Self { lockdown: false, verification: false, pause_verify: true, slowmode_secs: 30, quiet_minutes: 15 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PolicyProfile {
     Lenient,
     Balanced,
     Strict,
 }
 
+/// Wartości profilu: progi, mnożnik wag (w %) i minimum dodatnich sygnałów.
+pub struct ProfilePreset {
+    pub low: u8,
+    pub high: u8,
+    pub weight_pct: i32,
+    pub min_signals: u8,
+}
+
+impl PolicyProfile {
+    pub fn preset(self) -> ProfilePreset {
+        match self {
+            PolicyProfile::Lenient => ProfilePreset { low: 50, high: 80, weight_pct: 80, min_signals: 3 },
+            PolicyProfile::Balanced => ProfilePreset { low: 40, high: 70, weight_pct: 100, min_signals: 2 },
+            PolicyProfile::Strict => ProfilePreset { low: 30, high: 55, weight_pct: 125, min_signals: 1 },
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PolicyProfile::Lenient => "lenient",
+            PolicyProfile::Balanced => "balanced",
+            PolicyProfile::Strict => "strict",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lenient" => Some(PolicyProfile::Lenient),
+            "balanced" => Some(PolicyProfile::Balanced),
+            "strict" => Some(PolicyProfile::Strict),
+            _ => None,
+        }
+    }
+}
+
+impl Weights {
+    /// Nazwy wag (klucze `Overrides::weights` i `/altguard config set weight.*`).
+    pub const NAMES: [&'static str; 9] = [
+        "account_age",
+        "burst_60s",
+        "burst_10m",
+        "invite_affinity",
+        "name_similarity",
+        "history_base",
+        "trusted_relief",
+        "avatar_ahash",
+        "behavior_pattern",
+    ];
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut i32> {
+        Some(match name {
+            "account_age" => &mut self.account_age_max,
+            "burst_60s" => &mut self.burst_60s_max,
+            "burst_10m" => &mut self.burst_10m_max,
+            "invite_affinity" => &mut self.invite_affinity_max,
+            "name_similarity" => &mut self.name_similarity_max,
+            "history_base" => &mut self.history_base_max,
+            "trusted_relief" => &mut self.trusted_relief,
+            "avatar_ahash" => &mut self.avatar_ahash_max,
+            "behavior_pattern" => &mut self.behavior_pattern_max,
+            _ => return None,
+        })
+    }
+}
+
+impl AltConfig {
+    /// Profil → nadpisania. Mnożnik profilu nie dotyczy ulgi (`trusted_relief`)
+    /// ani wag nadpisanych ręcznie; progi zawsze zostają uporządkowane (LOW < HIGH).
+    pub fn resolve(&self) -> ResolvedPolicy {
+        let p = self.profile.preset();
+        let mut weights = self.weights.clone();
+        for name in Weights::NAMES {
+            let Some(w) = weights.get_mut(name) else { continue };
+            *w = match self.overrides.weights.get(name) {
+                Some(v) => *v,
+                None if name == "trusted_relief" => *w,
+                None => (*w * p.weight_pct + 50) / 100,
+            };
+        }
+        let high = self.overrides.high.unwrap_or(p.high).clamp(1, 100);
+        let low = self.overrides.low.unwrap_or(p.low).min(high - 1);
+        ResolvedPolicy {
+            thresholds: Thresholds { low, high },
+            weights,
+            min_signals_for_auto: self.overrides.min_signals.unwrap_or(p.min_signals),
+        }
+    }
+}
+
 impl Default for AltConfig {
     fn default() -> Self {
         Self {
             enabled: true,
-            thresholds: Thresholds { low: 40, high: 70 },
             weights: Weights {
                 account_age_max: 20,
                 burst_60s_max: 20,
@@ -112,10 +375,12 @@ impl Default for AltConfig {
                 avatar_ahash_max: 20,
                 behavior_pattern_max: 15,
             },
-            min_signals_for_auto: 2,
             raidaware_enabled: true,
             raidaware_join_per_60s: 8,
             profile: PolicyProfile::Balanced,
+            overrides: Overrides::default(),
+            enforcement: Enforcement::default(),
+            raid: RaidPolicy::default(),
         }
     }
 }
@@ -152,7 +417,7 @@ pub struct AltSignal {
 pub struct AltScore {
     pub score: u8,
     pub verdict: AltVerdict,
-    pub top_signals: Vec<AltSignal>,
+    pub top_signals: Vec<AltSignal>, // wszystkie sygnały, malejąco po wadze (do bazy idzie top 8)
     pub explain: String,
 }
 
@@ -169,6 +434,14 @@ pub struct ScoreInput {
     pub avatar_url: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct WhitelistEntry {
+    pub user_id: u64,
+    pub note: String,
+    pub added_by: Option<u64>,
+    pub created_unix: i64,
+}
+
 #[derive(Debug, Clone)]
 pub struct JoinMeta {
     pub guild_id: u64,
@@ -182,12 +455,25 @@ pub struct JoinMeta {
    Stan wewnętrzny AltGuard
    ============================== */
 
+/// Jak długo tożsamość ukaranego zasila NameSimilarity / AvatarPHash.
+const PUNISHED_TTL: Duration = Duration::from_secs(14 * 24 * 3600);
+const PUNISHED_MAX: usize = 200;
+
 #[derive(Debug, Clone)]
 struct PunishedProfile {
+    /// Ukarany użytkownik (None – wpis dodany z zewnątrz bez ID).
+    user_id: Option<u64>,
     username_norm: String,
     when_instant: Instant,
 }
 
+#[derive(Debug, Clone)]
+struct PunishedAvatar {
+    user_id: Option<u64>,
+    ahash: u64,
+    when_instant: Instant,
+}
+
 struct GuildJoinWindows {
     total_60s: Mutex<VecDeque<Instant>>,
     total_10m: Mutex<VecDeque<Instant>>,
@@ -222,7 +508,7 @@ pub struct AltGuard {
     guild_windows: DashMap<u64, Arc<GuildJoinWindows>>,
     join_times: DashMap<(u64, u64), Instant>, // (guild,user) -> kiedy dołączył
     punished_names: DashMap<u64, Arc<Mutex<Vec<PunishedProfile>>>>,
-    punished_avatars: DashMap<u64, Arc<Mutex<Vec<(u64 /*aHash*/, Instant)>>>>, // per-guild
+    punished_avatars: DashMap<u64, Arc<Mutex<Vec<PunishedAvatar>>>>, // per-guild
     msg_buffers: DashMap<(u64, u64), Arc<Mutex<VecDeque<MessageFP>>>>, // (guild,user)->ostatnie N
     recent_verified: DashMap<u64, Arc<Mutex<Vec<VerifiedFP>>>>,
 
@@ -346,21 +632,31 @@ impl AltGuard {
                 self.whitelist_mem.insert((guild_id, uid), ());
             }
         }
-        if let Ok(list) = load_recent_punished_names(self.db(), guild_id, 200).await {
-            let vec = list
-                .into_iter()
-                .map(|name| PunishedProfile {
-                    username_norm: normalize_name(&name),
-                    when_instant: Instant::now(),
-                })
-                .collect::<Vec<_>>();
-            self.punished_names
-                .insert(guild_id, Arc::new(Mutex::new(vec)));
-        }
-        // avatary: zaczynamy pustą listę; będą wpadać podczas kar przez push_punished_avatar_hash_*
-        self.punished_avatars
-            .entry(guild_id)
-            .or_insert_with(|| Arc::new(Mutex::new(Vec::with_capacity(200))));
+        // ukarani z tss.alt_punished – wiek wpisu zachowany, więc TTL liczy się od kary, nie od restartu
+        match load_punished_db(self.db(), guild_id, PUNISHED_MAX as i64).await {
+            Ok(rows) => {
+                let now = Instant::now();
+                let mut names = Vec::with_capacity(PUNISHED_MAX);
+                let mut avatars = Vec::with_capacity(PUNISHED_MAX);
+                // od najstarszych – tak jak dopisuje push_*
+                for r in rows.into_iter().rev() {
+                    let when = now.checked_sub(Duration::from_secs(r.age_secs)).unwrap_or(now);
+                    for name in std::iter::once(&r.username).chain(r.global_name.iter()) {
+                        names.push(PunishedProfile {
+                            user_id: Some(r.user_id),
+                            username_norm: normalize_name(name),
+                            when_instant: when,
+                        });
+                    }
+                    if let Some(h) = r.avatar_hash {
+                        avatars.push(PunishedAvatar { user_id: Some(r.user_id), ahash: h, when_instant: when });
+                    }
+                }
+                self.punished_names.insert(guild_id, Arc::new(Mutex::new(names)));
+                self.punished_avatars.insert(guild_id, Arc::new(Mutex::new(avatars)));
+            }
+            Err(e) => debug!(err=?e, "load_punished_db failed (ok to ignore if table missing)"),
+        }
     }
 
     /* --------- JOIN & MESSAGE rekordery --------- */
@@ -464,11 +760,12 @@ impl AltGuard {
             });
         }
 
+        let policy = cfg.resolve();
         let mut signals: Vec<AltSignal> = Vec::with_capacity(10);
 
         // A) Wiek konta
         if let Some(age) = account_age_hours(input.user_id) {
-            let w = weight_account_age(age, cfg.weights.account_age_max);
+            let w = weight_account_age(age, policy.weights.account_age_max);
             if w > 0 {
                 signals.push(AltSignal {
                     kind: AltSignalKind::AccountAge,
@@ -485,8 +782,8 @@ impl AltGuard {
                 let c10 = gw.total_10m.lock().await.len() as u32;
                 (c60, c10)
             };
-            let w60 = weight_burst_count(c60, cfg.weights.burst_60s_max);
-            let w10 = weight_burst10_count(c10, cfg.weights.burst_10m_max);
+            let w60 = weight_burst_count(c60, policy.weights.burst_60s_max);
+            let w10 = weight_burst10_count(c10, policy.weights.burst_10m_max);
             if w60 > 0 {
                 signals.push(AltSignal {
                     kind: AltSignalKind::Burst60s,
@@ -519,7 +816,7 @@ impl AltGuard {
                         .unwrap_or(0);
                     (i60, i10)
                 };
-                let wi = weight_invite_affinity(i60, i10, cfg.weights.invite_affinity_max);
+                let wi = weight_invite_affinity(i60, i10, policy.weights.invite_affinity_max);
                 if wi > 0 {
                     signals.push(AltSignal {
                         kind: AltSignalKind::InviteAffinity,
@@ -532,13 +829,13 @@ impl AltGuard {
 
         // D) Podobieństwo nazw do niedawno ukaranych (z pruningiem TTL)
         if let Some(sim_w) = self
-            .similarity_to_punished(input.guild_id, &collect_names(input))
+            .similarity_to_punished(input.guild_id, input.user_id, &collect_names(input))
             .await?
         {
             if sim_w > 0 {
                 signals.push(AltSignal {
                     kind: AltSignalKind::NameSimilarity,
-                    weight: min(sim_w, cfg.weights.name_similarity_max),
+                    weight: min(sim_w, policy.weights.name_similarity_max),
                     detail: "levenshtein≈ niedawno ukarani".into(),
                 });
             }
@@ -547,7 +844,7 @@ impl AltGuard {
         // G) Historia bazowa: świeże bany w 24h (mały dopalacz)
         if let Ok(recent_bans) = count_recent_bans(self.db(), input.guild_id, 24).await {
             if recent_bans > 0 {
-                let w = min(5 + (recent_bans as i32 * 2), cfg.weights.history_base_max);
+                let w = min(5 + (recent_bans as i32 * 2), policy.weights.history_base_max);
                 signals.push(AltSignal {
                     kind: AltSignalKind::HistoryBase,
                     weight: w,
@@ -560,7 +857,7 @@ impl AltGuard {
         if input.has_trusted_role {
             signals.push(AltSignal {
                 kind: AltSignalKind::TrustedRelief,
-                weight: -cfg.weights.trusted_relief.abs(),
+                weight: -policy.weights.trusted_relief.abs(),
                 detail: "trusted_role=true".into(),
             });
         }
@@ -572,22 +869,21 @@ impl AltGuard {
                     let mut guard = pool.lock().await;
                     // TTL: 14 dni
                     let now = Instant::now();
-                    let ttl = Duration::from_secs(14 * 24 * 3600);
-                    guard.retain(|(_, t)| now.duration_since(*t) <= ttl);
-
-                    if !guard.is_empty() {
-                        let mut best_dist = u32::MAX;
-                        for (h, _) in guard.iter() {
-                            let d = (my_hash ^ *h).count_ones();
-                            if d < best_dist {
-                                best_dist = d;
-                            }
-                        }
+                    let ttl = PUNISHED_TTL;
+                    guard.retain(|p| now.duration_since(p.when_instant) <= ttl);
+
+                    // własny avatar ukaranego (np. po ułaskawieniu) nie jest sygnałem
+                    let best_dist = guard
+                        .iter()
+                        .filter(|p| p.user_id != Some(input.user_id))
+                        .map(|p| (my_hash ^ p.ahash).count_ones())
+                        .min();
+                    if let Some(best_dist) = best_dist {
                         // mapowanie dystansu na wagę
                         let w = if best_dist <= 10 {
-                            cfg.weights.avatar_ahash_max
+                            policy.weights.avatar_ahash_max
                         } else if best_dist <= 14 {
-                            min(10, cfg.weights.avatar_ahash_max)
+                            min(10, policy.weights.avatar_ahash_max)
                         } else {
                             0
                         };
@@ -614,7 +910,7 @@ impl AltGuard {
                     .filter(|m| m.at.duration_since(join_at) <= Duration::from_secs(300))
                     .cloned()
                     .collect::<Vec<_>>();
-                let w = weight_behavior_pattern(&first_5m, cfg.weights.behavior_pattern_max);
+                let w = weight_behavior_pattern(&first_5m, policy.weights.behavior_pattern_max);
                 if w > 0 {
                     signals.push(AltSignal {
                         kind: AltSignalKind::BehaviorPattern,
@@ -639,22 +935,15 @@ impl AltGuard {
         let mut score = total as u8;
 
         // RaidAware progi
-        let raidaware = if cfg.raidaware_enabled {
-            if let Some(gw) = self.guild_windows.get(&input.guild_id) {
-                gw.total_60s.lock().await.len() as u32 >= cfg.raidaware_join_per_60s
-            } else {
-                false
-            }
-        } else {
-            false
-        };
+        let raidaware =
+            cfg.raidaware_enabled && self.joins_last_60s(input.guild_id).await >= cfg.raidaware_join_per_60s;
         let (low, high) = if raidaware {
             (
-                cfg.thresholds.low.saturating_sub(5),
-                cfg.thresholds.high.saturating_sub(10),
+                policy.thresholds.low.saturating_sub(5),
+                policy.thresholds.high.saturating_sub(10),
             )
         } else {
-            (cfg.thresholds.low, cfg.thresholds.high)
+            (policy.thresholds.low, policy.thresholds.high)
         };
 
         let mut verdict = if score >= high {
@@ -687,7 +976,7 @@ impl AltGuard {
             .filter(|s| s.weight > 0 && !matches!(s.kind, AltSignalKind::TrustedRelief))
             .count() as u8;
 
-        if pos_signals < cfg.min_signals_for_auto && verdict != AltVerdict::Low {
+        if pos_signals < policy.min_signals_for_auto && verdict != AltVerdict::Low {
             // zbijamy do poniżej progu low
             score = low.saturating_sub(1);
             verdict = AltVerdict::Low;
@@ -705,11 +994,36 @@ impl AltGuard {
         Ok(AltScore {
             score,
             verdict,
-            top_signals: top_for_db,
+            top_signals: signals_sorted,
             explain,
         })
     }
 
+    /// Wejścia w ostatnich 60 s (okno z `record_join`) – próg raidu.
+    pub async fn joins_last_60s(&self, guild_id: u64) -> u32 {
+        let Some(gw) = self.guild_windows.get(&guild_id).map(|g| g.clone()) else { return 0; };
+        let mut w60 = gw.total_60s.lock().await;
+        prune_older_than(&mut w60, Duration::from_secs(60), Instant::now());
+        w60.len() as u32
+    }
+
+    /* --------- Konfiguracja (/altguard config) --------- */
+
+    /// Konfiguracja gildii z cache (domyślna, jeśli gildia nic nie zapisała).
+    pub fn config(&self, guild_id: u64) -> AltConfig {
+        self.config_cache
+            .get(&guild_id)
+            .map(|e| e.clone())
+            .unwrap_or_default()
+    }
+
+    /// Zapis do tss.alt_config + odświeżenie cache (bez restartu).
+    pub async fn set_config(&self, guild_id: u64, cfg: AltConfig) -> Result<()> {
+        save_config_db(self.db(), guild_id, &cfg).await?;
+        self.config_cache.insert(guild_id, cfg);
+        Ok(())
+    }
+
     /* --------- Whitelist API --------- */
 
     pub async fn whitelist_add(
@@ -745,9 +1059,34 @@ impl AltGuard {
         self.whitelist_mem.contains_key(&(guild_id, user_id))
     }
 
+    /// Najnowsze wpisy whitelisty (z notatką i autorem).
+    pub async fn whitelist_list(&self, guild_id: u64, limit: i64) -> Result<Vec<WhitelistEntry>> {
+        let rows = sqlx::query(
+            r#"SELECT user_id, note, added_by, EXTRACT(EPOCH FROM created_at)::BIGINT AS created_unix
+               FROM tss.alt_whitelist
+               WHERE guild_id = $1
+               ORDER BY created_at DESC
+               LIMIT $2"#,
+        )
+        .bind(guild_id as i64)
+        .bind(limit)
+        .fetch_all(self.db())
+        .await?;
+        let mut out = Vec::with_capacity(rows.len());
+        for r in rows {
+            out.push(WhitelistEntry {
+                user_id: r.try_get::<i64, _>("user_id")? as u64,
+                note: r.try_get::<Option<String>, _>("note")?.unwrap_or_default(),
+                added_by: r.try_get::<Option<i64>, _>("added_by")?.map(|v| v as u64),
+                created_unix: r.try_get("created_unix")?,
+            });
+        }
+        Ok(out)
+    }
+
     /* --------- Integracje z karami (push punished) --------- */
 
-    pub async fn push_punished_name(&self, guild_id: u64, username_like: &str) {
+    pub async fn push_punished_name(&self, guild_id: u64, user_id: Option<u64>, username_like: &str) {
         let list = self
             .punished_names
             .entry(guild_id)
@@ -756,13 +1095,14 @@ impl AltGuard {
         let mut guard = list.lock().await;
         // TTL 14 dni
         let now = Instant::now();
-        let ttl = Duration::from_secs(14 * 24 * 3600);
+        let ttl = PUNISHED_TTL;
         guard.retain(|p| now.duration_since(p.when_instant) <= ttl);
 
-        if guard.len() >= 200 {
+        if guard.len() >= PUNISHED_MAX {
             guard.remove(0);
         }
         guard.push(PunishedProfile {
+            user_id,
             username_norm: normalize_name(username_like),
             when_instant: now,
         });
@@ -775,22 +1115,7 @@ impl AltGuard {
         bytes: &[u8],
     ) -> Result<()> {
         if let Some(h) = ahash_from_bytes(bytes)? {
-            let vec = self
-                .punished_avatars
-                .entry(guild_id)
-                .or_insert_with(|| Arc::new(Mutex::new(Vec::with_capacity(200))))
-                .clone();
-            let mut guard = vec.lock().await;
-
-            // TTL 14 dni
-            let now = Instant::now();
-            let ttl = Duration::from_secs(14 * 24 * 3600);
-            guard.retain(|(_, t)| now.duration_since(*t) <= ttl);
-
-            if guard.len() >= 200 {
-                guard.remove(0);
-            }
-            guard.push((h, now));
+            self.push_punished_avatar_hash(guild_id, None, h).await;
         }
         Ok(())
     }
@@ -802,31 +1127,90 @@ impl AltGuard {
         url: &str,
     ) -> Result<()> {
         if let Some(h) = self.fetch_and_ahash_cached(url).await? {
-            let vec = self
-                .punished_avatars
-                .entry(guild_id)
-                .or_insert_with(|| Arc::new(Mutex::new(Vec::with_capacity(200))))
-                .clone();
-            let mut guard = vec.lock().await;
-
-            // TTL 14 dni
-            let now = Instant::now();
-            let ttl = Duration::from_secs(14 * 24 * 3600);
-            guard.retain(|(_, t)| now.duration_since(*t) <= ttl);
-
-            if guard.len() >= 200 {
-                guard.remove(0);
-            }
-            guard.push((h, now));
+            self.push_punished_avatar_hash(guild_id, None, h).await;
         }
         Ok(())
     }
 
+    async fn push_punished_avatar_hash(&self, guild_id: u64, user_id: Option<u64>, hash: u64) {
+        let vec = self
+            .punished_avatars
+            .entry(guild_id)
+            .or_insert_with(|| Arc::new(Mutex::new(Vec::with_capacity(PUNISHED_MAX))))
+            .clone();
+        let mut guard = vec.lock().await;
+
+        let now = Instant::now();
+        guard.retain(|p| now.duration_since(p.when_instant) <= PUNISHED_TTL);
+
+        if guard.len() >= PUNISHED_MAX {
+            guard.remove(0);
+        }
+        guard.push(PunishedAvatar { user_id, ahash: hash, when_instant: now });
+    }
+
+    /// Potwierdzona kara (ban / kick / długi mute): nazwy i aHash avatara celu trafiają
+    /// od razu do pamięci i do tss.alt_punished (z TTL), więc konto obchodzące bana
+    /// dostaje NameSimilarity / AvatarPHash także po restarcie.
+    /// W tle (pobranie użytkownika i avatara) – nie opóźnia odpowiedzi moderatorowi ani logu.
+    pub fn record_punished(self: &Arc<Self>, ctx: &Context, guild_id: u64, user_id: u64, action: &'static str) {
+        let (ag, http) = (self.clone(), ctx.http.clone());
+        tokio::spawn(async move { ag.store_punished(&http, guild_id, user_id, action).await });
+    }
+
+    async fn store_punished(&self, http: &Http, guild_id: u64, user_id: u64, action: &str) {
+        let user = match UserId::new(user_id).to_user(http).await {
+            Ok(u) => u,
+            Err(e) => {
+                tracing::warn!(error=?e, guild_id, user_id, "altguard record_punished: user fetch failed");
+                return;
+            }
+        };
+
+        self.push_punished_name(guild_id, Some(user_id), &user.name).await;
+        if let Some(global) = &user.global_name {
+            self.push_punished_name(guild_id, Some(user_id), global).await;
+        }
+        let avatar_hash = match user.avatar_url() {
+            Some(url) => self.fetch_and_ahash_cached(&url).await.ok().flatten(),
+            None => None,
+        };
+        if let Some(h) = avatar_hash {
+            self.push_punished_avatar_hash(guild_id, Some(user_id), h).await;
+        }
+
+        let row = PunishedRow {
+            user_id,
+            username: user.name.clone(),
+            global_name: user.global_name.clone(),
+            avatar_hash,
+            age_secs: 0,
+        };
+        if let Err(e) = persist_punished(self.db(), guild_id, user_id, &row, action).await {
+            tracing::warn!(error=?e, guild_id, user_id, "altguard persist_punished failed");
+        }
+    }
+
+    /// Zdjęcie bana (/unban, przyjęte odwołanie): tożsamość ułaskawionego przestaje
+    /// zasilać NameSimilarity / AvatarPHash – w pamięci i w tss.alt_punished.
+    pub async fn forget_punished(&self, guild_id: u64, user_id: u64) {
+        if let Some(list) = self.punished_names.get(&guild_id).map(|v| v.clone()) {
+            list.lock().await.retain(|p| p.user_id != Some(user_id));
+        }
+        if let Some(list) = self.punished_avatars.get(&guild_id).map(|v| v.clone()) {
+            list.lock().await.retain(|p| p.user_id != Some(user_id));
+        }
+        if let Err(e) = delete_punished_db(self.db(), guild_id, user_id).await {
+            tracing::warn!(error=?e, guild_id, user_id, "altguard delete_punished failed");
+        }
+    }
+
     /* --------- Pomocnicze --------- */
 
     async fn similarity_to_punished(
         &self,
         guild_id: u64,
+        user_id: u64,
         candidates: &[String],
     ) -> Result<Option<i32>> {
         let list = if let Some(v) = self.punished_names.get(&guild_id) {
@@ -837,7 +1221,7 @@ impl AltGuard {
         let mut guard = list.lock().await;
         // TTL 14 dni
         let now = Instant::now();
-        let ttl = Duration::from_secs(14 * 24 * 3600);
+        let ttl = PUNISHED_TTL;
         guard.retain(|p| now.duration_since(p.when_instant) <= ttl);
 
         if guard.is_empty() || candidates.is_empty() {
@@ -846,7 +1230,8 @@ impl AltGuard {
         let mut best = 0i32;
         for cand in candidates {
             let c = normalize_name(cand);
-            for p in guard.iter() {
+            // własne nazwy ukaranego (np. po ułaskawieniu) pomijamy
+            for p in guard.iter().filter(|p| p.user_id != Some(user_id)) {
                 let d = levenshtein(&c, &p.username_norm);
                 let len = max(c.len(), p.username_norm.len()) as i32;
                 if len == 0 {
@@ -892,6 +1277,244 @@ impl AltGuard {
     }
 }
 
+/* ==============================
+   Egzekwowanie werdyktów (JOIN)
+   ============================== */
+
+const BRAND_FOOTER: &str = "Tigris Security System™ • AltGuard";
+
+impl AltGuard {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &[],
+        components: &["altg_"],
+        modals: &[],
+    };
+
+    /// MEDIUM/HIGH przy wejściu: akcja z polityki gildii + log z przyciskami na kanale AltGuard.
+    /// Staff jest tylko logowany (bez akcji).
+    pub async fn enforce(&self, ctx: &Context, input: &ScoreInput, score: &AltScore) {
+        let cfg = self
+            .config_cache
+            .get(&input.guild_id)
+            .map(|e| e.clone())
+            .unwrap_or_default();
+        let policy = match score.verdict {
+            AltVerdict::Low => return,
+            AltVerdict::Medium => cfg.enforcement.medium,
+            AltVerdict::High => cfg.enforcement.high,
+        };
+        let (gid, uid) = (input.guild_id, input.user_id);
+
+        let outcome = if permissions::authorize_user(ctx, GuildId::new(gid), UserId::new(uid), Permission::Review).await {
+            "Pominięto – członek staffu.".to_string()
+        } else {
+            match self.apply_action(ctx, input, score, policy.action, cfg.enforcement.timeout_minutes).await {
+                Ok(note) => {
+                    tracing::info!(gid, uid, action = policy.action.as_str(), score = score.score, "altguard action applied");
+                    note
+                }
+                Err(e) => {
+                    tracing::warn!(error=?e, gid, uid, action = policy.action.as_str(), "altguard action failed");
+                    format!("{} – nie udało się: {e}", policy.action.label())
+                }
+            }
+        };
+
+        send_action_log(ctx, input, score, policy, &outcome).await;
+    }
+
+    async fn apply_action(
+        &self,
+        ctx: &Context,
+        input: &ScoreInput,
+        score: &AltScore,
+        action: AltAction,
+        timeout_minutes: u32,
+    ) -> Result<String> {
+        let gid = GuildId::new(input.guild_id);
+        let uid = UserId::new(input.user_id);
+        let reason = format!("AltGuard: podejrzenie multikonta (score {}/100)", score.score);
+        match action {
+            AltAction::Log => Ok(action.label().to_string()),
+            AltAction::Quarantine => {
+                let role = env_roles::quarantine_id(input.guild_id);
+                if role == 0 {
+                    anyhow::bail!("brak roli `role.quarantine` w /registry");
+                }
+                ctx.http.add_member_role(gid, uid, RoleId::new(role), Some(&reason)).await?;
+                Ok(format!("{} (<@&{role}>)", action.label()))
+            }
+            AltAction::Timeout => {
+                let minutes = timeout_minutes.clamp(1, 28 * 24 * 60); // limit Discorda: 28 dni
+                let until = chrono::Utc::now() + chrono::Duration::minutes(minutes as i64);
+                gid.edit_member(
+                    &ctx.http,
+                    uid,
+                    EditMember::new()
+                        .disable_communication_until_datetime(until.into())
+                        .audit_log_reason(&reason),
+                )
+                .await?;
+                let bot = ctx.cache.current_user().id.get();
+                cases::record_case_logged(
+                    &self.ctx.db,
+                    &NewCase {
+                        guild_id: input.guild_id,
+                        user_id: input.user_id,
+                        moderator_id: bot,
+                        action: CaseAction::Timeout,
+                        reason: &reason,
+                        evidence: None,
+                        source: Some("AltGuard"),
+                        reference: None,
+                    },
+                )
+                .await;
+                Ok(format!("{} ({minutes} min)", action.label()))
+            }
+            AltAction::Kick => {
+                let bot = ctx.cache.current_user().id;
+                kick::apply_kick(ctx, &self.ctx, gid, bot, uid, &reason, Some("AltGuard")).await?;
+                Ok(action.label().to_string())
+            }
+        }
+    }
+
+    pub async fn on_interaction(&self, ctx: &Context, _app: &AppContext, interaction: Interaction) {
+        let Some(comp) = interaction.message_component() else { return; };
+        let Some((kind, uid)) = comp
+            .data
+            .custom_id
+            .strip_prefix("altg_")
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(kind, uid)| Some((kind.to_string(), uid.parse::<u64>().ok()?)))
+        else {
+            return;
+        };
+        if let Err(e) = self.on_btn_action(ctx, &comp, &kind, uid).await {
+            tracing::warn!(error=?e, custom_id = %comp.data.custom_id, "altguard button failed");
+        }
+    }
+
+    /// Przyciski z logu: Zatwierdź / Kick / Ban / Whitelist.
+    async fn on_btn_action(&self, ctx: &Context, i: &ComponentInteraction, kind: &str, uid: u64) -> Result<()> {
+        let Some(gid) = i.guild_id else { return Ok(()); };
+        let perm = match kind {
+            "kick" => Permission::Kick,
+            "ban" => Permission::Ban,
+            _ => Permission::Review,
+        };
+        if !permissions::authorize(gid, i.member.as_ref(), perm) {
+            i.create_response(&ctx.http, CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().content("Brak uprawnień.").ephemeral(true)
+            )).await?;
+            return Ok(());
+        }
+        i.create_response(&ctx.http, CreateInteractionResponse::Defer(
+            CreateInteractionResponseMessage::new().ephemeral(true)
+        )).await?;
+
+        let by = i.user.id;
+        let target = UserId::new(uid);
+        let reason = format!("AltGuard: decyzja z logu ({})", by.get());
+        let msg = match kind {
+            "ok" => match release(ctx, gid, target, &reason).await {
+                Ok(note) => format!("✅ <@{uid}> zatwierdzony. {note}"),
+                Err(e) => format!("Nie udało się zdjąć ograniczeń: {e}"),
+            },
+            "wl" => {
+                self.whitelist_add(gid.get(), uid, Some("AltGuard: zatwierdzony z logu"), Some(by.get())).await?;
+                let note = release(ctx, gid, target, &reason).await.unwrap_or_else(|e| format!("(ograniczenia: {e})"));
+                format!("✅ <@{uid}> na whitelist AltGuard. {note}")
+            }
+            "kick" => match kick::apply_kick(ctx, &self.ctx, gid, by, target, "AltGuard: podejrzenie multikonta", Some("AltGuard")).await {
+                Ok(()) => format!("👢 Wyrzucono <@{uid}>."),
+                Err(e) => format!("Nie udało się wyrzucić: {e}"),
+            },
+            "ban" => match ban::apply_auto_ban(ctx, &self.ctx, gid, by, target, "AltGuard: multikonto", Some("AltGuard")).await {
+                Ok(_) => format!("🔨 Zbanowano <@{uid}>."),
+                Err(e) => format!("Nie udało się zbanować: {e}"),
+            },
+            _ => "Nieznana akcja.".to_string(),
+        };
+        tracing::info!(gid = gid.get(), uid, by = by.get(), action = kind, "altguard log decision");
+        i.edit_response(&ctx.http, EditInteractionResponse::new().content(msg)).await?;
+        Ok(())
+    }
+}
+
+/// Zdejmij kwarantannę i timeout (jeśli są).
+async fn release(ctx: &Context, gid: GuildId, uid: UserId, audit: &str) -> Result<String> {
+    let member = gid.member(&ctx.http, uid).await?;
+    let mut notes = Vec::new();
+
+    let role = env_roles::quarantine_id(gid.get());
+    if role != 0 && member.roles.contains(&RoleId::new(role)) {
+        ctx.http.remove_member_role(gid, uid, RoleId::new(role), Some(audit)).await?;
+        notes.push("Zdjęto kwarantannę.");
+    }
+    if member.communication_disabled_until.is_some() {
+        gid.edit_member(&ctx.http, uid, EditMember::new().enable_communication().audit_log_reason(audit))
+            .await?;
+        notes.push("Zdjęto timeout.");
+    }
+    Ok(if notes.is_empty() { "Brak ograniczeń do zdjęcia.".to_string() } else { notes.join(" ") })
+}
+
+async fn send_action_log(ctx: &Context, input: &ScoreInput, score: &AltScore, policy: VerdictPolicy, outcome: &str) {
+    let log_id = env_channels::logs::altguard_id(input.guild_id);
+    if log_id == 0 {
+        return;
+    }
+    let (title, colour) = match score.verdict {
+        AltVerdict::Low => ("AltGuard: Niskie ryzyko", Colour::DARK_GREEN),
+        AltVerdict::Medium => ("AltGuard: Średnie ryzyko", Colour::GOLD),
+        AltVerdict::High => ("AltGuard: Wysokie ryzyko", Colour::RED),
+    };
+    let top = score
+        .top_signals
+        .iter()
+        .take(5)
+        .map(|s| format!("• {:?} ({:+}) – {}", s.kind, s.weight, s.detail))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let uid = input.user_id;
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(format!("<@{uid}> dołączył do serwera."))
+        .field("User ID", format!("`{uid}`"), true)
+        .field("Score", format!("**{}** / 100", score.score), true)
+        .field("Zaproszenie", invites::label(input.invite_code.as_deref(), input.inviter_id), true)
+        .field("Akcja", outcome, false)
+        .field("Wyjaśnienie", &score.explain, false)
+        .field("Sygnały", if top.is_empty() { "–".into() } else { top }, false)
+        .footer(CreateEmbedFooter::new(BRAND_FOOTER))
+        .colour(colour);
+
+    let mut buttons = Vec::with_capacity(4);
+    if policy.action != AltAction::Kick {
+        buttons.push(CreateButton::new(format!("altg_ok:{uid}")).label("✅ Zatwierdź").style(ButtonStyle::Success));
+        buttons.push(CreateButton::new(format!("altg_kick:{uid}")).label("👢 Kick").style(ButtonStyle::Secondary));
+    }
+    buttons.push(CreateButton::new(format!("altg_ban:{uid}")).label("🔨 Ban").style(ButtonStyle::Danger));
+    buttons.push(CreateButton::new(format!("altg_wl:{uid}")).label("🤍 Whitelist").style(ButtonStyle::Primary));
+
+    let mut msg = CreateMessage::new()
+        .embed(embed)
+        .components(vec![CreateActionRow::Buttons(buttons)]);
+    let ping = env_roles::moderator_id(input.guild_id);
+    if policy.ping_staff && ping != 0 {
+        msg = msg
+            .content(format!("<@&{ping}>"))
+            .allowed_mentions(CreateAllowedMentions::new().roles(vec![RoleId::new(ping)]));
+    }
+    if let Err(e) = ChannelId::new(log_id).send_message(&ctx.http, msg).await {
+        tracing::warn!(error=?e, gid = input.guild_id, uid, "altguard action log failed");
+    }
+}
+
 /* ==============================
    Funkcje wag sygnałów
    ============================== */
@@ -1182,6 +1805,20 @@ async fn load_config_db(db: &Pool<Postgres>, guild_id: u64) -> Result<Option<Alt
     }
 }
 
+async fn save_config_db(db: &Pool<Postgres>, guild_id: u64, cfg: &AltConfig) -> Result<()> {
+    let val = serde_json::to_value(cfg)?;
+    sqlx::query(
+        r#"INSERT INTO tss.alt_config (guild_id, config, updated_at)
+           VALUES ($1, $2, now())
+           ON CONFLICT (guild_id) DO UPDATE SET config = EXCLUDED.config, updated_at = now()"#,
+    )
+    .bind(guild_id as i64)
+    .bind(val)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
 async fn load_whitelist_db(db: &Pool<Postgres>, guild_id: u64) -> Result<Vec<u64>> {
     let rows = sqlx::query("SELECT user_id FROM tss.alt_whitelist WHERE guild_id = $1")
         .bind(guild_id as i64)
@@ -1257,7 +1894,7 @@ async fn persist_score(
 async fn count_recent_bans(db: &Pool<Postgres>, guild_id: u64, hours: i64) -> Result<i64> {
     let q = r#"SELECT COUNT(*) AS c
                FROM tss.cases
-               WHERE guild_id = $1 AND action = 'BAN' AND created_at >= (now() - ($2::text || ' hours')::interval)"#;
+               WHERE guild_id = $1 AND action = 'BAN' AND deleted_at IS NULL AND created_at >= (now() - ($2::text || ' hours')::interval)"#;
     let row = sqlx::query(q)
         .bind(guild_id as i64)
         .bind(hours.to_string())
@@ -1267,14 +1904,25 @@ async fn count_recent_bans(db: &Pool<Postgres>, guild_id: u64, hours: i64) -> Re
     Ok(c)
 }
 
-async fn load_recent_punished_names(
-    db: &Pool<Postgres>,
-    guild_id: u64,
-    limit: i64,
-) -> Result<Vec<String>> {
-    let q = r#"SELECT reason
-               FROM tss.cases
-               WHERE guild_id = $1 AND action IN ('BAN','MUTE','TIMEOUT','KICK','WARN')
+/// Wpis tss.alt_punished; `age_secs` = ile minęło od kary.
+struct PunishedRow {
+    user_id: u64,
+    username: String,
+    global_name: Option<String>,
+    avatar_hash: Option<u64>,
+    age_secs: u64,
+}
+
+async fn load_punished_db(db: &Pool<Postgres>, guild_id: u64, limit: i64) -> Result<Vec<PunishedRow>> {
+    // przy okazji sprzątamy wygasłe
+    sqlx::query("DELETE FROM tss.alt_punished WHERE guild_id = $1 AND expires_at <= now()")
+        .bind(guild_id as i64)
+        .execute(db)
+        .await?;
+    let q = r#"SELECT user_id, username, global_name, avatar_hash,
+                      GREATEST(EXTRACT(EPOCH FROM now() - created_at), 0)::BIGINT AS age_secs
+               FROM tss.alt_punished
+               WHERE guild_id = $1 AND expires_at > now()
                ORDER BY created_at DESC
                LIMIT $2"#;
     let rows = sqlx::query(q)
@@ -1282,24 +1930,81 @@ async fn load_recent_punished_names(
         .bind(limit)
         .fetch_all(db)
         .await?;
-    let mut names = Vec::new();
+    let mut out = Vec::with_capacity(rows.len());
     for r in rows {
-        if let Ok(Some(reason)) = r.try_get::<Option<String>, _>("reason") {
-            if let Some(name) = extract_at_name(&reason) {
-                names.push(name.to_string());
-            }
-        }
+        out.push(PunishedRow {
+            user_id: r.try_get::<i64, _>("user_id")? as u64,
+            username: r.try_get("username")?,
+            global_name: r.try_get("global_name")?,
+            // aHash trzymamy w BIGINT bit-w-bit
+            avatar_hash: r.try_get::<Option<i64>, _>("avatar_hash")?.map(|h| h as u64),
+            age_secs: r.try_get::<i64, _>("age_secs")?.max(0) as u64,
+        });
     }
-    Ok(names)
+    Ok(out)
 }
 
-fn extract_at_name(s: &str) -> Option<&str> {
-    if let Some(pos) = s.find('@') {
-        let rest = &s[pos + 1..];
-        let end = rest.find(|c: char| c.is_whitespace()).unwrap_or(rest.len());
-        if end > 0 {
-            return Some(&rest[..end]);
-        }
+async fn delete_punished_db(db: &Pool<Postgres>, guild_id: u64, user_id: u64) -> Result<()> {
+    sqlx::query("DELETE FROM tss.alt_punished WHERE guild_id = $1 AND user_id = $2")
+        .bind(guild_id as i64)
+        .bind(user_id as i64)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+async fn persist_punished(
+    db: &Pool<Postgres>,
+    guild_id: u64,
+    user_id: u64,
+    row: &PunishedRow,
+    action: &str,
+) -> Result<()> {
+    let q = r#"INSERT INTO tss.alt_punished
+                   (guild_id, user_id, username, global_name, avatar_hash, action, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, now() + make_interval(secs => $7))"#;
+    sqlx::query(q)
+        .bind(guild_id as i64)
+        .bind(user_id as i64)
+        .bind(&row.username)
+        .bind(row.global_name.as_deref())
+        .bind(row.avatar_hash.map(|h| h as i64))
+        .bind(action)
+        .bind(PUNISHED_TTL.as_secs() as f64)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_thresholds_become_overrides() {
+        // zapis sprzed profili z nadpisaniami: threshold.high=60, min_signals=1
+        let legacy = serde_json::json!({
+            "enabled": true,
+            "thresholds": { "low": 40, "high": 60 },
+            "weights": serde_json::to_value(AltConfig::default().weights).unwrap(),
+            "min_signals_for_auto": 1,
+            "raidaware_enabled": true,
+            "raidaware_join_per_60s": 8,
+            "profile": "Balanced",
+            "enforcement": serde_json::to_value(Enforcement::default()).unwrap(),
+        });
+        let cfg: AltConfig = serde_json::from_value(legacy).unwrap();
+        // low = wartość profilu – nie zamrażamy jej jako nadpisania
+        assert_eq!(cfg.overrides.low, None);
+        assert_eq!(cfg.overrides.high, Some(60));
+        assert_eq!(cfg.overrides.min_signals, Some(1));
+
+        let r = cfg.resolve();
+        assert_eq!((r.thresholds.low, r.thresholds.high), (40, 60));
+        assert_eq!(r.min_signals_for_auto, 1);
+
+        // nowy zapis nie niesie już starych pól i wczytuje się bez zmian
+        let again: AltConfig = serde_json::from_value(serde_json::to_value(&cfg).unwrap()).unwrap();
+        assert_eq!(again.overrides.high, Some(60));
     }
-    None
 }
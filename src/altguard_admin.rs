@@ -0,0 +1,520 @@
+// src/altguard_admin.rs
+//
+// /altguard – administracja AltGuardem: konfiguracja gildii (tss.alt_config, od razu w cache),
+// whitelist oraz ręczny scoring użytkownika z pełnym rozbiciem sygnałów.
+
+use anyhow::Result;
+use serenity::all::*;
+
+use crate::altguard::{AltAction, AltConfig, AltScore, AltVerdict, PolicyProfile, ScoreInput, Weights};
+use crate::discord::router::Routes;
+use crate::{
+    invites,
+    permissions::{self, Permission},
+    registry::env_roles,
+    AppContext,
+};
+
+const SYSTEM_NAME: &str = "Tigris AltGuard";
+
+/// Klucze `/altguard config set` (nazwa, opis w podpowiedzi).
+const CONFIG_KEYS: &[(&str, &str)] = &[
+    ("enabled", "AltGuard włączony (on/off)"),
+    ("threshold.low", "Próg MEDIUM (0–100)"),
+    ("threshold.high", "Próg HIGH (0–100)"),
+    ("min_signals", "Min. dodatnich sygnałów do akcji"),
+    ("raidaware", "Obniżanie progów przy raidzie (on/off)"),
+    ("raidaware.joins_60s", "Wejść w 60 s = raid"),
+    ("medium.action", "Akcja MEDIUM (log/quarantine/timeout/kick)"),
+    ("medium.ping", "Ping staffu przy MEDIUM (on/off)"),
+    ("high.action", "Akcja HIGH (log/quarantine/timeout/kick)"),
+    ("high.ping", "Ping staffu przy HIGH (on/off)"),
+    ("timeout_minutes", "Długość timeoutu (min)"),
+    ("raid.lockdown", "Tryb raidu przy fali wejść (on/off)"),
+    ("raid.verification", "Raid: wyższy poziom weryfikacji (on/off)"),
+    ("raid.pause_verify", "Raid: wstrzymaj panel weryfikacji (on/off)"),
+    ("raid.slowmode", "Raid: slowmode czatów w s (0 = bez)"),
+    ("raid.quiet_minutes", "Raid: koniec po tylu min bez wejść"),
+    ("weight.account_age", "Waga: wiek konta"),
+    ("weight.burst_60s", "Waga: burst 60 s"),
+    ("weight.burst_10m", "Waga: burst 10 min"),
+    ("weight.invite_affinity", "Waga: to samo zaproszenie"),
+    ("weight.name_similarity", "Waga: nazwa jak u ukaranych"),
+    ("weight.history_base", "Waga: świeże bany"),
+    ("weight.trusted_relief", "Ulga: zaufana rola"),
+    ("weight.avatar_ahash", "Waga: avatar jak u ukaranych"),
+    ("weight.behavior_pattern", "Waga: wzorzec wiadomości"),
+];
+
+pub struct AltGuardAdmin;
+
+impl AltGuardAdmin {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["altguard"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        let user_opt = || CreateCommandOption::new(CommandOptionType::User, "user", "Użytkownik").required(true);
+        let key_opt = CONFIG_KEYS.iter().fold(
+            CreateCommandOption::new(CommandOptionType::String, "key", "Pole konfiguracji").required(true),
+            |opt, (key, _)| opt.add_string_choice(*key, *key),
+        );
+
+        vec![
+            CreateCommand::new("altguard")
+                .description("AltGuard – wykrywanie multikont")
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommandGroup, "config", "Konfiguracja AltGuard")
+                        .add_sub_option(CreateCommandOption::new(
+                            CommandOptionType::SubCommand,
+                            "show",
+                            "Pokaż konfigurację",
+                        ))
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Ustaw pole konfiguracji")
+                                .add_sub_option(key_opt)
+                                .add_sub_option(
+                                    CreateCommandOption::new(CommandOptionType::String, "value", "Nowa wartość (auto = wartość z profilu)")
+                                        .required(true),
+                                ),
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::SubCommand, "profile", "Profil polityki")
+                                .add_sub_option(
+                                    CreateCommandOption::new(CommandOptionType::String, "value", "lenient|balanced|strict")
+                                        .required(true)
+                                        .add_string_choice("lenient", "lenient")
+                                        .add_string_choice("balanced", "balanced")
+                                        .add_string_choice("strict", "strict"),
+                                ),
+                        ),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommandGroup, "whitelist", "Whitelist AltGuard")
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Dodaj do whitelisty")
+                                .add_sub_option(user_opt())
+                                .add_sub_option(CreateCommandOption::new(
+                                    CommandOptionType::String,
+                                    "note",
+                                    "Notatka (opcjonalnie)",
+                                )),
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Usuń z whitelisty")
+                                .add_sub_option(user_opt()),
+                        )
+                        .add_sub_option(CreateCommandOption::new(
+                            CommandOptionType::SubCommand,
+                            "list",
+                            "Pokaż whitelistę",
+                        )),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "score", "Policz ryzyko użytkownika")
+                        .add_sub_option(user_opt()),
+                ),
+        ]
+    }
+
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        let Some(cmd) = interaction.command() else { return; };
+        if cmd.data.name.as_str() != "altguard" { return; }
+        if let Err(e) = handle_altguard(ctx, app, &cmd).await {
+            tracing::warn!(?e, "altguard command failed");
+        }
+    }
+}
+
+async fn handle_altguard(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Altguard) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień do AltGuard.").await;
+    }
+    let Some((group, sub, params)) = extract_sub(&cmd.data.options) else {
+        return edit_ephemeral(ctx, cmd, "Wybierz podkomendę.").await;
+    };
+
+    let ag = app.altguard();
+    match (group, sub) {
+        (Some("config"), "show") => {
+            let embed = config_embed(&ag.config(gid.get()));
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await?;
+            Ok(())
+        }
+        (Some("config"), "set") => {
+            let (Some(key), Some(value)) = (str_param(params, "key"), str_param(params, "value")) else {
+                return edit_ephemeral(ctx, cmd, "Podaj klucz i wartość.").await;
+            };
+            let mut cfg = ag.config(gid.get());
+            if let Err(msg) = set_config_key(&mut cfg, key, value) {
+                return edit_ephemeral(ctx, cmd, &format!("⚠️ {msg}")).await;
+            }
+            ag.set_config(gid.get(), cfg.clone()).await?;
+            tracing::info!(gid = gid.get(), key, value, by = cmd.user.id.get(), "altguard config changed");
+
+            let mut msg = format!("✅ `{key}` = `{value}`.");
+            let quarantine = [cfg.enforcement.medium.action, cfg.enforcement.high.action].contains(&AltAction::Quarantine);
+            if quarantine && env_roles::quarantine_id(gid.get()) == 0 {
+                msg.push_str("\n⚠️ Brak roli kwarantanny – ustaw `role.quarantine` przez /registry.");
+            }
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg).embed(config_embed(&cfg)))
+                .await?;
+            Ok(())
+        }
+        (Some("config"), "profile") => {
+            let Some(profile) = str_param(params, "value").and_then(PolicyProfile::parse) else {
+                return edit_ephemeral(ctx, cmd, "Nieznany profil.").await;
+            };
+            let mut cfg = ag.config(gid.get());
+            cfg.profile = profile;
+            ag.set_config(gid.get(), cfg.clone()).await?;
+            tracing::info!(gid = gid.get(), profile = profile.as_str(), by = cmd.user.id.get(), "altguard profile changed");
+
+            let policy = cfg.resolve();
+            let mut msg = format!(
+                "✅ Profil AltGuard: **{}** – MEDIUM ≥ **{}**, HIGH ≥ **{}**.",
+                profile.as_str(),
+                policy.thresholds.low,
+                policy.thresholds.high
+            );
+            let kept = override_count(&cfg);
+            if kept > 0 {
+                msg.push_str(&format!("\nRęczne nadpisania ({kept}) nadal obowiązują – `value: auto` je zdejmuje."));
+            }
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg).embed(config_embed(&cfg)))
+                .await?;
+            Ok(())
+        }
+        (Some("whitelist"), "add") => {
+            let Some(uid) = user_param(params, "user") else {
+                return edit_ephemeral(ctx, cmd, "Wskaż użytkownika.").await;
+            };
+            let note = str_param(params, "note");
+            let added = ag.whitelist_add(gid.get(), uid.get(), note, Some(cmd.user.id.get())).await?;
+            let msg = if added {
+                format!("🤍 <@{}> dodany do whitelisty AltGuard.", uid.get())
+            } else {
+                format!("<@{}> już jest na whiteliście.", uid.get())
+            };
+            edit_ephemeral(ctx, cmd, &msg).await
+        }
+        (Some("whitelist"), "remove") => {
+            let Some(uid) = user_param(params, "user") else {
+                return edit_ephemeral(ctx, cmd, "Wskaż użytkownika.").await;
+            };
+            let removed = ag.whitelist_remove(gid.get(), uid.get()).await?;
+            let msg = if removed {
+                format!("🗑️ <@{}> usunięty z whitelisty.", uid.get())
+            } else {
+                format!("<@{}> nie był na whiteliście.", uid.get())
+            };
+            edit_ephemeral(ctx, cmd, &msg).await
+        }
+        (Some("whitelist"), "list") => {
+            let list = ag.whitelist_list(gid.get(), 25).await?;
+            let desc = if list.is_empty() {
+                "Whitelist jest pusta.".to_string()
+            } else {
+                list.iter()
+                    .map(|w| {
+                        let by = w.added_by.map(|b| format!(" • przez <@{b}>")).unwrap_or_default();
+                        let note = if w.note.is_empty() { String::new() } else { format!(" – {}", w.note) };
+                        format!("<@{}> <t:{}:R>{by}{note}", w.user_id, w.created_unix)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let embed = base_embed("🤍 Whitelist AltGuard".into()).description(desc);
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await?;
+            Ok(())
+        }
+        (None, "score") => {
+            let Some(uid) = user_param(params, "user") else {
+                return edit_ephemeral(ctx, cmd, "Wskaż użytkownika.").await;
+            };
+            let user = uid.to_user(&ctx.http).await?;
+            let member = gid.member(&ctx.http, uid).await.ok();
+            let staff = env_roles::staff_set(gid.get());
+            let join = invites::last_join(&app.db, gid.get(), uid.get()).await.unwrap_or_else(|e| {
+                tracing::warn!(error=?e, "member_joins lookup failed");
+                None
+            });
+            let input = ScoreInput {
+                guild_id: gid.get(),
+                user_id: uid.get(),
+                username: Some(user.name.clone()),
+                display_name: member.as_ref().and_then(|m| m.nick.clone()),
+                global_name: user.global_name.clone(),
+                invite_code: join.as_ref().and_then(|j| j.invite_code.clone()),
+                inviter_id: join.as_ref().and_then(|j| j.inviter_id),
+                has_trusted_role: member
+                    .as_ref()
+                    .is_some_and(|m| m.roles.iter().any(|r| staff.contains(&r.get()))),
+                avatar_url: user.avatar_url(),
+            };
+            let score = ag.score_user(&input).await?;
+            let embed = score_embed(&input, &score, ag.is_whitelisted(gid.get(), uid.get()).await);
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed)).await?;
+            Ok(())
+        }
+        _ => edit_ephemeral(ctx, cmd, "Nieznana podkomenda.").await,
+    }
+}
+
+/* ========================= Konfiguracja ========================= */
+
+/// Progi, `min_signals` i wagi trafiają do `overrides` (wygrywają z profilem);
+/// `auto` zdejmuje nadpisanie i wraca do wartości z profilu.
+fn set_config_key(cfg: &mut AltConfig, key: &str, value: &str) -> std::result::Result<(), String> {
+    let auto = value.trim().eq_ignore_ascii_case("auto");
+    let num = || value.trim().parse::<i64>().map_err(|_| format!("`{value}` nie jest liczbą."));
+    let flag = || parse_flag(value).ok_or_else(|| format!("`{value}` – użyj on/off."));
+    let action = || AltAction::parse(value).ok_or_else(|| format!("`{value}` – użyj log/quarantine/timeout/kick."));
+    let ov = &mut cfg.overrides;
+    match key {
+        "enabled" => cfg.enabled = flag()?,
+        "threshold.low" => ov.low = if auto { None } else { Some(num()?.clamp(0, 100) as u8) },
+        "threshold.high" => ov.high = if auto { None } else { Some(num()?.clamp(0, 100) as u8) },
+        "min_signals" => ov.min_signals = if auto { None } else { Some(num()?.clamp(0, 9) as u8) },
+        "raidaware" => cfg.raidaware_enabled = flag()?,
+        "raidaware.joins_60s" => cfg.raidaware_join_per_60s = num()?.clamp(2, 1000) as u32,
+        "medium.action" => cfg.enforcement.medium.action = action()?,
+        "medium.ping" => cfg.enforcement.medium.ping_staff = flag()?,
+        "high.action" => cfg.enforcement.high.action = action()?,
+        "high.ping" => cfg.enforcement.high.ping_staff = flag()?,
+        "timeout_minutes" => cfg.enforcement.timeout_minutes = num()?.clamp(1, 28 * 24 * 60) as u32,
+        "raid.lockdown" => cfg.raid.lockdown = flag()?,
+        "raid.verification" => cfg.raid.verification = flag()?,
+        "raid.pause_verify" => cfg.raid.pause_verify = flag()?,
+        "raid.slowmode" => cfg.raid.slowmode_secs = num()?.clamp(0, 21_600) as u16, // limit Discorda: 6 h
+        "raid.quiet_minutes" => cfg.raid.quiet_minutes = num()?.clamp(1, 24 * 60) as u32,
+        _ => {
+            let Some(name) = key.strip_prefix("weight.").filter(|n| Weights::NAMES.contains(n)) else {
+                return Err(format!("Nieznany klucz `{key}`."));
+            };
+            if auto {
+                ov.weights.remove(name);
+            } else {
+                ov.weights.insert(name.to_string(), num()?.clamp(0, 100) as i32);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn override_count(cfg: &AltConfig) -> usize {
+    let ov = &cfg.overrides;
+    [ov.low.is_some(), ov.high.is_some(), ov.min_signals.is_some()].iter().filter(|b| **b).count() + ov.weights.len()
+}
+
+fn parse_flag(v: &str) -> Option<bool> {
+    match v.trim().to_ascii_lowercase().as_str() {
+        "on" | "true" | "1" | "tak" | "yes" => Some(true),
+        "off" | "false" | "0" | "nie" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn config_embed(cfg: &AltConfig) -> CreateEmbed {
+    let onoff = |b: bool| if b { "on" } else { "off" };
+    let e = &cfg.enforcement;
+    let ov = &cfg.overrides;
+    let policy = cfg.resolve();
+    let w = &policy.weights;
+    // ✱ = wartość nadpisana ręcznie (profil jej nie zmienia)
+    let mark = |set: bool| if set { "✱" } else { "" };
+    let wm = |name: &str| mark(ov.weights.contains_key(name));
+    base_embed("🛡️ AltGuard – konfiguracja".into())
+        .field("Włączony", onoff(cfg.enabled), true)
+        .field("Profil", cfg.profile.as_str(), true)
+        .field(
+            "Progi",
+            format!(
+                "MEDIUM ≥ **{}**{} • HIGH ≥ **{}**{}",
+                policy.thresholds.low,
+                mark(ov.low.is_some()),
+                policy.thresholds.high,
+                mark(ov.high.is_some())
+            ),
+            true,
+        )
+        .field(
+            "Min. sygnałów",
+            format!("{}{}", policy.min_signals_for_auto, mark(ov.min_signals.is_some())),
+            true,
+        )
+        .field(
+            "Raid-aware",
+            format!("{} ({} wejść / 60 s)", onoff(cfg.raidaware_enabled), cfg.raidaware_join_per_60s),
+            true,
+        )
+        .field(
+            "Tryb raidu",
+            format!(
+                "{} • weryfikacja {} • panel pauza {} • slowmode {} s • koniec po {} min ciszy",
+                onoff(cfg.raid.lockdown),
+                onoff(cfg.raid.verification),
+                onoff(cfg.raid.pause_verify),
+                cfg.raid.slowmode_secs,
+                cfg.raid.quiet_minutes
+            ),
+            false,
+        )
+        .field(
+            "Akcje",
+            format!(
+                "MEDIUM: **{}**{}\nHIGH: **{}**{}\nTimeout: {} min",
+                e.medium.action.label(),
+                if e.medium.ping_staff { " + ping" } else { "" },
+                e.high.action.label(),
+                if e.high.ping_staff { " + ping" } else { "" },
+                e.timeout_minutes
+            ),
+            false,
+        )
+        .field(
+            "Wagi",
+            format!(
+                "wiek konta {}{} • burst 60 s {}{} • burst 10 min {}{} • zaproszenie {}{}\n\
+                 nazwa {}{} • bany {}{} • avatar {}{} • wiadomości {}{} • ulga staff −{}{}",
+                w.account_age_max,
+                wm("account_age"),
+                w.burst_60s_max,
+                wm("burst_60s"),
+                w.burst_10m_max,
+                wm("burst_10m"),
+                w.invite_affinity_max,
+                wm("invite_affinity"),
+                w.name_similarity_max,
+                wm("name_similarity"),
+                w.history_base_max,
+                wm("history_base"),
+                w.avatar_ahash_max,
+                wm("avatar_ahash"),
+                w.behavior_pattern_max,
+                wm("behavior_pattern"),
+                w.trusted_relief,
+                wm("trusted_relief")
+            ),
+            false,
+        )
+}
+
+fn score_embed(input: &ScoreInput, score: &AltScore, whitelisted: bool) -> CreateEmbed {
+    let (verdict, colour) = match score.verdict {
+        AltVerdict::Low => ("LOW", Colour::DARK_GREEN),
+        AltVerdict::Medium => ("MEDIUM", Colour::GOLD),
+        AltVerdict::High => ("HIGH", Colour::RED),
+    };
+    let breakdown = if score.top_signals.is_empty() {
+        "Brak sygnałów.".to_string()
+    } else {
+        score
+            .top_signals
+            .iter()
+            .map(|s| format!("`{:?}` **{:+}** – {}", s.kind, s.weight, s.detail))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let mut e = base_embed("🔎 AltGuard – scoring".into())
+        .colour(colour)
+        .description(format!("<@{}> (`{}`)", input.user_id, input.user_id))
+        .field("Score", format!("**{}** / 100", score.score), true)
+        .field("Werdykt", verdict, true)
+        .field("Zaproszenie", invites::label(input.invite_code.as_deref(), input.inviter_id), true)
+        .field("Sygnały", breakdown, false)
+        .field("Wyjaśnienie", &score.explain, false);
+    if whitelisted {
+        e = e.field("Whitelist", "Tak – scoring pominięty.", false);
+    }
+    e
+}
+
+/* ========================= Helpers ========================= */
+
+/// (grupa, podkomenda, parametry) – `score` nie ma grupy.
+fn extract_sub(options: &[CommandDataOption]) -> Option<(Option<&str>, &str, &[CommandDataOption])> {
+    let first = options.first()?;
+    match &first.value {
+        CommandDataOptionValue::SubCommandGroup(subs) => {
+            let sub = subs.first()?;
+            match &sub.value {
+                CommandDataOptionValue::SubCommand(params) => Some((Some(first.name.as_str()), sub.name.as_str(), params)),
+                _ => None,
+            }
+        }
+        CommandDataOptionValue::SubCommand(params) => Some((None, first.name.as_str(), params)),
+        _ => None,
+    }
+}
+
+fn str_param<'a>(params: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    params.iter().find(|p| p.name == name).and_then(|p| p.value.as_str())
+}
+
+fn user_param(params: &[CommandDataOption], name: &str) -> Option<UserId> {
+    params.iter().find(|p| p.name == name).and_then(|p| p.value.as_user_id())
+}
+
+fn base_embed(title: String) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .colour(Colour::new(0x5865F2))
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+}
+
+async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_config_key_is_settable() {
+        for (key, _) in CONFIG_KEYS {
+            let value = match *key {
+                "enabled" | "raidaware" | "medium.ping" | "high.ping" | "raid.lockdown" | "raid.verification"
+                | "raid.pause_verify" => "off",
+                "medium.action" | "high.action" => "kick",
+                _ => "7",
+            };
+            assert!(set_config_key(&mut AltConfig::default(), key, value).is_ok(), "{key}");
+        }
+        assert!(CONFIG_KEYS.len() <= 25, "Discord: max 25 choices");
+    }
+
+    #[test]
+    fn thresholds_stay_ordered() {
+        let mut cfg = AltConfig::default();
+        set_config_key(&mut cfg, "threshold.low", "90").unwrap();
+        set_config_key(&mut cfg, "threshold.high", "50").unwrap();
+        let policy = cfg.resolve();
+        assert!(policy.thresholds.low < policy.thresholds.high);
+    }
+
+    #[test]
+    fn overrides_win_over_profile() {
+        let mut cfg = AltConfig::default();
+        set_config_key(&mut cfg, "weight.burst_60s", "33").unwrap();
+        cfg.profile = PolicyProfile::Strict;
+        let strict = cfg.resolve();
+        assert_eq!(strict.weights.burst_60s_max, 33);
+        assert!(strict.weights.account_age_max > AltConfig::default().resolve().weights.account_age_max);
+        assert!(strict.thresholds.high < 70);
+
+        set_config_key(&mut cfg, "weight.burst_60s", "auto").unwrap();
+        assert!(cfg.overrides.weights.is_empty());
+    }
+}
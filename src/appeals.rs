@@ -0,0 +1,507 @@
+// src/appeals.rs
+//
+// Odwołania od banów:
+//  - przycisk „Odwołaj się” w formalnym DM o banie (ban::send_formal_dm) → modal z treścią,
+//  - zapis w tss.appeals + wpis na kanale odwołań (env_channels::contact::appeals_id)
+//    z przyciskami Przyjmij / Odrzuć,
+//  - przyjęcie zdejmuje bana przez ban::lift_ban, wynik trafia do użytkownika w DM,
+//  - tylko dla zbanowanych (otwarta sprawa w tss.ban_cases albo ban na Discordzie),
+//  - cooldown: jedno odwołanie na APPEAL_COOLDOWN_DAYS (oczekujące blokuje kolejne).
+
+use anyhow::Result;
+use serenity::all::*;
+use sqlx::{Pool, Postgres, Row};
+
+use crate::discord::router::Routes;
+use crate::permissions::{self, Permission};
+use crate::{ban, registry::env_channels, AppContext};
+
+const SYSTEM_NAME: &str = "Tigris Appeals";
+const SERVER_NAME: &str = "Unfaithful";
+
+/// Minimalny odstęp między odwołaniami tego samego użytkownika.
+const APPEAL_COOLDOWN_DAYS: i64 = 7;
+
+pub struct Appeals;
+
+/// Przycisk do formalnego DM o banie. DM nie ma kontekstu gildii, więc niesiemy ją w custom_id.
+pub(crate) fn appeal_button(guild_id: GuildId) -> CreateButton {
+    CreateButton::new(format!("appeal:open:{}", guild_id.get()))
+        .label("Odwołaj się")
+        .emoji('📨')
+        .style(ButtonStyle::Primary)
+}
+
+impl Appeals {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &[],
+        components: &["appeal:"],
+        modals: &["appeal:"],
+    };
+
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        if let Some(comp) = interaction.clone().message_component() {
+            let id = comp.data.custom_id.as_str();
+            let res = if id.starts_with("appeal:open:") {
+                on_open(ctx, app, &comp).await
+            } else if id.starts_with("appeal:accept:") {
+                on_decide(ctx, app, &comp, true).await
+            } else if id.starts_with("appeal:deny:") {
+                on_decide(ctx, app, &comp, false).await
+            } else {
+                return;
+            };
+            if let Err(e) = res {
+                tracing::warn!(?e, custom_id = %comp.data.custom_id, "appeal component failed");
+            }
+            return;
+        }
+
+        if let Some(modal) = interaction.modal_submit() {
+            if !modal.data.custom_id.starts_with("appeal:modal:") {
+                return;
+            }
+            if let Err(e) = on_submit(ctx, app, &modal).await {
+                tracing::warn!(?e, "appeal submit failed");
+            }
+        }
+    }
+}
+
+/* ========================= Użytkownik ========================= */
+
+async fn on_open(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
+    let Some(gid) = parse_id(&comp.data.custom_id) else { return Ok(()); };
+
+    if let Some(msg) = cooldown_message(&app.db, gid, comp.user.id.get()).await? {
+        return reply_component(ctx, comp, &msg).await;
+    }
+    let ban = open_ban_case(&app.db, gid, comp.user.id.get()).await?;
+    if !is_banned(ctx, gid, comp.user.id.get(), ban.as_ref()).await {
+        return reply_component(ctx, comp, NOT_BANNED).await;
+    }
+
+    let modal = CreateModal::new(format!("appeal:modal:{gid}"), "Odwołanie od bana")
+        .components(vec![CreateActionRow::InputText(
+            CreateInputText::new(
+                InputTextStyle::Paragraph,
+                "Dlaczego ban powinien zostać zdjęty?",
+                "appeal_text",
+            )
+            .required(true)
+            .min_length(20)
+            .max_length(1500),
+        )]);
+    comp.create_response(&ctx.http, CreateInteractionResponse::Modal(modal))
+        .await?;
+    Ok(())
+}
+
+async fn on_submit(ctx: &Context, app: &AppContext, modal: &ModalInteraction) -> Result<()> {
+    let Some(gid) = parse_id(&modal.data.custom_id) else { return Ok(()); };
+    let uid = modal.user.id.get();
+
+    modal
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+        )
+        .await?;
+
+    let mut text = String::new();
+    for row in &modal.data.components {
+        for c in &row.components {
+            if let ActionRowComponent::InputText(input) = c
+                && input.custom_id == "appeal_text"
+                && let Some(v) = &input.value
+            {
+                text = v.trim().to_string();
+            }
+        }
+    }
+    if text.is_empty() {
+        return edit_modal(ctx, modal, "Treść odwołania nie może być pusta.").await;
+    }
+
+    // ponownie – mogło minąć trochę czasu między otwarciem modala a wysłaniem
+    if let Some(msg) = cooldown_message(&app.db, gid, uid).await? {
+        return edit_modal(ctx, modal, &msg).await;
+    }
+
+    let ban = open_ban_case(&app.db, gid, uid).await?;
+    if !is_banned(ctx, gid, uid, ban.as_ref()).await {
+        return edit_modal(ctx, modal, NOT_BANNED).await;
+    }
+    let Some(appeal_id) = insert_appeal(&app.db, gid, uid, ban.as_ref().map(|b| b.id), &text).await? else {
+        return edit_modal(ctx, modal, "Masz już oczekujące odwołanie.").await;
+    };
+
+    let ch = appeals_channel(gid);
+    if ch == 0 {
+        tracing::warn!(appeal_id, guild_id = gid, "appeals channel not configured");
+    } else {
+        let embed = appeal_embed(appeal_id, uid, ban.as_ref(), &text, None);
+        let msg = ChannelId::new(ch)
+            .send_message(
+                &ctx.http,
+                CreateMessage::new().embed(embed).components(decision_buttons(appeal_id)),
+            )
+            .await;
+        match msg {
+            Ok(m) => {
+                sqlx::query("UPDATE tss.appeals SET channel_id = $2, message_id = $3 WHERE id = $1")
+                    .bind(appeal_id)
+                    .bind(m.channel_id.get() as i64)
+                    .bind(m.id.get() as i64)
+                    .execute(&app.db)
+                    .await?;
+            }
+            Err(e) => tracing::warn!(?e, appeal_id, "appeal post failed"),
+        }
+    }
+
+    edit_modal(
+        ctx,
+        modal,
+        &format!("📨 Odwołanie **#{appeal_id}** zostało wysłane. Decyzję otrzymasz w wiadomości prywatnej."),
+    )
+    .await
+}
+
+/* ========================= Administracja ========================= */
+
+async fn on_decide(ctx: &Context, app: &AppContext, comp: &ComponentInteraction, accept: bool) -> Result<()> {
+    let Some(appeal_id) = parse_id(&comp.data.custom_id).map(|v| v as i64) else { return Ok(()); };
+
+    let Some(gid) = comp.guild_id else { return Ok(()); };
+    if !permissions::authorize(gid, comp.member.as_ref(), Permission::Appeals) {
+        return reply_component(ctx, comp, "⛔ Brak uprawnień do rozpatrywania odwołań.").await;
+    }
+
+    comp.create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    // rezerwacja decyzji – drugi klik (lub drugi moderator) nic już nie zrobi
+    let status = if accept { "accepted" } else { "denied" };
+    let Some(appeal) = claim_appeal(&app.db, appeal_id, status, comp.user.id.get()).await? else {
+        return followup(ctx, comp, "To odwołanie zostało już rozpatrzone.").await;
+    };
+
+    let gid = GuildId::new(appeal.guild_id);
+    let uid = UserId::new(appeal.user_id);
+
+    if accept {
+        let reason = format!("Odwołanie #{appeal_id} przyjęte");
+        if let Err(e) =
+            ban::lift_ban(&ctx.http, app, gid, uid, comp.user.id, &reason, Some("Odwołanie od bana")).await
+        {
+            tracing::warn!(?e, appeal_id, "appeal unban failed");
+            release_appeal(&app.db, appeal_id).await?;
+            return followup(ctx, comp, &format!("⛔ Nie udało się zdjąć bana: {e}")).await;
+        }
+    }
+
+    let _ = dm_result(ctx, uid, appeal_id, accept).await;
+
+    let ban = open_or_last_ban_case(&app.db, appeal.guild_id, appeal.user_id).await.ok().flatten();
+    let embed = appeal_embed(appeal_id, appeal.user_id, ban.as_ref(), &appeal.message, Some((accept, comp.user.id)));
+    comp.edit_response(&ctx.http, EditInteractionResponse::new().embed(embed).components(vec![]))
+        .await?;
+    Ok(())
+}
+
+/* ========================= Embeds ========================= */
+
+fn appeal_embed(
+    appeal_id: i64,
+    uid: u64,
+    ban: Option<&BanInfo>,
+    text: &str,
+    decision: Option<(bool, UserId)>,
+) -> CreateEmbed {
+    let (colour, status) = match decision {
+        None => (0x3498DB, "⏳ Oczekuje".to_string()),
+        Some((true, by)) => (0x2ECC71, format!("✅ Przyjęte przez <@{}>", by.get())),
+        Some((false, by)) => (0xE74C3C, format!("❌ Odrzucone przez <@{}>", by.get())),
+    };
+
+    let mut e = CreateEmbed::new()
+        .title(format!("📨 Odwołanie od bana #{appeal_id}"))
+        .colour(Colour::new(colour))
+        .field("Użytkownik", format!("<@{uid}> (`{uid}`)"), true)
+        .field("Status", status, true)
+        .field("Treść odwołania", format!("```{}```", truncate(text, 1000)), false)
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME));
+
+    match ban {
+        Some(b) => {
+            let kind = if b.kind == "temp" { "tymczasowy" } else { "permanentny" };
+            e = e
+                .field("Ban", format!("{kind} • <t:{}:R>", b.created_unix), true)
+                .field("Nałożył", format!("<@{}>", b.moderator_id), true)
+                .field("Powód bana", format!("```{}```", truncate(&b.reason, 500)), false);
+        }
+        None => {
+            e = e.field("Ban", "_brak sprawy w rejestrze (ban spoza bota?)_", false);
+        }
+    }
+    e
+}
+
+fn decision_buttons(appeal_id: i64) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("appeal:accept:{appeal_id}"))
+            .label("Przyjmij (unban)")
+            .style(ButtonStyle::Success),
+        CreateButton::new(format!("appeal:deny:{appeal_id}"))
+            .label("Odrzuć")
+            .style(ButtonStyle::Danger),
+    ])]
+}
+
+async fn dm_result(ctx: &Context, uid: UserId, appeal_id: i64, accepted: bool) -> Result<()> {
+    let user = uid.to_user(&ctx.http).await?;
+    let e = if accepted {
+        CreateEmbed::new()
+            .title(format!("Odwołanie przyjęte – {SERVER_NAME}"))
+            .colour(Colour::new(0x2ECC71))
+            .description(format!(
+                "Twoje odwołanie **#{appeal_id}** zostało przyjęte, a ban zdjęty.\n\
+                 Możesz ponownie dołączyć do serwera."
+            ))
+    } else {
+        CreateEmbed::new()
+            .title(format!("Odwołanie odrzucone – {SERVER_NAME}"))
+            .colour(Colour::new(0xE74C3C))
+            .description(format!(
+                "Twoje odwołanie **#{appeal_id}** zostało rozpatrzone negatywnie.\n\
+                 Kolejne odwołanie możesz złożyć po {APPEAL_COOLDOWN_DAYS} dniach od poprzedniego."
+            ))
+    }
+    .footer(CreateEmbedFooter::new(SYSTEM_NAME));
+
+    let dm = user.create_dm_channel(&ctx.http).await?;
+    dm.send_message(&ctx.http, CreateMessage::new().embed(e)).await?;
+    Ok(())
+}
+
+/* ========================= DB ========================= */
+
+struct BanInfo {
+    id: i64,
+    kind: String,
+    moderator_id: u64,
+    reason: String,
+    created_unix: i64,
+}
+
+struct ClaimedAppeal {
+    guild_id: u64,
+    user_id: u64,
+    message: String,
+}
+
+/// Komunikat blokady, jeśli użytkownik ma oczekujące odwołanie albo jest w cooldownie.
+async fn cooldown_message(db: &Pool<Postgres>, gid: u64, uid: u64) -> Result<Option<String>> {
+    let row = sqlx::query(
+        r#"
+        SELECT status,
+               EXTRACT(EPOCH FROM created_at + make_interval(days => $3::INT))::BIGINT AS next_unix
+        FROM tss.appeals
+        WHERE guild_id = $1 AND user_id = $2
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .bind(APPEAL_COOLDOWN_DAYS as i32)
+    .fetch_optional(db)
+    .await?;
+
+    let Some(r) = row else { return Ok(None) };
+    let status: String = r.get("status");
+    let next_unix: i64 = r.get("next_unix");
+
+    if status == "pending" {
+        return Ok(Some("⏳ Twoje poprzednie odwołanie czeka na rozpatrzenie.".into()));
+    }
+    // każda decyzja (także przyjęcie) otwiera cooldown
+    if next_unix > now_unix() {
+        return Ok(Some(format!("⏳ Kolejne odwołanie możesz złożyć <t:{next_unix}:R>.")));
+    }
+    Ok(None)
+}
+
+fn ban_info(r: &sqlx::postgres::PgRow) -> BanInfo {
+    BanInfo {
+        id: r.get("id"),
+        kind: r.get("kind"),
+        moderator_id: r.get::<i64, _>("moderator_id") as u64,
+        reason: r.get("reason"),
+        created_unix: r.get("created_unix"),
+    }
+}
+
+async fn open_ban_case(db: &Pool<Postgres>, gid: u64, uid: u64) -> Result<Option<BanInfo>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, kind, moderator_id, reason, EXTRACT(EPOCH FROM created_at)::BIGINT AS created_unix
+        FROM tss.ban_cases
+        WHERE guild_id = $1 AND user_id = $2 AND unbanned_at IS NULL AND deleted_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .fetch_optional(db)
+    .await?;
+    Ok(row.as_ref().map(ban_info))
+}
+
+/// Po przyjęciu odwołania sprawa jest już zamknięta – pokazujemy ostatnią.
+async fn open_or_last_ban_case(db: &Pool<Postgres>, gid: u64, uid: u64) -> Result<Option<BanInfo>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, kind, moderator_id, reason, EXTRACT(EPOCH FROM created_at)::BIGINT AS created_unix
+        FROM tss.ban_cases
+        WHERE guild_id = $1 AND user_id = $2 AND deleted_at IS NULL
+        ORDER BY (unbanned_at IS NULL) DESC, created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .fetch_optional(db)
+    .await?;
+    Ok(row.as_ref().map(ban_info))
+}
+
+/// `None`, gdy istnieje już oczekujące odwołanie (unikalny indeks częściowy).
+async fn insert_appeal(
+    db: &Pool<Postgres>,
+    gid: u64,
+    uid: u64,
+    ban_case_id: Option<i64>,
+    text: &str,
+) -> Result<Option<i64>> {
+    let id: Option<i64> = sqlx::query_scalar(
+        r#"
+        INSERT INTO tss.appeals (guild_id, user_id, ban_case_id, message)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (guild_id, user_id) WHERE status = 'pending' DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .bind(ban_case_id)
+    .bind(text)
+    .fetch_optional(db)
+    .await?;
+    Ok(id)
+}
+
+async fn claim_appeal(db: &Pool<Postgres>, appeal_id: i64, status: &str, by: u64) -> Result<Option<ClaimedAppeal>> {
+    let row = sqlx::query(
+        r#"
+        UPDATE tss.appeals
+           SET status = $2, decided_at = now(), decided_by = $3
+         WHERE id = $1 AND status = 'pending'
+        RETURNING guild_id, user_id, message
+        "#,
+    )
+    .bind(appeal_id)
+    .bind(status)
+    .bind(by as i64)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| ClaimedAppeal {
+        guild_id: r.get::<i64, _>("guild_id") as u64,
+        user_id: r.get::<i64, _>("user_id") as u64,
+        message: r.get("message"),
+    }))
+}
+
+/// Cofnięcie rezerwacji, gdy unban się nie powiódł – można spróbować ponownie.
+async fn release_appeal(db: &Pool<Postgres>, appeal_id: i64) -> Result<()> {
+    sqlx::query(
+        "UPDATE tss.appeals SET status = 'pending', decided_at = NULL, decided_by = NULL WHERE id = $1",
+    )
+    .bind(appeal_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/* ========================= Helpers ========================= */
+
+const NOT_BANNED: &str = "Nie masz aktywnego bana na tym serwerze – nie ma od czego się odwoływać.";
+
+/// Otwarta sprawa bana albo (ban sprzed rejestru / nałożony ręcznie) ban na Discordzie.
+async fn is_banned(ctx: &Context, gid: u64, uid: u64, open_case: Option<&BanInfo>) -> bool {
+    if open_case.is_some() {
+        return true;
+    }
+    match ban::is_banned(&ctx.http, GuildId::new(gid), UserId::new(uid)).await {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::warn!(error=?e, guild_id = gid, user_id = uid, "appeal: ban lookup failed");
+            false
+        }
+    }
+}
+
+fn parse_id(custom_id: &str) -> Option<u64> {
+    custom_id.split(':').nth(2)?.parse().ok()
+}
+
+fn appeals_channel(gid: u64) -> u64 {
+    env_channels::contact::appeals_id(gid)
+}
+
+async fn reply_component(ctx: &Context, comp: &ComponentInteraction, msg: &str) -> Result<()> {
+    comp.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new().content(msg).ephemeral(true),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn followup(ctx: &Context, comp: &ComponentInteraction, msg: &str) -> Result<()> {
+    comp.create_followup(
+        &ctx.http,
+        CreateInteractionResponseFollowup::new().content(msg).ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn edit_modal(ctx: &Context, modal: &ModalInteraction, msg: &str) -> Result<()> {
+    modal
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
+
+fn now_unix() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.len() <= max {
+        s.to_string()
+    } else {
+        let mut cut = max.saturating_sub(1);
+        while !s.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        format!("{}…", &s[..cut])
+    }
+}
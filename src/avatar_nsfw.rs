@@ -0,0 +1,222 @@
+// src/avatar_nsfw.rs
+//
+// Offline heurystyka NSFW dla avatarów (IdGuard: sygnał AvatarNSFW). Tylko CPU, bez modeli:
+// maska skóry (reguła RGB Kovaca ∩ zakres YCbCr), spójne regiony skóry i ich układ.
+// Wynik to pewność 0.0–1.0; próg ustawia gildia (`IdgConfig::nsfw_threshold`).
+// Sama ilość skóry nie wystarcza: zbliżenie twarzy/selfie to jeden zwarty, owalny region,
+// więc wysoki wynik wymaga układu ciała – kilku rozłącznych regionów (tułów, ręce, nogi)
+// albo regionu szerszego niż wysoki (tułów w poprzek kadru).
+
+use image::{imageops::FilterType, DynamicImage};
+
+/// Bok roboczego obrazu.
+const SIDE: u32 = 96;
+/// Region liczy się do układu, gdy zajmuje co najmniej tyle obrazu.
+const MIN_REGION_SHARE: f32 = 0.02;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NsfwScore {
+    /// Pewność 0.0–1.0.
+    pub confidence: f32,
+    /// Udział pikseli skóry w całym obrazie.
+    pub skin_ratio: f32,
+    /// Udział największego regionu skóry we wszystkich pikselach skóry.
+    pub largest_share: f32,
+    /// Liczba istotnych (≥ `MIN_REGION_SHARE`) rozłącznych regionów skóry.
+    pub regions: usize,
+}
+
+/// Spójny region maski skóry z prostokątem otaczającym.
+struct Region {
+    size: usize,
+    x0: usize,
+    x1: usize,
+    y0: usize,
+    y1: usize,
+}
+
+impl Region {
+    /// Szerokość / wysokość prostokąta otaczającego.
+    fn aspect(&self) -> f32 {
+        (self.x1 - self.x0 + 1) as f32 / (self.y1 - self.y0 + 1) as f32
+    }
+}
+
+pub fn classify(img: &DynamicImage) -> NsfwScore {
+    let rgb = img.resize_exact(SIDE, SIDE, FilterType::Triangle).to_rgb8();
+    let (w, h) = (SIDE as usize, SIDE as usize);
+
+    let mut skin = vec![false; w * h];
+    let mut luma = Vec::new();
+    for (i, p) in rgb.pixels().enumerate() {
+        let [r, g, b] = p.0;
+        if is_skin(r, g, b) {
+            skin[i] = true;
+            luma.push(0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32);
+        }
+    }
+
+    let total = luma.len();
+    let skin_ratio = total as f32 / (w * h) as f32;
+    if total == 0 {
+        return NsfwScore::default();
+    }
+
+    let min_size = ((w * h) as f32 * MIN_REGION_SHARE) as usize;
+    let regions: Vec<Region> = skin_regions(&skin, w, h).into_iter().filter(|r| r.size >= min_size).collect();
+    let Some(largest) = regions.iter().max_by_key(|r| r.size) else {
+        return NsfwScore { skin_ratio, ..NsfwScore::default() };
+    };
+    let largest_share = largest.size as f32 / total as f32;
+
+    // środek kadru (połowa boku) – tam zwykle jest główny obiekt
+    let (cx0, cx1, cy0, cy1) = (w / 4, w * 3 / 4, h / 4, h * 3 / 4);
+    let center_total = (cx1 - cx0) * (cy1 - cy0);
+    let center_skin = (cy0..cy1)
+        .flat_map(|y| (cx0..cx1).map(move |x| y * w + x))
+        .filter(|&i| skin[i])
+        .count();
+    let center_ratio = center_skin as f32 / center_total as f32;
+
+    // jednolite tło w kolorze skóry (beż, pomarańcz) nie ma faktury ciała
+    let mean = luma.iter().sum::<f32>() / total as f32;
+    let std = (luma.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / total as f32).sqrt();
+
+    // układ: twarz to jeden owalny region (wyższy niż szeroki); ciało – kilka regionów
+    // albo tułów w poprzek kadru
+    let limbs = smoothstep(0.0, 2.0, (regions.len() - 1) as f32);
+    let torso = smoothstep(1.1, 1.5, largest.aspect());
+    let layout = limbs.max(torso);
+
+    let coverage = smoothstep(0.15, 0.55, skin_ratio);
+    let centered = smoothstep(0.20, 0.70, center_ratio);
+    let texture = smoothstep(2.0, 8.0, std);
+
+    NsfwScore {
+        confidence: (coverage * texture * (0.15 + 0.85 * layout) * (0.6 + 0.4 * centered)).clamp(0.0, 1.0),
+        skin_ratio,
+        largest_share,
+        regions: regions.len(),
+    }
+}
+
+fn is_skin(r: u8, g: u8, b: u8) -> bool {
+    let (rf, gf, bf) = (r as f32, g as f32, b as f32);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let rgb_rule = r > 95
+        && g > 40
+        && b > 20
+        && max - min > 15
+        && r.abs_diff(g) > 15
+        && r > g
+        && r > b;
+
+    let cb = 128.0 - 0.168_736 * rf - 0.331_264 * gf + 0.5 * bf;
+    let cr = 128.0 + 0.5 * rf - 0.418_688 * gf - 0.081_312 * bf;
+    rgb_rule && (77.0..=127.0).contains(&cb) && (133.0..=173.0).contains(&cr)
+}
+
+/// Spójne regiony maski (4-sąsiedztwo).
+fn skin_regions(mask: &[bool], w: usize, h: usize) -> Vec<Region> {
+    let mut seen = vec![false; mask.len()];
+    let mut stack = Vec::new();
+    let mut out = Vec::new();
+
+    for start in 0..mask.len() {
+        if !mask[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let mut r = Region { size: 0, x0: w, x1: 0, y0: h, y1: 0 };
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            r.size += 1;
+            r.x0 = r.x0.min(x);
+            r.x1 = r.x1.max(x);
+            r.y0 = r.y0.min(y);
+            r.y1 = r.y1.max(y);
+            let mut visit = |j: usize| {
+                if mask[j] && !seen[j] {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            };
+            if x > 0 { visit(i - 1); }
+            if x + 1 < w { visit(i + 1); }
+            if y > 0 { visit(i - w); }
+            if y + 1 < h { visit(i + w); }
+        }
+        out.push(r);
+    }
+    out
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn flat_and_non_skin_images_score_low() {
+        let blue = DynamicImage::ImageRgb8(RgbImage::from_pixel(128, 128, Rgb([40, 90, 200])));
+        assert!(classify(&blue).confidence < 0.05);
+
+        // beżowe tło: kolor skóry, ale bez faktury
+        let beige = DynamicImage::ImageRgb8(RgbImage::from_pixel(128, 128, Rgb([224, 172, 140])));
+        assert!(classify(&beige).confidence < 0.2);
+    }
+
+    /// Skóra z cieniowaniem jak na zdjęciu: jaśniej w środku kształtu, ciemniej przy krawędzi.
+    fn shaded_skin(d: f32) -> Rgb<u8> {
+        let shade = d * 0.8;
+        Rgb([(235.0 - shade) as u8, (165.0 - shade * 0.7) as u8, (125.0 - shade * 0.5) as u8])
+    }
+
+    #[test]
+    fn body_layout_scores_high() {
+        // tułów w poprzek kadru + dwie ręce jako osobne regiony
+        let img = RgbImage::from_fn(128, 128, |x, y| {
+            let (fx, fy) = (x as f32, y as f32);
+            let torso = ((fx - 64.0) / 42.0).powi(2) + ((fy - 72.0) / 38.0).powi(2);
+            let arm_l = ((fx - 10.0) / 8.0).powi(2) + ((fy - 64.0) / 50.0).powi(2);
+            let arm_r = ((fx - 118.0) / 8.0).powi(2) + ((fy - 64.0) / 50.0).powi(2);
+            match [torso, arm_l, arm_r].into_iter().find(|&e| e < 1.0) {
+                Some(e) => shaded_skin(e.sqrt() * 40.0),
+                None => Rgb([20, 20, 30]),
+            }
+        });
+        let score = classify(&DynamicImage::ImageRgb8(img));
+        assert!(score.regions >= 3, "{score:?}");
+        assert!(score.confidence > 0.8, "{score:?}");
+    }
+
+    #[test]
+    fn face_close_up_stays_below_default_threshold() {
+        // owalna twarz wypełniająca kadr, oczy i usta, włosy u góry
+        let img = RgbImage::from_fn(128, 128, |x, y| {
+            let (fx, fy) = (x as f32, y as f32);
+            let face = ((fx - 64.0) / 46.0).powi(2) + ((fy - 70.0) / 58.0).powi(2);
+            let eye = |cx: f32| ((fx - cx) / 7.0).powi(2) + ((fy - 58.0) / 4.0).powi(2) < 1.0;
+            let mouth = ((fx - 64.0) / 16.0).powi(2) + ((fy - 98.0) / 4.0).powi(2) < 1.0;
+            if fy < 22.0 {
+                Rgb([60, 40, 25])
+            } else if eye(46.0) || eye(82.0) || mouth {
+                Rgb([70, 30, 30])
+            } else if face < 1.0 {
+                shaded_skin(face.sqrt() * 52.0)
+            } else {
+                Rgb([200, 210, 220])
+            }
+        });
+        let score = classify(&DynamicImage::ImageRgb8(img));
+        let default_threshold = crate::idguard::IdgConfig::default().nsfw_threshold;
+        assert!(score.confidence * 100.0 < default_threshold as f32, "{score:?}");
+    }
+}
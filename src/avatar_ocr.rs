@@ -0,0 +1,443 @@
+// src/avatar_ocr.rs
+//
+// Offline OCR napisów na avatarach (IdGuard: sygnał AvatarOCR). Bez usług sieciowych i
+// modeli: binaryzacja Otsu, spójne składowe jako znaki, grupowanie w linie i dopasowanie
+// do wbudowanego fontu 5×7 (A–Z, a–z, 0–9). Celuje w proste, kontrastowe napisy typu
+// „DISCORD STAFF” / „nitro free”. Odczytuje tylko tekst o kształtach bliskich bitmapowemu
+// fontowi (także przeskalowany, wygładzony czy pogrubiony) – typowe fonty wektorowe
+// (szeryfy, kursywa, ozdobne kroje) zwykle nie zostaną odczytane, więc brak wyniku
+// nie znaczy, że na avatarze nie ma napisu.
+
+use image::{imageops::FilterType, DynamicImage, GrayImage};
+use once_cell::sync::Lazy;
+
+/// Bok roboczego obrazu (avatary są kwadratowe, zwykle 128–1024 px).
+const CANVAS: u32 = 256;
+/// Siatka porównania znaku z wzorcem.
+const GRID_W: usize = 10;
+const GRID_H: usize = 14;
+/// Maksymalna odległość od wzorca, przy której znak uznajemy za odczytany.
+const MAX_GLYPH_DIST: f32 = 0.30;
+/// Linia musi mieć tyle odczytanych znaków, żeby trafić do wyniku (odsiewa szum).
+const MIN_LINE_GLYPHS: usize = 3;
+
+/// Odczytany tekst (linie rozdzielone " | "), albo None gdy nic sensownego.
+pub fn read_text(img: &DynamicImage) -> Option<String> {
+    let gray = img.resize_exact(CANVAS, CANVAS, FilterType::Triangle).to_luma8();
+    let threshold = otsu_threshold(&gray);
+
+    // tekst bywa jasny na ciemnym i odwrotnie – czytamy obie polaryzacje
+    let mut lines = read_polarity(&gray, threshold, true);
+    lines.extend(read_polarity(&gray, threshold, false));
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines.join(" | "))
+}
+
+/* ===========================
+   Segmentacja
+   =========================== */
+
+#[derive(Debug, Clone)]
+struct Blob {
+    x0: usize,
+    y0: usize,
+    x1: usize, // włącznie
+    y1: usize, // włącznie
+    pixels: Vec<(usize, usize)>,
+}
+
+impl Blob {
+    fn w(&self) -> usize { self.x1 - self.x0 + 1 }
+    fn h(&self) -> usize { self.y1 - self.y0 + 1 }
+}
+
+fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut hist = [0u64; 256];
+    for p in gray.pixels() {
+        hist[p.0[0] as usize] += 1;
+    }
+    let total: u64 = hist.iter().sum();
+    let sum_all: f64 = hist.iter().enumerate().map(|(i, &c)| i as f64 * c as f64).sum();
+
+    let (mut best_t, mut best_var) = (127u8, 0.0f64);
+    let (mut w_bg, mut sum_bg) = (0u64, 0.0f64);
+    for (t, &count) in hist.iter().enumerate() {
+        w_bg += count;
+        if w_bg == 0 || w_bg == total {
+            continue;
+        }
+        sum_bg += t as f64 * count as f64;
+        let w_fg = total - w_bg;
+        let m_bg = sum_bg / w_bg as f64;
+        let m_fg = (sum_all - sum_bg) / w_fg as f64;
+        let var = w_bg as f64 * w_fg as f64 * (m_bg - m_fg).powi(2);
+        if var > best_var {
+            best_var = var;
+            best_t = t as u8;
+        }
+    }
+    best_t
+}
+
+fn read_polarity(gray: &GrayImage, threshold: u8, dark_ink: bool) -> Vec<String> {
+    let (w, h) = (gray.width() as usize, gray.height() as usize);
+    let ink: Vec<bool> = gray
+        .pixels()
+        .map(|p| if dark_ink { p.0[0] <= threshold } else { p.0[0] > threshold })
+        .collect();
+
+    let blobs: Vec<Blob> = components(&ink, w, h)
+        .into_iter()
+        .filter(|b| plausible_glyph(b, w, h))
+        .collect();
+
+    group_lines(blobs)
+        .into_iter()
+        .filter_map(|line| recognize_line(&line))
+        .collect()
+}
+
+/// Spójne składowe (8-sąsiedztwo), iteracyjnie – bez rekurencji.
+fn components(ink: &[bool], w: usize, h: usize) -> Vec<Blob> {
+    let mut seen = vec![false; ink.len()];
+    let mut out = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in 0..ink.len() {
+        if !ink[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let mut blob = Blob { x0: usize::MAX, y0: usize::MAX, x1: 0, y1: 0, pixels: Vec::new() };
+
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            blob.x0 = blob.x0.min(x);
+            blob.y0 = blob.y0.min(y);
+            blob.x1 = blob.x1.max(x);
+            blob.y1 = blob.y1.max(y);
+            blob.pixels.push((x, y));
+
+            for dy in -1i32..=1 {
+                for dx in -1i32..=1 {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 || nx >= w as i32 || ny >= h as i32 {
+                        continue;
+                    }
+                    let j = ny as usize * w + nx as usize;
+                    if ink[j] && !seen[j] {
+                        seen[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+        out.push(blob);
+    }
+    out
+}
+
+/// Odrzuć tło, ramki i drobny szum – zostają kształty o rozmiarze litery.
+fn plausible_glyph(b: &Blob, w: usize, h: usize) -> bool {
+    let (bw, bh) = (b.w(), b.h());
+    let fill = b.pixels.len() as f32 / (bw * bh) as f32;
+    bh >= 8
+        && bh <= h * 2 / 5
+        && bw >= 2
+        && bw <= w * 2 / 5
+        && bw <= bh * 2
+        && fill >= 0.10
+        && b.x0 > 0
+        && b.y0 > 0
+        && b.x1 < w - 1
+        && b.y1 < h - 1
+}
+
+/// Linie: składowe o zbliżonej wysokości, nachodzące na siebie w pionie.
+fn group_lines(mut blobs: Vec<Blob>) -> Vec<Vec<Blob>> {
+    blobs.sort_by_key(|b| (b.y0 + b.y1) / 2);
+    let mut lines: Vec<Vec<Blob>> = Vec::new();
+
+    for b in blobs {
+        let home = lines.iter_mut().find(|line| {
+            let (ly0, ly1) = (
+                line.iter().map(|x| x.y0).min().unwrap_or(0),
+                line.iter().map(|x| x.y1).max().unwrap_or(0),
+            );
+            let overlap = (b.y1.min(ly1) + 1).saturating_sub(b.y0.max(ly0));
+            let line_h = ly1 - ly0 + 1;
+            overlap * 2 >= b.h().min(line_h) && b.h() * 2 >= line_h / 2
+        });
+        match home {
+            Some(line) => line.push(b),
+            None => lines.push(vec![b]),
+        }
+    }
+
+    for line in &mut lines {
+        line.sort_by_key(|b| b.x0);
+        // kropki nad i/j oraz szum: dużo niższe od mediany linii
+        let mut heights: Vec<usize> = line.iter().map(Blob::h).collect();
+        heights.sort_unstable();
+        let median = heights[heights.len() / 2];
+        line.retain(|b| b.h() * 100 >= median * 35);
+    }
+    lines.retain(|l| l.len() >= MIN_LINE_GLYPHS);
+    lines
+}
+
+/* ===========================
+   Rozpoznawanie
+   =========================== */
+
+fn recognize_line(line: &[Blob]) -> Option<String> {
+    let mut heights: Vec<usize> = line.iter().map(Blob::h).collect();
+    heights.sort_unstable();
+    let median_h = heights[heights.len() / 2];
+
+    let mut text = String::new();
+    let mut recognized = 0usize;
+    let mut prev_x1: Option<usize> = None;
+
+    for b in line {
+        if let Some(px) = prev_x1
+            && b.x0 > px
+            && (b.x0 - px) * 2 > median_h
+        {
+            text.push(' ');
+        }
+        prev_x1 = Some(b.x1);
+
+        match classify(b) {
+            Some(c) => {
+                text.push(c);
+                recognized += 1;
+            }
+            None => text.push('?'),
+        }
+    }
+
+    (recognized >= MIN_LINE_GLYPHS).then(|| text.trim().to_string())
+}
+
+fn classify(b: &Blob) -> Option<char> {
+    let (bw, bh) = (b.w(), b.h());
+    let mut mask = vec![false; bw * bh];
+    for &(x, y) in &b.pixels {
+        mask[(y - b.y0) * bw + (x - b.x0)] = true;
+    }
+    let grid = sample_grid(&mask, bw, bh);
+    let aspect = bw as f32 / bh as f32;
+
+    TEMPLATES
+        .iter()
+        .map(|t| {
+            let diff: f32 = grid.iter().zip(t.grid.iter()).map(|(a, b)| (a - b).abs()).sum::<f32>()
+                / (GRID_W * GRID_H) as f32;
+            (t.ch, diff + 0.15 * (aspect / t.aspect).ln().abs())
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .filter(|(_, d)| *d <= MAX_GLYPH_DIST)
+        .map(|(c, _)| c)
+}
+
+/// Maska w ramce znaku -> siatka GRID_W×GRID_H z udziałem „tuszu” w komórce.
+fn sample_grid(mask: &[bool], w: usize, h: usize) -> Vec<f32> {
+    let mut out = Vec::with_capacity(GRID_W * GRID_H);
+    for gy in 0..GRID_H {
+        let y0 = gy * h / GRID_H;
+        let y1 = ((gy + 1) * h).div_ceil(GRID_H).max(y0 + 1).min(h);
+        for gx in 0..GRID_W {
+            let x0 = gx * w / GRID_W;
+            let x1 = ((gx + 1) * w).div_ceil(GRID_W).max(x0 + 1).min(w);
+            let mut on = 0usize;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    if mask[y * w + x] {
+                        on += 1;
+                    }
+                }
+            }
+            out.push(on as f32 / ((y1 - y0) * (x1 - x0)) as f32);
+        }
+    }
+    out
+}
+
+/* ===========================
+   Wbudowany font 5×7
+   =========================== */
+
+struct Template {
+    ch: char,
+    grid: Vec<f32>,
+    aspect: f32,
+}
+
+static TEMPLATES: Lazy<Vec<Template>> = Lazy::new(|| {
+    FONT.iter()
+        .map(|(ch, rows)| {
+            let (mask, w, h) = crop_rows(rows);
+            Template { ch: *ch, grid: sample_grid(&mask, w, h), aspect: w as f32 / h as f32 }
+        })
+        .collect()
+});
+
+/// Wiersze fontu przycięte do ramki znaku (tak samo jak składowe z obrazu).
+fn crop_rows(rows: &[&str; 7]) -> (Vec<bool>, usize, usize) {
+    let on = |x: usize, y: usize| rows[y].as_bytes()[x] == b'#';
+    let xs = (0..5).filter(|&x| (0..7).any(|y| on(x, y)));
+    let ys = (0..7).filter(|&y| (0..5).any(|x| on(x, y)));
+    let (x0, x1) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(4));
+    let (y0, y1) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(6));
+
+    let (w, h) = (x1 - x0 + 1, y1 - y0 + 1);
+    let mut mask = Vec::with_capacity(w * h);
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            mask.push(on(x, y));
+        }
+    }
+    (mask, w, h)
+}
+
+// i/j bez kropek – kropka to osobna, odrzucana składowa
+const FONT: &[(char, [&str; 7])] = &[
+    ('a', [".....", ".....", ".###.", "....#", ".####", "#...#", ".####"]),
+    ('b', ["#....", "#....", "#.##.", "##..#", "#...#", "#...#", "####."]),
+    ('c', [".....", ".....", ".###.", "#....", "#....", "#...#", ".###."]),
+    ('d', ["....#", "....#", ".##.#", "#..##", "#...#", "#...#", ".####"]),
+    ('e', [".....", ".....", ".###.", "#...#", "#####", "#....", ".###."]),
+    ('f', ["..##.", ".#..#", ".#...", "###..", ".#...", ".#...", ".#..."]),
+    ('g', [".....", ".####", "#...#", "#...#", ".####", "....#", ".###."]),
+    ('h', ["#....", "#....", "#.##.", "##..#", "#...#", "#...#", "#...#"]),
+    ('i', [".....", ".....", ".##..", "..#..", "..#..", "..#..", ".###."]),
+    ('j', [".....", ".....", "..##.", "...#.", "...#.", "#..#.", ".##.."]),
+    ('k', ["#....", "#....", "#..#.", "#.#..", "##...", "#.#..", "#..#."]),
+    ('l', [".##..", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('m', [".....", ".....", "##.#.", "#.#.#", "#.#.#", "#...#", "#...#"]),
+    ('n', [".....", ".....", "#.##.", "##..#", "#...#", "#...#", "#...#"]),
+    ('o', [".....", ".....", ".###.", "#...#", "#...#", "#...#", ".###."]),
+    ('p', [".....", ".....", "####.", "#...#", "####.", "#....", "#...."]),
+    ('q', [".....", ".....", ".##.#", "#..##", ".####", "....#", "....#"]),
+    ('r', [".....", ".....", "#.##.", "##..#", "#....", "#....", "#...."]),
+    ('s', [".....", ".....", ".###.", "#....", ".###.", "....#", "####."]),
+    ('t', [".#...", ".#...", "###..", ".#...", ".#...", ".#..#", "..##."]),
+    ('u', [".....", ".....", "#...#", "#...#", "#...#", "#..##", ".##.#"]),
+    ('v', [".....", ".....", "#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+    ('w', [".....", ".....", "#...#", "#...#", "#.#.#", "#.#.#", ".#.#."]),
+    ('x', [".....", ".....", "#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+    ('y', [".....", ".....", "#...#", "#...#", ".####", "....#", ".###."]),
+    ('z', [".....", ".....", "#####", "...#.", "..#..", ".#...", "#####"]),
+    ('A', [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('B', ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."]),
+    ('C', [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."]),
+    ('D', ["####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####."]),
+    ('E', ["#####", "#....", "#....", "####.", "#....", "#....", "#####"]),
+    ('F', ["#####", "#....", "#....", "####.", "#....", "#....", "#...."]),
+    ('G', [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"]),
+    ('H', ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('I', [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('J', ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."]),
+    ('K', ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"]),
+    ('L', ["#....", "#....", "#....", "#....", "#....", "#....", "#####"]),
+    ('M', ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"]),
+    ('N', ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"]),
+    ('O', [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('P', ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."]),
+    ('Q', [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"]),
+    ('R', ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"]),
+    ('S', [".####", "#....", "#....", ".###.", "....#", "....#", "####."]),
+    ('T', ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."]),
+    ('U', ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('V', ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+    ('W', ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."]),
+    ('X', ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."]),
+    ('Z', ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"]),
+    ('0', [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."]),
+    ('1', ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('2', [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"]),
+    ('3', ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."]),
+    ('4', ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."]),
+    ('5', ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."]),
+    ('6', ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."]),
+    ('7', ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."]),
+    ('8', [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."]),
+    ('9', [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Luma, RgbImage};
+
+    /// Napis wyrenderowany wbudowanym fontem (skala `scale`), ciemny na jasnym tle.
+    fn render(text: &str, scale: u32) -> DynamicImage {
+        render_on(text, scale, CANVAS, 12, 100)
+    }
+
+    fn render_on(text: &str, scale: u32, side: u32, x0: u32, y0: u32) -> DynamicImage {
+        let mut img = GrayImage::from_pixel(side, side, Luma([235]));
+        let (mut cx, cy) = (x0, y0);
+        for ch in text.chars() {
+            if let Some((_, rows)) = FONT.iter().find(|(c, _)| *c == ch) {
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, b) in row.bytes().enumerate() {
+                        if b != b'#' {
+                            continue;
+                        }
+                        for dy in 0..scale {
+                            for dx in 0..scale {
+                                img.put_pixel(cx + x as u32 * scale + dx, cy + y as u32 * scale + dy, Luma([20]));
+                            }
+                        }
+                    }
+                }
+            }
+            cx += 6 * scale;
+        }
+        DynamicImage::ImageLuma8(img)
+    }
+
+    #[test]
+    fn reads_rendered_text() {
+        let text = read_text(&render("NITRO FREE", 4)).unwrap_or_default();
+        assert!(text.to_lowercase().contains("nitro free"), "{text}");
+    }
+
+    #[test]
+    fn reads_scaled_antialiased_bold_text() {
+        // jak prawdziwy avatar: inna rozdzielczość, pogrubione kreski, dwukrotne
+        // niecałkowite skalowanie z wygładzaniem (upload → CDN → nasz CANVAS)
+        let base = render_on("NITRO FREE", 5, 700, 20, 300).to_luma8();
+        let bold = GrayImage::from_fn(700, 700, |x, y| {
+            let left = base.get_pixel(x.saturating_sub(1), y).0[0];
+            Luma([base.get_pixel(x, y).0[0].min(left)])
+        });
+        let cdn = DynamicImage::ImageLuma8(bold).resize_exact(410, 410, FilterType::Lanczos3);
+        let text = read_text(&cdn).unwrap_or_default();
+        assert!(text.to_lowercase().contains("nitro free"), "{text}");
+    }
+
+    #[test]
+    fn shapes_are_not_read_as_text() {
+        // kropki i paski o rozmiarach liter – bez kształtów znaków
+        let img = GrayImage::from_fn(CANVAS, CANVAS, |x, y| {
+            let dot = (x % 24 < 10) && (y % 40 < 10);
+            let bar = (60..200).contains(&y) && (x % 32 < 3);
+            Luma(if dot || bar { [20] } else { [235] })
+        });
+        let text = read_text(&DynamicImage::ImageLuma8(img)).unwrap_or_default();
+        assert!(!text.chars().any(|c| c.is_ascii_alphabetic()), "{text}");
+    }
+
+    #[test]
+    fn plain_image_has_no_text() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(CANVAS, CANVAS, image::Rgb([40, 120, 200])));
+        assert_eq!(read_text(&img), None);
+    }
+}
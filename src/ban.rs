@@ -1,10 +1,10 @@
 // src/ban.rs
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use dashmap::DashMap;
-use once_cell::sync::Lazy;
+use sqlx::{Pool, Postgres, Row};
 use tokio::time::sleep;
 
 use serenity::all::{
@@ -12,10 +12,17 @@ use serenity::all::{
     CommandOptionType, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateCommand,
     CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
     CreateInteractionResponseMessage, CreateInputText, CreateModal, CreateSelectMenu,
-    CreateSelectMenuKind, CreateSelectMenuOption, EditMessage, GuildId, InputTextStyle,
-    Interaction, MessageId, Permissions, UserId, Colour, Timestamp,
+    CreateSelectMenuKind, CreateSelectMenuOption, GuildId, InputTextStyle,
+    Interaction, MessageId, Permissions, UserId, Colour, Timestamp, CreateMessage, Http, HttpError,
+    EditInteractionResponse, AutocompleteChoice, CreateAutocompleteResponse,
 };
+use serenity::http::{LightMethod, Request, Route};
 
+use crate::discord::router::Routes;
+use crate::appeals;
+use crate::cases::{self, CaseAction, CaseRef, NewCase};
+use crate::permissions::{self, Permission};
+use crate::registry::env_channels;
 use crate::AppContext;
 
 /* ==========================================
@@ -25,8 +32,15 @@ use crate::AppContext;
 const SYSTEM_NAME: &str = "Tigris Ban Panel";
 const SERVER_NAME: &str = "Unfaithful";
 
-// zostaw puste "" jeśli nie chcesz logów
-const LOG_CHANNEL_ID: &str = "1408795534973468793";
+/// Co ile scheduler sprawdza wygasłe bany tymczasowe.
+const EXPIRY_TICK: Duration = Duration::from_secs(60);
+/// Ile wygasłych banów zdejmujemy w jednym przebiegu (reszta w kolejnym).
+const EXPIRY_BATCH: i64 = 50;
+/// Czas życia sesji panelu liczony od utworzenia (bez przedłużania – token interakcji,
+/// którym edytujemy panel, ważny jest 15 min). Zapas na tick sweepera, żeby zdążył
+/// oznaczyć panel jako wygasły, póki token działa.
+const PANEL_TTL_SECS: i64 = 13 * 60;
+
 // === Konfiguracja (na górze pliku) ===
 const PERMABAN_VIDEO_URL: &str = "https://www.youtube.com/watch?v=PLteDgvYKIM&ab_channel=BrzydkiBurak";
 
@@ -48,11 +62,14 @@ struct CaseState {
     kind: Option<BanType>,
     duration: Option<Duration>,
     reason: Option<String>,
+    /// Ile sekund historii wiadomości usunąć przy banie (0 = nic).
+    purge_secs: u32,
     panel_msg: Option<(ChannelId, MessageId)>,
+    /// Token interakcji, której odpowiedzią jest aktualny panel (ephemeral –
+    /// edytować go można tylko przez webhook interakcji).
+    panel_token: Option<String>,
 }
 
-static CASES: Lazy<DashMap<String, CaseState>> = Lazy::new(DashMap::new);
-
 fn case_id_from(inter_id: u64, moderator: UserId) -> String {
     format!("{inter_id}-{}", moderator.get())
 }
@@ -65,32 +82,88 @@ pub struct BanPanel;
 pub type Ban = BanPanel;
 
 impl BanPanel {
-    pub async fn register_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-        guild_id
-            .create_command(
-                &ctx.http,
-                CreateCommand::new("ban")
-                    .description("Panel bana (perm/temp) z potwierdzeniem")
-                    .add_option(
-                        CreateCommandOption::new(
-                            CommandOptionType::User,
-                            "user",
-                            "Użytkownik do zbanowania",
-                        )
-                        .required(true),
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &["ban", "unban"],
+        components: &["banp:"],
+        modals: &["banp:"],
+    };
+
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new("ban")
+                .description("Panel bana (perm/temp) z potwierdzeniem")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::User,
+                        "user",
+                        "Użytkownik do zbanowania",
                     )
-                    .default_member_permissions(Permissions::BAN_MEMBERS),
-            )
-            .await?;
-        Ok(())
+                    .required(true),
+                )
+                .default_member_permissions(Permissions::BAN_MEMBERS),
+            CreateCommand::new("unban")
+                .description("Zdejmij bana (z powodem, zamyka sprawę bana)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "user_id",
+                        "Zbanowany użytkownik (ID lub wybierz z listy)",
+                    )
+                    .required(true)
+                    .set_autocomplete(true),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::String, "reason", "Powód zdjęcia bana")
+                        .required(true),
+                )
+                .default_member_permissions(Permissions::BAN_MEMBERS),
+        ]
+    }
+
+    /// Scheduler banów tymczasowych (startowany z `AppContext::bootstrap`).
+    /// Pierwszy przebieg od razu – zdejmuje bany, które wygasły w czasie, gdy bot nie działał –
+    /// a potem co `EXPIRY_TICK`. Przy okazji sprząta wygasłe sesje panelu /ban.
+    pub fn spawn_expiry_scheduler(app: Arc<AppContext>) {
+        let http = Arc::new(Http::new(&app.settings.discord.token));
+        tokio::spawn(async move {
+            let mut bot_id: Option<u64> = None;
+            loop {
+                if bot_id.is_none() {
+                    bot_id = http.get_current_user().await.ok().map(|u| u.id.get());
+                }
+                if let Some(bot) = bot_id
+                    && let Err(e) = lift_expired_bans(&http, &app, bot).await
+                {
+                    tracing::warn!(error=?e, "ban expiry scheduler tick failed");
+                }
+                if let Err(e) = sweep_expired_panels(&http, &app.db).await {
+                    tracing::warn!(error=?e, "ban panel sweep failed");
+                }
+                sleep(EXPIRY_TICK).await;
+            }
+        });
     }
 
-    pub async fn on_interaction(ctx: &Context, _app: &AppContext, interaction: Interaction) {
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        if let Some(ac) = interaction.clone().autocomplete() {
+            if ac.data.name == "unban"
+                && let Err(e) = handle_unban_autocomplete(ctx, &ac).await
+            {
+                tracing::warn!(error=?e, "unban autocomplete failed");
+            }
+            return;
+        }
+
         if let Some(cmd) = interaction.clone().command() {
             if cmd.data.name == "ban" {
-                if let Err(e) = handle_ban_slash(ctx, &cmd).await {
+                if let Err(e) = handle_ban_slash(ctx, app, &cmd).await {
                     tracing::warn!(error=?e, "ban slash failed");
                 }
+            } else if cmd.data.name == "unban"
+                && let Err(e) = handle_unban_slash(ctx, app, &cmd).await
+            {
+                tracing::warn!(error=?e, "unban slash failed");
             }
             return;
         }
@@ -98,20 +171,37 @@ impl BanPanel {
         if let Some(comp) = interaction.clone().message_component() {
             let id = comp.data.custom_id.as_str();
 
-            if id.starts_with("banp:type:")    { let _ = on_type_select(ctx, &comp).await;    return; }
-            if id.starts_with("banp:dur:")     { let _ = on_duration_select(ctx, &comp).await; return; }
-            if id.starts_with("banp:reason:")  { let _ = on_reason_modal_open(ctx, &comp).await; return; }
-            if id.starts_with("banp:refresh:") { let _ = on_refresh(ctx, &comp).await;        return; }
-            if id.starts_with("banp:proceed:") { let _ = on_proceed(ctx, &comp).await;        return; }
-            if id.starts_with("banp:cancel:")  { let _ = on_cancel(ctx, &comp).await;         return; }
-            if id.starts_with("banp:confirm:") { let _ = on_confirm(ctx, &comp).await;        return; }
+            let res = if id.starts_with("banp:type:") {
+                on_type_select(ctx, app, &comp).await
+            } else if id.starts_with("banp:dur:") {
+                on_duration_select(ctx, app, &comp).await
+            } else if id.starts_with("banp:purge:") {
+                on_purge_select(ctx, app, &comp).await
+            } else if id.starts_with("banp:reason:") {
+                on_reason_modal_open(ctx, app, &comp).await
+            } else if id.starts_with("banp:refresh:") {
+                on_refresh(ctx, app, &comp).await
+            } else if id.starts_with("banp:proceed:") {
+                on_proceed(ctx, app, &comp).await
+            } else if id.starts_with("banp:cancel:") {
+                on_cancel(ctx, app, &comp).await
+            } else if id.starts_with("banp:confirm:") {
+                on_confirm(ctx, app, &comp).await
+            } else {
+                return;
+            };
+            if let Err(e) = res {
+                tracing::warn!(error=?e, custom_id = %comp.data.custom_id, "ban panel component failed");
+            }
 
             return;
         }
 
         if let Some(modal) = interaction.modal_submit() {
-            if modal.data.custom_id.starts_with("banp:modalreason:") {
-                let _ = on_reason_modal_submit(ctx, &modal).await;
+            if modal.data.custom_id.starts_with("banp:modalreason:")
+                && let Err(e) = on_reason_modal_submit(ctx, app, &modal).await
+            {
+                tracing::warn!(error=?e, "ban panel reason submit failed");
             }
             return;
         }
@@ -122,7 +212,7 @@ impl BanPanel {
    Handlery SLASH / komponenty / modale
    ========================================== */
 
-async fn handle_ban_slash(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
+async fn handle_ban_slash(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
     let Some(gid) = cmd.guild_id else {
         cmd.create_response(
             &ctx.http,
@@ -136,7 +226,7 @@ async fn handle_ban_slash(ctx: &Context, cmd: &CommandInteraction) -> Result<()>
         return Ok(());
     };
 
-    if !user_can_ban(ctx, gid, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Ban) {
         cmd.create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
@@ -184,23 +274,23 @@ async fn handle_ban_slash(ctx: &Context, cmd: &CommandInteraction) -> Result<()>
         return Ok(());
     }
 
-    // zainicjuj state
+    // zainicjuj state (w DB – przeżyje restart bota)
     let case_id = case_id_from(cmd.id.get(), cmd.user.id);
-    CASES.insert(
-        case_id.clone(),
-        CaseState {
-            guild_id: gid,
-            moderator_id: cmd.user.id,
-            target_id: target,
-            kind: None,
-            duration: None,
-            reason: None,
-            panel_msg: None,
-        },
-    );
+    let mut st = CaseState {
+        guild_id: gid,
+        moderator_id: cmd.user.id,
+        target_id: target,
+        kind: None,
+        duration: None,
+        reason: None,
+        purge_secs: 0,
+        panel_msg: None,
+        panel_token: Some(cmd.token.clone()),
+    };
+    save_session(&app.db, &case_id, &st).await?;
 
     // panel startowy
-    let embed = summary_embed(&case_id);
+    let embed = summary_embed(&st);
     let components = vec![CreateActionRow::SelectMenu(
         CreateSelectMenu::new(
             format!("banp:type:{case_id}"),
@@ -229,25 +319,129 @@ async fn handle_ban_slash(ctx: &Context, cmd: &CommandInteraction) -> Result<()>
 
     // zapisz kanał/wiadomość
     if let Ok(msg) = cmd.get_response(&ctx.http).await {
-        CASES.alter(&case_id, |_, mut s| {
-            s.panel_msg = Some((msg.channel_id, msg.id));
-            s
-        });
+        st.panel_msg = Some((msg.channel_id, msg.id));
+        save_session(&app.db, &case_id, &st).await?;
     }
 
     Ok(())
 }
 
-async fn on_type_select(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+/* ---------- /unban ---------- */
+
+async fn handle_unban_slash(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_unban_reply(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Unban) {
+        return edit_unban_reply(ctx, cmd, "⛔ Brak uprawnień do zdejmowania banów.").await;
+    }
+
+    let mut raw_id: Option<String> = None;
+    let mut reason: Option<String> = None;
+    for o in &cmd.data.options {
+        match (o.name.as_str(), &o.value) {
+            ("user_id", CommandDataOptionValue::String(s)) => raw_id = Some(s.trim().to_string()),
+            ("reason", CommandDataOptionValue::String(s)) => reason = Some(s.trim().to_string()),
+            _ => {}
+        }
+    }
+    // akceptujemy też wklejoną wzmiankę <@123>
+    let Some(uid) = raw_id
+        .as_deref()
+        .map(|s| s.trim_start_matches("<@").trim_start_matches('!').trim_end_matches('>'))
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|v| *v != 0)
+        .map(UserId::new)
+    else {
+        return edit_unban_reply(ctx, cmd, "Podaj poprawne ID użytkownika.").await;
+    };
+    let reason = reason.filter(|r| !r.is_empty()).unwrap_or_else(|| "Brak powodu".into());
+
+    // zdjęcie bana + zamknięcie sprawy (scheduler już jej nie ruszy) + log
+    let was_banned = match lift_ban(&ctx.http, app, gid, uid, cmd.user.id, &reason, None).await {
+        Ok(v) => v,
+        Err(e) => {
+            return edit_unban_reply(ctx, cmd, &format!("⛔ Nie udało się zdjąć bana: {e}")).await;
+        }
+    };
+    if !was_banned {
+        return edit_unban_reply(
+            ctx,
+            cmd,
+            &format!("ℹ️ <@{}> nie jest zbanowany. Otwarte sprawy bana (jeśli były) zostały zamknięte.", uid.get()),
+        )
+        .await;
+    }
+
+    let dm_ok = send_unban_dm(ctx, uid, &reason).await.is_ok();
+
+    let e = CreateEmbed::new()
+        .title("🔓 Ban zdjęty")
+        .colour(Colour::new(0x2ECC71))
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+        .field("Użytkownik", format!("<@{}> (`{}`)", uid.get(), uid.get()), true)
+        .field("DM", if dm_ok { "✅ wysłano" } else { "— nie udało się" }, true)
+        .field("Powód", format!("```{}```", shorten_code_block(&reason, 900)), false);
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(e))
+        .await?;
+    Ok(())
+}
+
+/// Podpowiedzi z aktualnej listy banów gildii (po nazwie albo ID).
+async fn handle_unban_autocomplete(ctx: &Context, ac: &CommandInteraction) -> Result<()> {
+    let Some(gid) = ac.guild_id else { return Ok(()) };
+    let needle = ac
+        .data
+        .autocomplete()
+        .map(|o| o.value.trim().to_lowercase())
+        .unwrap_or_default();
+
+    let bans = gid.bans(&ctx.http, None, None).await.unwrap_or_default();
+    let choices = bans
+        .into_iter()
+        .filter(|b| {
+            needle.is_empty()
+                || b.user.name.to_lowercase().contains(&needle)
+                || b.user.id.get().to_string().starts_with(&needle)
+        })
+        .take(25)
+        .map(|b| {
+            let label = format!("{} ({})", b.user.name, b.user.id.get());
+            AutocompleteChoice::new(label.chars().take(100).collect::<String>(), b.user.id.get().to_string())
+        })
+        .collect();
+
+    ac.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Autocomplete(CreateAutocompleteResponse::new().set_choices(choices)),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn edit_unban_reply(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
+
+async fn on_type_select(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    if !guard_current_panel(ctx, &case_id, comp).await? { return Ok(()); }
+    let Some(mut st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
     let Some(val) = first_value(comp) else { return Ok(()); };
-    let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
     let kind = if val == "perma" { BanType::Perma } else { BanType::Temp };
 
-    CASES.alter(&case_id, |_, mut s| { s.kind = Some(kind); s });
+    st.kind = Some(kind);
+    save_session(&app.db, &case_id, &st).await?;
 
-    let (embed, comps) = ui_for_case(&case_id);
+    let (embed, comps) = ui_for_case(&case_id, &st);
     comp.create_response(
         &ctx.http,
         CreateInteractionResponse::UpdateMessage(
@@ -258,15 +452,38 @@ async fn on_type_select(ctx: &Context, comp: &ComponentInteraction) -> Result<()
     Ok(())
 }
 
-async fn on_duration_select(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+async fn on_duration_select(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    if !guard_current_panel(ctx, &case_id, comp).await? { return Ok(()); }
+    let Some(mut st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
     let Some(secs) = first_value(comp).and_then(|s| s.parse::<u64>().ok()) else { return Ok(()); };
+
+    st.duration = Some(Duration::from_secs(secs));
+    save_session(&app.db, &case_id, &st).await?;
+
+    let (embed, comps) = ui_for_case(&case_id, &st);
+    comp.create_response(
+        &ctx.http,
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new().add_embed(embed).components(comps),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn on_purge_select(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
+    let Some(mut st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
+    let Some(secs) = first_value(comp).and_then(|s| s.parse::<u32>().ok()) else { return Ok(()); };
+    // tylko wartości z listy (Discord i tak nie przyjmie > 7 dni)
+    if !PURGE_OPTIONS.iter().any(|(v, _)| *v == secs) { return Ok(()); }
 
-    CASES.alter(&case_id, |_, mut s| { s.duration = Some(Duration::from_secs(secs)); s });
+    st.purge_secs = secs;
+    save_session(&app.db, &case_id, &st).await?;
 
-    let (embed, comps) = ui_for_case(&case_id);
+    let (embed, comps) = ui_for_case(&case_id, &st);
     comp.create_response(
         &ctx.http,
         CreateInteractionResponse::UpdateMessage(
@@ -277,8 +494,10 @@ async fn on_duration_select(ctx: &Context, comp: &ComponentInteraction) -> Resul
     Ok(())
 }
 
-async fn on_reason_modal_open(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+async fn on_reason_modal_open(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
+    let Some(st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
 
     let modal = CreateModal::new(format!("banp:modalreason:{case_id}"), "Powód bana")
         .components(vec![CreateActionRow::InputText(
@@ -294,11 +513,23 @@ async fn on_reason_modal_open(ctx: &Context, comp: &ComponentInteraction) -> Res
 
 async fn on_reason_modal_submit(
     ctx: &Context,
+    app: &AppContext,
     modal: &serenity::all::ModalInteraction,
 ) -> Result<()> {
     let Some(case_id) = modal.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else {
         return Ok(());
     };
+    let Some(mut st) = load_session(&app.db, &case_id).await? else {
+        modal.create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(EXPIRED_NOTE)
+                    .ephemeral(true),
+            ),
+        ).await?;
+        return Ok(());
+    };
 
     // wyciągamy treść z InputText
     let mut reason_val: Option<String> = None;
@@ -327,11 +558,17 @@ async fn on_reason_modal_submit(
         return Ok(());
     }
 
+    // poprzedni panel zastępujemy nowym – stary czyścimy, żeby nie zostały martwe przyciski
+    clear_panel(&ctx.http, &st, "↪️ Panel przeniesiony niżej.").await;
+
     // zapis do stanu
-    CASES.alter(&case_id, |_, mut s| { s.reason = Some(reason); s });
+    st.reason = Some(reason);
+    st.panel_msg = None;
+    st.panel_token = Some(modal.token.clone());
+    save_session(&app.db, &case_id, &st).await?;
 
     // zbuduj nowy panel i wyślij go jako ODPOWIEDŹ na modal
-    let (embed, comps) = ui_for_case(&case_id);
+    let (embed, comps) = ui_for_case(&case_id, &st);
     modal.create_response(
         &ctx.http,
         CreateInteractionResponse::Message(
@@ -345,17 +582,18 @@ async fn on_reason_modal_submit(
 
     // pobierz id tej nowej wiadomości i ustaw jako „aktualny panel”
     if let Ok(msg) = modal.get_response(&ctx.http).await {
-        CASES.alter(&case_id, |_, mut s| { s.panel_msg = Some((msg.channel_id, msg.id)); s });
+        st.panel_msg = Some((msg.channel_id, msg.id));
+        save_session(&app.db, &case_id, &st).await?;
     }
 
     Ok(())
 }
 
-async fn on_refresh(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+async fn on_refresh(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    if !guard_current_panel(ctx, &case_id, comp).await? { return Ok(()); }
-    let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    let (embed, comps) = ui_for_case(&case_id);
+    let Some(st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
+    let (embed, comps) = ui_for_case(&case_id, &st);
     comp.create_response(
         &ctx.http,
         CreateInteractionResponse::UpdateMessage(
@@ -366,11 +604,10 @@ async fn on_refresh(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
     Ok(())
 }
 
-async fn on_proceed(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
-    let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    if !guard_current_panel(ctx, &case_id, comp).await? { return Ok(()); }
+async fn on_proceed(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    let Some(st) = CASES.get(&case_id).map(|e| e.clone()) else { return Ok(()); };
+    let Some(st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
 
     match st.kind {
         Some(BanType::Perma) => {
@@ -412,11 +649,10 @@ async fn on_proceed(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
     Ok(())
 }
 
-async fn on_cancel(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+async fn on_cancel(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    if !guard_current_panel(ctx, &case_id, comp).await? { return Ok(()); }
-    let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string())
-        else { return Ok(()); };
+    let Some(st) = session_or_expired(ctx, app, comp, &case_id).await? else { return Ok(()); };
+    if !guard_current_panel(ctx, &st, comp).await? { return Ok(()); }
 
     // wyczyść panel, na którym kliknięto
     comp.create_response(
@@ -429,35 +665,27 @@ async fn on_cancel(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
         ),
     ).await?;
 
-    // dodatkowo wyczyść PIERWOTNY panel (jeśli istniał)
-    try_clear_existing_panel(ctx, &case_id, "✅ Panel bana został anulowany.").await;
-
     // usuń stan
-    let _ = CASES.remove(&case_id);
+    delete_session(&app.db, &case_id).await?;
 
     Ok(())
 }
 
-async fn on_confirm(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+async fn on_confirm(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
     let Some(case_id) = comp.data.custom_id.split(':').nth(2).map(|s| s.to_string()) else { return Ok(()); };
-    let Some(st) = CASES.remove(&case_id).map(|(_, v)| v) else { return Ok(()); };
+    // „zabranie” sesji z DB – drugi klik (lub drugi proces) już jej nie dostanie
+    let Some(st) = take_session(&app.db, &case_id).await? else {
+        return expired_panel_response(ctx, comp).await;
+    };
 
-    if !user_can_ban(ctx, st.guild_id, st.moderator_id).await {
+    if !permissions::authorize_user(ctx, st.guild_id, st.moderator_id, Permission::Ban).await {
         ephemeral_note(ctx, comp, "⛔ Utracono uprawnienia do banowania.").await?;
         return Ok(());
     }
 
     let reason_text = st.reason.clone().unwrap_or_else(|| "Brak powodu".into());
 
-    let _ = send_formal_dm(ctx, st.target_id, &st, &reason_text).await;
-
-    let del_days = 0u8;
-    let reason_for_audit = format!("[{}] {}", SYSTEM_NAME, reason_text);
-    if let Err(e) = st
-        .guild_id
-        .ban_with_reason(&ctx.http, st.target_id, del_days, &reason_for_audit)
-        .await
-    {
+    if let Err(e) = execute_ban(ctx, app, &st, &reason_text, None).await {
         comp.create_response(
             &ctx.http,
             CreateInteractionResponse::UpdateMessage(
@@ -467,30 +695,11 @@ async fn on_confirm(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
                     .embeds(vec![]),
             ),
         )
-        
         .await
         .ok();
         return Ok(());
     }
 
-    if let Some(dur) = st.duration {
-        let http = ctx.http.clone();
-        let gid = st.guild_id;
-        let uid = st.target_id;
-        tokio::spawn(async move {
-            sleep(dur).await;
-            let _ = gid.unban(&http, uid).await;
-        });
-    }
-
-    if let Ok(cid) = LOG_CHANNEL_ID.parse::<u64>() {
-        if cid != 0 {
-            let _ = ChannelId::new(cid)
-                .send_message(&ctx.http, make_log_embed(&st, &reason_text))
-                .await;
-        }
-    }
-
     comp.create_response(
         &ctx.http,
         CreateInteractionResponse::UpdateMessage(
@@ -506,10 +715,85 @@ async fn on_confirm(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
     )
     .await
     .ok();
-    try_clear_existing_panel(ctx, &case_id, "✅ Ban wykonany.").await;
     Ok(())
 }
 
+/// Wspólna ścieżka bana (panel /ban oraz automatyczne eskalacje):
+/// DM formalny → ban z powodem w audit logu → zapis sprawy → log.
+/// Zwraca id sprawy w `tss.ban_cases` (None, jeśli zapis się nie udał – ban i tak obowiązuje).
+async fn execute_ban(
+    ctx: &Context,
+    app: &AppContext,
+    st: &CaseState,
+    reason_text: &str,
+    source: Option<&str>,
+) -> Result<Option<i64>> {
+    let _ = send_formal_dm(ctx, st.target_id, st, reason_text).await;
+
+    let reason_for_audit = format!("[{}] {}", SYSTEM_NAME, reason_text);
+    ban_with_purge(&ctx.http, st.guild_id, st.target_id, st.purge_secs, &reason_for_audit).await?;
+
+    // zapis sprawy – dla TEMP z expires_at, zdejmie go scheduler (także po restarcie)
+    let case_id = match insert_ban_case(&app.db, st, reason_text).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::warn!(error=?e, guild_id = st.guild_id.get(), user_id = st.target_id.get(), "ban case insert failed");
+            None
+        }
+    };
+
+    let case_no = cases::record_case_logged(
+        &app.db,
+        &NewCase {
+            guild_id: st.guild_id.get(),
+            user_id: st.target_id.get(),
+            moderator_id: st.moderator_id.get(),
+            action: CaseAction::Ban,
+            reason: reason_text,
+            evidence: None,
+            source,
+            reference: case_id.map(CaseRef::Ban),
+        },
+    )
+    .await;
+
+    if let Some(cid) = log_channel(st.guild_id.get()) {
+        let _ = ChannelId::new(cid)
+            .send_message(&ctx.http, make_log_embed(st, reason_text, source, case_no))
+            .await;
+    }
+
+    // AltGuard: nazwy + avatar zbanowanego -> wykrywanie obejścia bana
+    app.altguard().record_punished(ctx, st.guild_id.get(), st.target_id.get(), "ban");
+
+    Ok(case_id)
+}
+
+/// Ban permanentny bez panelu – dla akcji systemowych (np. eskalacja warnów).
+/// `moderator_id` to wykonawca zapisany w sprawie (zwykle bot).
+pub(crate) async fn apply_auto_ban(
+    ctx: &Context,
+    app: &AppContext,
+    guild_id: GuildId,
+    moderator_id: UserId,
+    target_id: UserId,
+    reason: &str,
+    source: Option<&str>,
+) -> Result<Option<i64>> {
+    let st = CaseState {
+        guild_id,
+        moderator_id,
+        target_id,
+        kind: Some(BanType::Perma),
+        duration: None,
+        reason: Some(reason.to_string()),
+        purge_secs: 0,
+        panel_msg: None,
+        panel_token: None,
+    };
+    execute_ban(ctx, app, &st, reason, source).await
+}
+
 /* ==========================================
    UI helpers (ładny, „na wypasie”)
    ========================================== */
@@ -526,10 +810,44 @@ const DURATIONS: &[(u64, &str)] = &[
     (30 * 86400, "30 dni"),
 ];
 
-fn summary_embed(case_id: &str) -> CreateEmbed {
-    let s = CASES.get(case_id).map(|e| e.clone());
+/// Zakres usuwanej historii wiadomości (Discord: maks. 7 dni).
+const PURGE_OPTIONS: &[(u32, &str)] = &[
+    (0, "Nie usuwaj wiadomości"),
+    (3600, "Ostatnia 1 godzina"),
+    (86400, "Ostatnie 24 godziny"),
+    (7 * 86400, "Ostatnie 7 dni"),
+];
+
+fn fmt_purge(secs: u32) -> &'static str {
+    PURGE_OPTIONS
+        .iter()
+        .find(|(v, _)| *v == secs)
+        .map(|(_, label)| *label)
+        .unwrap_or("—")
+}
+
+fn purge_row(case_id: &str, st: &CaseState) -> CreateActionRow {
+    CreateActionRow::SelectMenu(
+        CreateSelectMenu::new(
+            format!("banp:purge:{case_id}"),
+            CreateSelectMenuKind::String {
+                options: PURGE_OPTIONS
+                    .iter()
+                    .map(|(secs, label)| {
+                        CreateSelectMenuOption::new(*label, secs.to_string())
+                            .default_selection(*secs == st.purge_secs)
+                    })
+                    .collect(),
+            },
+        )
+        .placeholder("Usuń historię wiadomości")
+        .min_values(1)
+        .max_values(1),
+    )
+}
 
-    let (emoji, title_colour) = match s.as_ref().and_then(|x| x.kind) {
+fn summary_embed(st: &CaseState) -> CreateEmbed {
+    let (emoji, title_colour) = match st.kind {
         Some(BanType::Perma) => ("🛑", Colour::new(0xE74C3C)),
         Some(BanType::Temp)  => ("⏳", Colour::new(0xF39C12)),
         None                 => ("❔", Colour::new(0x95A5A6)),
@@ -540,54 +858,51 @@ fn summary_embed(case_id: &str) -> CreateEmbed {
         .colour(title_colour)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
-    if let Some(st) = s {
-        let target = format!("<@{}>", st.target_id.get());
-        let moderator = format!("<@{}>", st.moderator_id.get());
+    let target = format!("<@{}>", st.target_id.get());
+    let moderator = format!("<@{}>", st.moderator_id.get());
 
-        let (typ_emoji, typ_txt) = match st.kind {
-            Some(BanType::Perma) => ("🛑", "Permanentny"),
-            Some(BanType::Temp)  => ("⏳", "Tymczasowy"),
-            None                 => ("❔", "—"),
-        };
+    let (typ_emoji, typ_txt) = match st.kind {
+        Some(BanType::Perma) => ("🛑", "Permanentny"),
+        Some(BanType::Temp)  => ("⏳", "Tymczasowy"),
+        None                 => ("❔", "—"),
+    };
 
-        let czas = match st.kind {
-            Some(BanType::Temp) => st.duration.map(fmt_duration).unwrap_or_else(|| "—".into()),
-            _ => "—".into(),
-        };
+    let czas = match st.kind {
+        Some(BanType::Temp) => st.duration.map(fmt_duration).unwrap_or_else(|| "—".into()),
+        _ => "—".into(),
+    };
 
-        let have_type   = st.kind.is_some();
-        let have_time   = matches!(st.kind, Some(BanType::Temp)) && st.duration.is_some() || matches!(st.kind, Some(BanType::Perma));
-        let have_reason = st.reason.as_ref().map(|r| !r.trim().is_empty()).unwrap_or(false);
-
-        let steps = [
-            (1, "Wybierz typ", have_type),
-            (2, "Ustaw czas",  have_time),
-            (3, "Wpisz powód", have_reason),
-            (4, "Zatwierdź",   false), // ostatni krok – zawsze ostatni
-        ]
-        .iter()
-        .map(|(n, label, done)| format!("{} **{}.** {}", if *done { "✅" } else { "⬜" }, n, label))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-        let reason_preview = st.reason.as_deref().unwrap_or("—");
-        let reason_preview = shorten_code_block(reason_preview, 420);
-
-        e = e
-            .description(format!(
-                "**Użytkownik:** {target}\n**Administrator:** {moderator}\n**Typ:** {typ_emoji} {typ}\n\n**Czas:** {czas}\n**Powód:**\n```{reason}```\n\n__Status kroków__:\n{steps}",
-                typ = typ_txt,
-                reason = reason_preview,
-            ));
-    }
+    let have_type   = st.kind.is_some();
+    let have_time   = matches!(st.kind, Some(BanType::Temp)) && st.duration.is_some() || matches!(st.kind, Some(BanType::Perma));
+    let have_reason = st.reason.as_ref().map(|r| !r.trim().is_empty()).unwrap_or(false);
+
+    let steps = [
+        (1, "Wybierz typ", have_type),
+        (2, "Ustaw czas",  have_time),
+        (3, "Wpisz powód", have_reason),
+        (4, "Zatwierdź",   false), // ostatni krok – zawsze ostatni
+    ]
+    .iter()
+    .map(|(n, label, done)| format!("{} **{}.** {}", if *done { "✅" } else { "⬜" }, n, label))
+    .collect::<Vec<_>>()
+    .join("\n");
+
+    let reason_preview = st.reason.as_deref().unwrap_or("—");
+    let reason_preview = shorten_code_block(reason_preview, 420);
+
+    e = e
+        .description(format!(
+            "**Użytkownik:** {target}\n**Administrator:** {moderator}\n**Typ:** {typ_emoji} {typ}\n\n**Czas:** {czas}\n**Usuń wiadomości:** {purge}\n**Powód:**\n```{reason}```\n\n__Status kroków__:\n{steps}",
+            typ = typ_txt,
+            purge = fmt_purge(st.purge_secs),
+            reason = reason_preview,
+        ));
 
     e
 }
 
-fn ui_for_case(case_id: &str) -> (CreateEmbed, Vec<CreateActionRow>) {
-    let s = CASES.get(case_id).map(|e| e.clone());
-
-    let embed = summary_embed(case_id);
+fn ui_for_case(case_id: &str, st: &CaseState) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let embed = summary_embed(st);
     let mut rows: Vec<CreateActionRow> = Vec::new();
 
     rows.push(CreateActionRow::SelectMenu(
@@ -608,9 +923,10 @@ fn ui_for_case(case_id: &str) -> (CreateEmbed, Vec<CreateActionRow>) {
     // gotowość „Dalej”
     let mut proceed_enabled = false;
 
-    match s.as_ref().and_then(|x| x.kind) {
+    match st.kind {
         Some(BanType::Perma) => {
-            proceed_enabled = s.as_ref().and_then(|x| x.reason.as_ref()).map(|r| !r.trim().is_empty()).unwrap_or(false);
+            proceed_enabled = st.reason.as_ref().map(|r| !r.trim().is_empty()).unwrap_or(false);
+            rows.push(purge_row(case_id, st));
             rows.push(CreateActionRow::Buttons(vec![
                 CreateButton::new(format!("banp:reason:{case_id}"))
                     .label("Wpisz powód")
@@ -625,8 +941,8 @@ fn ui_for_case(case_id: &str) -> (CreateEmbed, Vec<CreateActionRow>) {
             ]));
         }
         Some(BanType::Temp) => {
-            let have_time = s.as_ref().and_then(|x| x.duration).is_some();
-            let have_reason = s.as_ref().and_then(|x| x.reason.as_ref()).map(|r| !r.trim().is_empty()).unwrap_or(false);
+            let have_time = st.duration.is_some();
+            let have_reason = st.reason.as_ref().map(|r| !r.trim().is_empty()).unwrap_or(false);
             proceed_enabled = have_time && have_reason;
 
             rows.push(CreateActionRow::SelectMenu(
@@ -643,6 +959,7 @@ fn ui_for_case(case_id: &str) -> (CreateEmbed, Vec<CreateActionRow>) {
                 .min_values(1)
                 .max_values(1),
             ));
+            rows.push(purge_row(case_id, st));
             rows.push(CreateActionRow::Buttons(vec![
                 CreateButton::new(format!("banp:reason:{case_id}"))
                     .label("Wpisz powód")
@@ -693,12 +1010,18 @@ fn confirm_embed(st: &CaseState) -> CreateEmbed {
         }
         _ => { e = e.field("Czas", "—", true); }
     }
+    e = e.field("Usuń wiadomości", fmt_purge(st.purge_secs), true);
 
     let reason = st.reason.as_deref().unwrap_or("—");
     e.description(format!("**Powód**:\n```{}```", shorten_code_block(reason, 900)))
 }
 
-fn make_log_embed(st: &CaseState, reason: &str) -> serenity::all::CreateMessage {
+fn make_log_embed(
+    st: &CaseState,
+    reason: &str,
+    source: Option<&str>,
+    case_no: Option<i64>,
+) -> serenity::all::CreateMessage {
     let (typ, col) = match st.kind {
         Some(BanType::Perma) => ("PERMA", Colour::new(0xE74C3C)),
         Some(BanType::Temp)  => ("TEMP",  Colour::new(0xF39C12)),
@@ -713,6 +1036,10 @@ fn make_log_embed(st: &CaseState, reason: &str) -> serenity::all::CreateMessage
         .field("Administrator", format!("<@{}>", st.moderator_id.get()), true)
         .field("Typ", typ, true);
 
+    if let Some(no) = case_no {
+        e = e.field("Sprawa", format!("#{no}"), true);
+    }
+
     match st.kind {
         Some(BanType::Temp) => {
             let czas_txt = st.duration.map(fmt_duration).unwrap_or_else(|| "—".into());
@@ -726,15 +1053,451 @@ fn make_log_embed(st: &CaseState, reason: &str) -> serenity::all::CreateMessage
         }
         _ => { e = e.field("Czas", "—", true); }
     }
+    if st.purge_secs > 0 {
+        e = e.field("Usunięte wiadomości", fmt_purge(st.purge_secs), true);
+    }
 
     e = e.field("Powód", format!("```{}```", shorten_code_block(reason, 900)), false);
+    if let Some(src) = source {
+        e = e.field("Źródło", src, false);
+    }
     serenity::all::CreateMessage::new().embed(e)
 }
 
+fn make_unban_log_embed(case: &ExpiredBan, source: &str, note: &str, case_no: Option<i64>) -> CreateMessage {
+    let now = now_unix();
+    let lifted_by = match source {
+        "scheduler" => "🤖 Scheduler (ban wygasł)".to_string(),
+        _ => "— (zdjęty poza botem)".to_string(),
+    };
+
+    let mut e = CreateEmbed::new()
+        .title("🔓 Ban zdjęty")
+        .colour(Colour::new(0x2ECC71))
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+        .field("Użytkownik", format!("<@{}>", case.user_id), true)
+        .field("Zdjęty przez", lifted_by, true);
+    if let Some(no) = case_no {
+        e = e.field("Sprawa", format!("#{no}"), true);
+    }
+    e = e
+        .field("Nałożył", format!("<@{}>", case.moderator_id), true)
+        .field("Nałożony", format!("<t:{}:F>", case.created_unix), true)
+        .field("Powód bana", format!("```{}```", shorten_code_block(&case.reason, 900)), false)
+        .field("Powód zdjęcia", note, false)
+        .field("Kiedy", format!("<t:{now}:F> • <t:{now}:R>"), false);
+
+    CreateMessage::new().embed(e)
+}
+
+/* ==========================================
+   DB: sesje panelu (tss.ban_panel_sessions)
+   ========================================== */
+
+const EXPIRED_NOTE: &str = "⌛ Panel bana wygasł. Użyj ponownie `/ban`.";
+
+fn session_from_row(r: &sqlx::postgres::PgRow) -> CaseState {
+    let kind = match r.get::<Option<String>, _>("kind").as_deref() {
+        Some("perma") => Some(BanType::Perma),
+        Some("temp") => Some(BanType::Temp),
+        _ => None,
+    };
+    let panel_msg = match (
+        r.get::<Option<i64>, _>("panel_channel_id"),
+        r.get::<Option<i64>, _>("panel_message_id"),
+    ) {
+        (Some(ch), Some(mid)) => Some((ChannelId::new(ch as u64), MessageId::new(mid as u64))),
+        _ => None,
+    };
+    CaseState {
+        guild_id: GuildId::new(r.get::<i64, _>("guild_id") as u64),
+        moderator_id: UserId::new(r.get::<i64, _>("moderator_id") as u64),
+        target_id: UserId::new(r.get::<i64, _>("target_id") as u64),
+        kind,
+        duration: r.get::<Option<i64>, _>("duration_secs").map(|v| Duration::from_secs(v as u64)),
+        reason: r.get("reason"),
+        purge_secs: r.get::<i32, _>("purge_secs").max(0) as u32,
+        panel_msg,
+        panel_token: r.get("panel_token"),
+    }
+}
+
+/// Sesja panelu, o ile nie wygasła.
+async fn load_session(db: &Pool<Postgres>, case_id: &str) -> Result<Option<CaseState>> {
+    let row = sqlx::query("SELECT * FROM tss.ban_panel_sessions WHERE case_id = $1 AND expires_at > now()")
+        .bind(case_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(row.as_ref().map(session_from_row))
+}
+
+/// Zapis sesji – `expires_at` ustawia tylko pierwszy zapis (`PANEL_TTL_SECS` od utworzenia).
+async fn save_session(db: &Pool<Postgres>, case_id: &str, st: &CaseState) -> Result<()> {
+    let kind = st.kind.map(|k| match k {
+        BanType::Perma => "perma",
+        BanType::Temp => "temp",
+    });
+    sqlx::query(
+        r#"
+        INSERT INTO tss.ban_panel_sessions
+            (case_id, guild_id, moderator_id, target_id, kind, duration_secs, reason,
+             panel_channel_id, panel_message_id, panel_token, purge_secs, updated_at, expires_at)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$12, now(), now() + make_interval(secs => $11::BIGINT))
+        ON CONFLICT (case_id) DO UPDATE SET
+            kind             = EXCLUDED.kind,
+            duration_secs    = EXCLUDED.duration_secs,
+            reason           = EXCLUDED.reason,
+            purge_secs       = EXCLUDED.purge_secs,
+            panel_channel_id = EXCLUDED.panel_channel_id,
+            panel_message_id = EXCLUDED.panel_message_id,
+            panel_token      = EXCLUDED.panel_token,
+            updated_at       = now()
+        "#,
+    )
+    .bind(case_id)
+    .bind(st.guild_id.get() as i64)
+    .bind(st.moderator_id.get() as i64)
+    .bind(st.target_id.get() as i64)
+    .bind(kind)
+    .bind(st.duration.map(|d| d.as_secs() as i64))
+    .bind(st.reason.as_deref())
+    .bind(st.panel_msg.map(|(ch, _)| ch.get() as i64))
+    .bind(st.panel_msg.map(|(_, mid)| mid.get() as i64))
+    .bind(st.panel_token.as_deref())
+    .bind(PANEL_TTL_SECS)
+    .bind(st.purge_secs as i32)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Atomowe „zabranie” sesji (potwierdzenie bana) – tylko jeden klik ją dostanie.
+async fn take_session(db: &Pool<Postgres>, case_id: &str) -> Result<Option<CaseState>> {
+    let row = sqlx::query(
+        "DELETE FROM tss.ban_panel_sessions WHERE case_id = $1 AND expires_at > now() RETURNING *",
+    )
+    .bind(case_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(row.as_ref().map(session_from_row))
+}
+
+async fn delete_session(db: &Pool<Postgres>, case_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM tss.ban_panel_sessions WHERE case_id = $1")
+        .bind(case_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Usuń wygasłe sesje i oznacz ich panele jako wygasłe (bez martwych przycisków).
+async fn sweep_expired_panels(http: &Http, db: &Pool<Postgres>) -> Result<()> {
+    let rows = sqlx::query("DELETE FROM tss.ban_panel_sessions WHERE expires_at <= now() RETURNING *")
+        .fetch_all(db)
+        .await?;
+    for r in &rows {
+        clear_panel(http, &session_from_row(r), EXPIRED_NOTE).await;
+    }
+    if !rows.is_empty() {
+        tracing::info!(count = rows.len(), "expired ban panels swept");
+    }
+    Ok(())
+}
+
+/* ==========================================
+   DB: sprawy banów + scheduler
+   ========================================== */
+
+struct ExpiredBan {
+    id: i64,
+    guild_id: u64,
+    user_id: u64,
+    moderator_id: u64,
+    reason: String,
+    created_unix: i64,
+}
+
+async fn insert_ban_case(db: &Pool<Postgres>, st: &CaseState, reason: &str) -> Result<i64> {
+    let kind = match st.kind {
+        Some(BanType::Temp) => "temp",
+        _ => "perma",
+    };
+    let secs: Option<i64> = match st.kind {
+        Some(BanType::Temp) => st.duration.map(|d| d.as_secs() as i64),
+        _ => None,
+    };
+
+    let id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO tss.ban_cases (guild_id, user_id, moderator_id, kind, reason, expires_at, purge_secs)
+        VALUES ($1, $2, $3, $4, $5,
+                CASE WHEN $6::BIGINT IS NULL THEN NULL
+                     ELSE now() + make_interval(secs => $6::BIGINT) END,
+                $7)
+        RETURNING id
+        "#,
+    )
+    .bind(st.guild_id.get() as i64)
+    .bind(st.target_id.get() as i64)
+    .bind(st.moderator_id.get() as i64)
+    .bind(kind)
+    .bind(reason)
+    .bind(secs)
+    .bind(st.purge_secs as i32)
+    .fetch_one(db)
+    .await?;
+    Ok(id)
+}
+
+/// Jeden przebieg schedulera: zdejmij wszystkie wygasłe, otwarte bany tymczasowe.
+/// Każde zdjęcie trafia do `tss.cases` jako UNBAN w imieniu bota.
+async fn lift_expired_bans(http: &Arc<Http>, app: &AppContext, bot_id: u64) -> Result<()> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, guild_id, user_id, moderator_id, reason,
+               EXTRACT(EPOCH FROM created_at)::BIGINT AS created_unix
+        FROM tss.ban_cases
+        WHERE kind = 'temp'
+          AND unbanned_at IS NULL
+          AND deleted_at IS NULL
+          AND expires_at IS NOT NULL
+          AND expires_at <= now()
+        ORDER BY expires_at ASC
+        LIMIT $1
+        "#,
+    )
+    .bind(EXPIRY_BATCH)
+    .fetch_all(&app.db)
+    .await?;
+
+    for r in rows {
+        let case = ExpiredBan {
+            id: r.get("id"),
+            guild_id: r.get::<i64, _>("guild_id") as u64,
+            user_id: r.get::<i64, _>("user_id") as u64,
+            moderator_id: r.get::<i64, _>("moderator_id") as u64,
+            reason: r.get("reason"),
+            created_unix: r.get("created_unix"),
+        };
+
+        let gid = GuildId::new(case.guild_id);
+        let uid = UserId::new(case.user_id);
+
+        let (source, note, by) = match gid.unban(http, uid).await {
+            Ok(()) => ("scheduler", "Ban tymczasowy wygasł.", Some(bot_id)),
+            // ktoś zdjął bana ręcznie w Discordzie – tylko zamykamy sprawę
+            Err(e) if is_unknown_ban(&e) => ("external", "Ban był już zdjęty poza botem.", None),
+            Err(e) => {
+                tracing::warn!(error=?e, case_id = case.id, guild_id = case.guild_id, user_id = case.user_id, "temp ban unban failed; retry next tick");
+                continue;
+            }
+        };
+
+        // zamykamy warunkowo – jeśli ktoś nas ubiegł, nie logujemy drugi raz
+        let closed = sqlx::query(
+            r#"
+            UPDATE tss.ban_cases
+               SET unbanned_at = now(), unbanned_by = $2, unban_source = $3, unban_reason = $4
+             WHERE id = $1 AND unbanned_at IS NULL
+            "#,
+        )
+        .bind(case.id)
+        .bind(by.map(|v| v as i64))
+        .bind(source)
+        .bind(note)
+        .execute(&app.db)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if !closed {
+            continue;
+        }
+
+        tracing::info!(case_id = case.id, guild_id = case.guild_id, user_id = case.user_id, source, "temp ban lifted");
+
+        let case_source = match source {
+            "scheduler" => "Automatycznie: ban tymczasowy wygasł",
+            _ => "Automatycznie: ban zdjęty poza botem",
+        };
+        let case_no = cases::record_case_logged(
+            &app.db,
+            &NewCase {
+                guild_id: case.guild_id,
+                user_id: case.user_id,
+                moderator_id: bot_id,
+                action: CaseAction::Unban,
+                reason: note,
+                evidence: None,
+                source: Some(case_source),
+                reference: Some(CaseRef::Ban(case.id)),
+            },
+        )
+        .await;
+
+        if let Some(cid) = log_channel(case.guild_id) {
+            let _ = ChannelId::new(cid)
+                .send_message(http, make_unban_log_embed(&case, source, note, case_no))
+                .await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ban z usunięciem historii wiadomości co do sekundy – `ban_with_reason` z serenity
+/// przyjmuje tylko pełne dni, a panel pozwala też na 1h.
+async fn ban_with_purge(http: &Http, guild_id: GuildId, user_id: UserId, purge_secs: u32, reason: &str) -> Result<()> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Ok(v) = reqwest::header::HeaderValue::from_str(&percent_encode(reason)) {
+        headers.insert("X-Audit-Log-Reason", v);
+    }
+    let req = Request::new(Route::GuildBan { guild_id, user_id }, LightMethod::Put)
+        .params(Some(vec![("delete_message_seconds", purge_secs.min(7 * 86400).to_string())]))
+        .headers(Some(headers));
+    http.request(req).await?;
+    Ok(())
+}
+
+/// Czy użytkownik ma bana na Discordzie (GET ban – serenity nie ma pojedynczego zapytania).
+pub(crate) async fn is_banned(http: &Http, guild_id: GuildId, user_id: UserId) -> Result<bool> {
+    let req = Request::new(Route::GuildBan { guild_id, user_id }, LightMethod::Get);
+    match http.request(req).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_unknown_ban(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Nagłówek X-Audit-Log-Reason musi być URL-encoded (UTF-8).
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Zdjęcie bana poza schedulerem (np. przyjęte odwołanie): unban na Discordzie →
+/// zamknięcie otwartych spraw w `tss.ban_cases` → wpis UNBAN w `tss.cases` → log.
+/// Zwraca `false`, jeśli użytkownik nie był już zbanowany (sprawy i tak zamykamy);
+/// bez bana i bez otwartej sprawy nie zapisujemy sprawy UNBAN ani logu.
+pub(crate) async fn lift_ban(
+    http: &Http,
+    app: &AppContext,
+    guild_id: GuildId,
+    user_id: UserId,
+    moderator_id: UserId,
+    reason: &str,
+    source: Option<&str>,
+) -> Result<bool> {
+    let audit = format!("[{}] {}", SYSTEM_NAME, reason);
+    let was_banned = match http.remove_ban(guild_id, user_id, Some(&audit)).await {
+        Ok(()) => true,
+        Err(e) if is_unknown_ban(&e) => false,
+        Err(e) => return Err(e.into()),
+    };
+
+    let closed: Option<i64> = sqlx::query_scalar(
+        r#"
+        UPDATE tss.ban_cases
+           SET unbanned_at = now(), unbanned_by = $3, unban_source = 'manual', unban_reason = $4
+         WHERE guild_id = $1 AND user_id = $2 AND unbanned_at IS NULL
+        RETURNING id
+        "#,
+    )
+    .bind(guild_id.get() as i64)
+    .bind(user_id.get() as i64)
+    .bind(moderator_id.get() as i64)
+    .bind(reason)
+    .fetch_all(&app.db)
+    .await?
+    .into_iter()
+    .max();
+
+    // nie był zbanowany i nie miał otwartej sprawy (np. literówka w ID) – nic do zapisania
+    if !was_banned && closed.is_none() {
+        return Ok(false);
+    }
+
+    // ułaskawiony nie powinien już zasilać AltGuard (NameSimilarity / AvatarPHash)
+    app.altguard().forget_punished(guild_id.get(), user_id.get()).await;
+
+    let case_no = cases::record_case_logged(
+        &app.db,
+        &NewCase {
+            guild_id: guild_id.get(),
+            user_id: user_id.get(),
+            moderator_id: moderator_id.get(),
+            action: CaseAction::Unban,
+            reason,
+            evidence: None,
+            source,
+            reference: closed.map(CaseRef::Ban),
+        },
+    )
+    .await;
+
+    if let Some(cid) = log_channel(guild_id.get()) {
+        let now = now_unix();
+        let mut e = CreateEmbed::new()
+            .title("🔓 Ban zdjęty")
+            .colour(Colour::new(0x2ECC71))
+            .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+            .field("Użytkownik", format!("<@{}> (`{}`)", user_id.get(), user_id.get()), true)
+            .field("Zdjęty przez", format!("<@{}>", moderator_id.get()), true)
+            .field("Powód zdjęcia", format!("```{}```", shorten_code_block(reason, 900)), false)
+            .field("Kiedy", format!("<t:{now}:F> • <t:{now}:R>"), false);
+        if let Some(no) = case_no {
+            e = e.field("Sprawa", format!("#{no}"), true);
+        }
+        if let Some(src) = source {
+            e = e.field("Źródło", src, false);
+        }
+        if !was_banned {
+            e = e.field("Uwaga", "Użytkownik nie był już zbanowany na Discordzie.", false);
+        }
+        let _ = ChannelId::new(cid).send_message(http, CreateMessage::new().embed(e)).await;
+    }
+
+    Ok(was_banned)
+}
+
+/// Discord: 10026 = Unknown Ban (użytkownik nie jest zbanowany).
+fn is_unknown_ban(e: &serenity::Error) -> bool {
+    matches!(
+        e,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(resp)) if resp.error.code == 10026
+    )
+}
+
 /* ==========================================
    DM formalny
    ========================================== */
 
+/// Informacja o zdjęciu bana. Może się nie udać, jeśli nie dzielimy już żadnego serwera.
+async fn send_unban_dm(ctx: &Context, target: UserId, reason: &str) -> Result<()> {
+    let user = target.to_user(&ctx.http).await?;
+    let e = CreateEmbed::new()
+        .title(format!("Ban zdjęty – {SERVER_NAME}"))
+        .colour(Colour::new(0x2ECC71))
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+        .description(format!(
+            "Szanowny Użytkowniku,\n\n\
+             Informujemy, że ban na serwerze **{SERVER_NAME}** został zdjęty. \
+             Możesz ponownie dołączyć do serwera."
+        ))
+        .field("Powód", format!("```{}```", shorten_code_block(reason, 900)), false);
+
+    let dm = user.create_dm_channel(&ctx.http).await?;
+    dm.send_message(&ctx.http, CreateMessage::new().embed(e)).await?;
+    Ok(())
+}
+
 async fn send_formal_dm(ctx: &Context, target: UserId, st: &CaseState, reason: &str) -> Result<()> {
     let user = target.to_user(&ctx.http).await?;
     let kind_txt = match st.kind {
@@ -762,7 +1525,7 @@ async fn send_formal_dm(ctx: &Context, target: UserId, st: &CaseState, reason: &
     let desc = format!(
         "Szanowny Użytkowniku,\n\n\
          Informujemy, że na Twoje konto został nałożony ban na serwerze **{server}**.\n\
-         Jeśli uważasz, że zaszła pomyłka, możesz złożyć odwołanie u zespołu administracji.\n\n\
+         Jeśli uważasz, że zaszła pomyłka, możesz złożyć odwołanie przyciskiem poniżej.\n\n\
          **Powód:**",
         server = SERVER_NAME
     );
@@ -777,9 +1540,14 @@ async fn send_formal_dm(ctx: &Context, target: UserId, st: &CaseState, reason: &
     // DM otwieramy raz
     let dm = user.create_dm_channel(&ctx.http).await?;
 
-    // 1) wiadomość z embedem
+    // 1) wiadomość z embedem + przycisk odwołania (działa także po banie – w DM)
     let _ = dm
-        .send_message(&ctx.http, serenity::all::CreateMessage::new().embed(e))
+        .send_message(
+            &ctx.http,
+            serenity::all::CreateMessage::new()
+                .embed(e)
+                .components(vec![CreateActionRow::Buttons(vec![appeals::appeal_button(st.guild_id)])]),
+        )
         .await;
 
     // 2) jeśli PERMA i mamy link — doślij sam URL, aby Discord wstawił player
@@ -795,13 +1563,18 @@ async fn send_formal_dm(ctx: &Context, target: UserId, st: &CaseState, reason: &
    Helpers
    ========================================== */
 
-async fn user_can_ban(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    if let Ok(member) = gid.member(&ctx.http, uid).await {
-        if let Ok(perms) = member.permissions(&ctx.cache) {
-            return perms.ban_members() || perms.administrator();
-        }
-    }
-    false
+/// Id kanału logów z env (LOGS_BAN_KICK_MUTE). Zwraca None jeśli 0/nieustawione.
+fn log_channel(gid: u64) -> Option<u64> {
+    let id = env_channels::logs::ban_kick_mute_id(gid);
+    if id == 0 { None } else { Some(id) }
+}
+
+fn now_unix() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
 }
 
 fn fmt_duration(d: Duration) -> String {
@@ -834,15 +1607,6 @@ fn first_value(comp: &ComponentInteraction) -> Option<String> {
     None
 }
 
-async fn try_update_existing_panel(ctx: &Context, case_id: &str) {
-    if let Some(st) = CASES.get(case_id).map(|e| e.clone()) {
-        if let Some((ch, mid)) = st.panel_msg {
-            let (embed, comps) = ui_for_case(case_id);
-            let _ = ch.edit_message(&ctx.http, mid, EditMessage::new().embed(embed).components(comps)).await;
-        }
-    }
-}
-
 fn color_for_kind(kind: Option<BanType>) -> Colour {
     match kind {
         Some(BanType::Perma) => Colour::new(0xE74C3C), // czerwony
@@ -867,39 +1631,60 @@ fn shorten_code_block(s: &str, max_chars: usize) -> String {
     out
 }
 
-async fn try_clear_existing_panel(ctx: &Context, case_id: &str, content: &str) {
-    if let Some(st) = CASES.get(case_id).map(|e| e.clone()) {
-        if let Some((ch, mid)) = st.panel_msg {
-            let _ = ch
-                .edit_message(
-                    &ctx.http,
-                    mid,
-                    EditMessage::new()
-                        .content(content)
-                        .components(Vec::<CreateActionRow>::new())
-                        .embeds(Vec::<CreateEmbed>::new()),
-                )
-                .await;
-        }
+/// Wyczyść panel (ephemeral) przez token interakcji – kanałowy `edit_message` na nim nie działa.
+async fn clear_panel(http: &Http, st: &CaseState, content: &str) {
+    let Some(token) = st.panel_token.as_deref() else { return; };
+    let edit = EditInteractionResponse::new()
+        .content(content)
+        .components(Vec::<CreateActionRow>::new())
+        .embeds(Vec::<CreateEmbed>::new());
+    let _ = http.edit_original_interaction_response(token, &edit, vec![]).await;
+}
+
+async fn guard_current_panel(ctx: &Context, st: &CaseState, comp: &ComponentInteraction) -> Result<bool> {
+    if let Some((_, mid)) = st.panel_msg
+        && comp.message.id != mid
+    {
+        comp.create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content("⚠️ Ten panel jest nieaktualny. Proszę użyj najnowszego panelu poniżej.")
+                    .ephemeral(true),
+            ),
+        ).await.ok();
+        return Ok(false);
     }
+    Ok(true)
 }
 
-async fn guard_current_panel(ctx: &Context, case_id: &str, comp: &ComponentInteraction) -> Result<bool> {
-    if let Some(st) = CASES.get(case_id) {
-        if let Some((_, mid)) = st.panel_msg {
-            if comp.message.id != mid {
-                comp.create_response(
-                    &ctx.http,
-                    CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new()
-                            .content("⚠️ Ten panel jest nieaktualny. Proszę użyj najnowszego panelu poniżej.")
-                            .ephemeral(true),
-                    ),
-                ).await.ok();
-                return Ok(false);
-            }
+/// Sesja z DB albo – gdy wygasła / zniknęła – od razu gasimy kliknięty panel.
+async fn session_or_expired(
+    ctx: &Context,
+    app: &AppContext,
+    comp: &ComponentInteraction,
+    case_id: &str,
+) -> Result<Option<CaseState>> {
+    match load_session(&app.db, case_id).await? {
+        Some(st) => Ok(Some(st)),
+        None => {
+            expired_panel_response(ctx, comp).await?;
+            Ok(None)
         }
     }
-    Ok(true)
+}
+
+async fn expired_panel_response(ctx: &Context, comp: &ComponentInteraction) -> Result<()> {
+    comp.create_response(
+        &ctx.http,
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(EXPIRED_NOTE)
+                .components(vec![])
+                .embeds(vec![]),
+        ),
+    )
+    .await?;
+    Ok(())
 }
 
@@ -0,0 +1,541 @@
+// src/cases.rs
+//
+// Wspólny rejestr spraw moderacyjnych (tss.cases) z numeracją per gildia.
+// Każda akcja (warn / mute / timeout / kick / ban) dostaje numer sprawy, a /case
+// pozwala ją podejrzeć i poprawić po numerze niezależnie od modułu, który ją utworzył.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serenity::all::*;
+use sqlx::{Pool, Postgres, Row};
+
+use crate::discord::router::Routes;
+use crate::permissions::{self, Permission};
+use crate::{registry::env_channels, warn, AppContext};
+
+const SYSTEM_NAME: &str = "Tigris Case System";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseAction {
+    Warn,
+    Mute,
+    Timeout,
+    Kick,
+    Ban,
+    Unban,
+    Unmute,
+}
+
+impl CaseAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CaseAction::Warn => "WARN",
+            CaseAction::Mute => "MUTE",
+            CaseAction::Timeout => "TIMEOUT",
+            CaseAction::Kick => "KICK",
+            CaseAction::Ban => "BAN",
+            CaseAction::Unban => "UNBAN",
+            CaseAction::Unmute => "UNMUTE",
+        }
+    }
+
+    fn label(s: &str) -> &'static str {
+        match s {
+            "WARN" => "⚠️ Ostrzeżenie",
+            "MUTE" => "🔇 Wyciszenie (rola)",
+            "TIMEOUT" => "⏳ Timeout",
+            "KICK" => "👢 Kick",
+            "BAN" => "🔨 Ban",
+            "UNBAN" => "🔓 Unban",
+            "UNMUTE" => "🔊 Zdjęcie wyciszenia",
+            _ => "—",
+        }
+    }
+}
+
+/// Odnośnik do szczegółów akcji w tabeli modułu.
+#[derive(Debug, Clone, Copy)]
+pub enum CaseRef {
+    Warn(i64),
+    Mute(i64),
+    Ban(i64),
+}
+
+impl CaseRef {
+    fn table(self) -> &'static str {
+        match self {
+            CaseRef::Warn(_) => "warn_cases",
+            CaseRef::Mute(_) => "mute_cases",
+            CaseRef::Ban(_) => "ban_cases",
+        }
+    }
+
+    fn id(self) -> i64 {
+        match self {
+            CaseRef::Warn(id) | CaseRef::Mute(id) | CaseRef::Ban(id) => id,
+        }
+    }
+}
+
+pub(crate) struct NewCase<'a> {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub moderator_id: u64,
+    pub action: CaseAction,
+    pub reason: &'a str,
+    pub evidence: Option<&'a str>,
+    pub source: Option<&'a str>,
+    pub reference: Option<CaseRef>,
+}
+
+#[derive(Debug, Clone)]
+struct CaseRow {
+    case_no: i64,
+    action: String,
+    user_id: u64,
+    moderator_id: u64,
+    reason: String,
+    evidence: Option<String>,
+    source: Option<String>,
+    ref_table: Option<String>,
+    ref_id: Option<i64>,
+    created_unix: i64,
+    updated_unix: Option<i64>,
+    updated_by: Option<u64>,
+    deleted_unix: Option<i64>,
+    deleted_by: Option<u64>,
+    delete_reason: Option<String>,
+}
+
+pub struct Cases;
+
+impl Cases {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["case"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        let case_no = || {
+            CreateCommandOption::new(CommandOptionType::Integer, "number", "Numer sprawy")
+                .required(true)
+                .min_int_value(1)
+        };
+
+        vec![
+            CreateCommand::new("case")
+                .description("Sprawy moderacyjne (podgląd i poprawki po numerze)")
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "view", "Pokaż sprawę")
+                        .add_sub_option(case_no()),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "edit-reason",
+                        "Zmień powód sprawy",
+                    )
+                    .add_sub_option(case_no())
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "reason", "Nowy powód")
+                            .required(true),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "attach-evidence",
+                        "Dołącz dowód (URL/opis)",
+                    )
+                    .add_sub_option(case_no())
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "evidence", "Dowód/URL")
+                            .required(true),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "delete",
+                        "Usuń sprawę (administrator)",
+                    )
+                    .add_sub_option(case_no())
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "reason",
+                        "Powód usunięcia",
+                    )),
+                )
+                .default_member_permissions(Permissions::MODERATE_MEMBERS),
+        ]
+    }
+
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        let Some(cmd) = interaction.command() else { return; };
+        if cmd.data.name.as_str() != "case" { return; }
+        if let Err(e) = handle_case(ctx, app, &cmd).await {
+            tracing::warn!(?e, "case command failed");
+        }
+    }
+}
+
+/* ========================= Zapis ========================= */
+
+/// Zapisz akcję w rejestrze spraw i zwróć jej numer (kolejny w obrębie gildii).
+pub(crate) async fn record_case(db: &Pool<Postgres>, c: &NewCase<'_>) -> Result<i64> {
+    let case_no: i64 = sqlx::query_scalar(
+        r#"
+        WITH n AS (
+            INSERT INTO tss.case_counters (guild_id, last_no)
+            VALUES ($1, 1)
+            ON CONFLICT (guild_id) DO UPDATE SET last_no = tss.case_counters.last_no + 1
+            RETURNING last_no
+        )
+        INSERT INTO tss.cases
+            (guild_id, case_no, action, user_id, moderator_id, reason, evidence, source, ref_table, ref_id)
+        SELECT $1, n.last_no, $2, $3, $4, $5, $6, $7, $8, $9 FROM n
+        RETURNING case_no
+        "#,
+    )
+    .bind(c.guild_id as i64)
+    .bind(c.action.as_str())
+    .bind(c.user_id as i64)
+    .bind(c.moderator_id as i64)
+    .bind(c.reason)
+    .bind(c.evidence)
+    .bind(c.source)
+    .bind(c.reference.map(|r| r.table()))
+    .bind(c.reference.map(|r| r.id()))
+    .fetch_one(db)
+    .await?;
+    Ok(case_no)
+}
+
+/// Jak `record_case`, ale błąd tylko logujemy – akcja na Discordzie już się wykonała.
+pub(crate) async fn record_case_logged(db: &Pool<Postgres>, c: &NewCase<'_>) -> Option<i64> {
+    match record_case(db, c).await {
+        Ok(no) => Some(no),
+        Err(e) => {
+            tracing::warn!(?e, guild_id = c.guild_id, user_id = c.user_id, action = c.action.as_str(), "case record failed");
+            None
+        }
+    }
+}
+
+/// Oznacz sprawę powiązaną z wpisem modułu jako usuniętą (np. /warn-remove).
+pub(crate) async fn mark_deleted_by_ref(
+    db: &Pool<Postgres>,
+    gid: u64,
+    reference: CaseRef,
+    by: u64,
+    why: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE tss.cases
+           SET deleted_at = now(), deleted_by = $1, delete_reason = $2
+         WHERE guild_id = $3 AND ref_table = $4 AND ref_id = $5 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(by as i64)
+    .bind(why)
+    .bind(gid as i64)
+    .bind(reference.table())
+    .bind(reference.id())
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/* ========================= /case ========================= */
+
+async fn handle_case(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Case) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
+    }
+    let Some(sc) = cmd.data.options.first() else {
+        return edit_ephemeral(ctx, cmd, "Wybierz podkomendę.").await;
+    };
+
+    let mut number: Option<i64> = None;
+    let mut text: Option<String> = None;
+    if let CommandDataOptionValue::SubCommand(params) = &sc.value {
+        for p in params {
+            match (p.name.as_str(), &p.value) {
+                ("number", CommandDataOptionValue::Integer(n)) => number = Some(*n),
+                ("reason" | "evidence", CommandDataOptionValue::String(s)) => {
+                    text = Some(s.trim().to_string())
+                }
+                _ => {}
+            }
+        }
+    }
+    let Some(no) = number else {
+        return edit_ephemeral(ctx, cmd, "Podaj numer sprawy.").await;
+    };
+
+    let Some(case) = fetch_case(&app.db, gid.get(), no).await? else {
+        return edit_ephemeral(ctx, cmd, &format!("Nie znaleziono sprawy #{no}.")).await;
+    };
+
+    let by = cmd.user.id.get();
+    match sc.name.as_str() {
+        "view" => {}
+        "edit-reason" => {
+            let Some(reason) = text.filter(|s| !s.is_empty()) else {
+                return edit_ephemeral(ctx, cmd, "Podaj nowy powód.").await;
+            };
+            if case.deleted_unix.is_some() {
+                return edit_ephemeral(ctx, cmd, "Sprawa jest usunięta.").await;
+            }
+            update_reason(&app.db, gid.get(), &case, &reason, by).await?;
+            log_change(ctx, gid.get(), &case, "Zmieniono powód", &reason, by).await;
+        }
+        "attach-evidence" => {
+            let Some(evidence) = text.filter(|s| !s.is_empty()) else {
+                return edit_ephemeral(ctx, cmd, "Podaj dowód.").await;
+            };
+            if case.deleted_unix.is_some() {
+                return edit_ephemeral(ctx, cmd, "Sprawa jest usunięta.").await;
+            }
+            update_evidence(&app.db, gid.get(), &case, &evidence, by).await?;
+            log_change(ctx, gid.get(), &case, "Dołączono dowód", &evidence, by).await;
+        }
+        "delete" => {
+            if !permissions::authorize(gid, cmd.member.as_deref(), Permission::CaseDelete) {
+                return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień do usuwania spraw.").await;
+            }
+            if case.deleted_unix.is_some() {
+                return edit_ephemeral(ctx, cmd, "Sprawa jest już usunięta.").await;
+            }
+            let why = text.filter(|s| !s.is_empty()).unwrap_or_else(|| "moderator remove".into());
+            delete_case(&app.db, gid.get(), &case, &why, by).await?;
+            log_change(ctx, gid.get(), &case, "Usunięto sprawę", &why, by).await;
+        }
+        _ => return edit_ephemeral(ctx, cmd, "Nieznana podkomenda.").await,
+    }
+
+    // świeży stan po zmianie
+    let case = fetch_case(&app.db, gid.get(), no).await?.unwrap_or(case);
+    let mut e = case_embed(&case);
+    if sc.name == "delete" && matches!(case.action.as_str(), "BAN" | "MUTE" | "TIMEOUT") {
+        e = e.field(
+            "Uwaga",
+            "Usunięto tylko wpis – kara na Discordzie pozostaje (zdejmij ją osobno).",
+            false,
+        );
+    }
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().embeds(vec![e]))
+        .await?;
+    Ok(())
+}
+
+fn case_embed(c: &CaseRow) -> CreateEmbed {
+    let colour = if c.deleted_unix.is_some() { 0x95A5A6 } else { 0x3498DB };
+    let mut e = CreateEmbed::new()
+        .title(format!("📁 Sprawa #{}", c.case_no))
+        .colour(Colour::new(colour))
+        .field("Akcja", CaseAction::label(&c.action), true)
+        .field("Użytkownik", format!("<@{}> (`{}`)", c.user_id, c.user_id), true)
+        .field("Moderator", format!("<@{}>", c.moderator_id), true)
+        .field("Kiedy", format!("<t:{0}:F> • <t:{0}:R>", c.created_unix), false)
+        .field("Powód", format!("```{}```", truncate(&c.reason, 900)), false)
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME));
+
+    if let Some(ev) = &c.evidence {
+        e = e.field("Dowód", truncate(ev, 1000), false);
+    }
+    if let Some(src) = &c.source {
+        e = e.field("Źródło", src.clone(), false);
+    }
+    if let (Some(t), Some(id)) = (&c.ref_table, c.ref_id) {
+        e = e.field("Wpis modułu", format!("`{t}` #{id}"), true);
+    }
+    if let Some(ts) = c.updated_unix {
+        let who = c.updated_by.map(|u| format!(" przez <@{u}>")).unwrap_or_default();
+        e = e.field("Edytowano", format!("<t:{ts}:R>{who}"), true);
+    }
+    if let Some(ts) = c.deleted_unix {
+        let who = c.deleted_by.map(|u| format!(" przez <@{u}>")).unwrap_or_default();
+        let why = c.delete_reason.as_deref().unwrap_or("—");
+        e = e.field("Usunięto", format!("<t:{ts}:R>{who}\n{}", truncate(why, 300)), false);
+    }
+    e
+}
+
+async fn log_change(ctx: &Context, gid: u64, c: &CaseRow, what: &str, value: &str, by: u64) {
+    let Some(log_ch) = log_channel(gid) else { return; };
+    let now = now_unix();
+    let e = CreateEmbed::new()
+        .title(format!("📝 Sprawa #{} – {}", c.case_no, what.to_lowercase()))
+        .colour(Colour::new(0xF1C40F))
+        .field("Akcja", CaseAction::label(&c.action), true)
+        .field("Użytkownik", format!("<@{}>", c.user_id), true)
+        .field("Moderator", format!("<@{by}>"), true)
+        .field("Kiedy", format!("<t:{now}:F> • <t:{now}:R>"), false)
+        .field(what, format!("```{}```", truncate(value, 900)), false)
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME));
+    let _ = ChannelId::new(log_ch)
+        .send_message(&ctx.http, CreateMessage::new().embed(e))
+        .await;
+}
+
+/* ========================= DB ========================= */
+
+async fn fetch_case(db: &Pool<Postgres>, gid: u64, case_no: i64) -> Result<Option<CaseRow>> {
+    let row = sqlx::query(
+        r#"
+        SELECT case_no, action, user_id, moderator_id, reason, evidence, source, ref_table, ref_id,
+               EXTRACT(EPOCH FROM created_at)::BIGINT AS created_unix,
+               EXTRACT(EPOCH FROM updated_at)::BIGINT AS updated_unix,
+               updated_by,
+               EXTRACT(EPOCH FROM deleted_at)::BIGINT AS deleted_unix,
+               deleted_by, delete_reason
+        FROM tss.cases
+        WHERE guild_id = $1 AND case_no = $2
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(case_no)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| CaseRow {
+        case_no: r.get("case_no"),
+        action: r.get("action"),
+        user_id: r.get::<i64, _>("user_id") as u64,
+        moderator_id: r.get::<i64, _>("moderator_id") as u64,
+        reason: r.get("reason"),
+        evidence: r.get("evidence"),
+        source: r.get("source"),
+        ref_table: r.get("ref_table"),
+        ref_id: r.get("ref_id"),
+        created_unix: r.get("created_unix"),
+        updated_unix: r.get("updated_unix"),
+        updated_by: r.get::<Option<i64>, _>("updated_by").map(|v| v as u64),
+        deleted_unix: r.get("deleted_unix"),
+        deleted_by: r.get::<Option<i64>, _>("deleted_by").map(|v| v as u64),
+        delete_reason: r.get("delete_reason"),
+    }))
+}
+
+/// Odnośnik z wiersza – tylko znane tabele (nazwa trafia do SQL).
+fn case_ref(c: &CaseRow) -> Option<CaseRef> {
+    match (c.ref_table.as_deref(), c.ref_id) {
+        (Some("warn_cases"), Some(id)) => Some(CaseRef::Warn(id)),
+        (Some("mute_cases"), Some(id)) => Some(CaseRef::Mute(id)),
+        (Some("ban_cases"), Some(id)) => Some(CaseRef::Ban(id)),
+        _ => None,
+    }
+}
+
+async fn update_reason(db: &Pool<Postgres>, gid: u64, c: &CaseRow, reason: &str, by: u64) -> Result<()> {
+    sqlx::query(
+        "UPDATE tss.cases SET reason = $1, updated_at = now(), updated_by = $2 WHERE guild_id = $3 AND case_no = $4",
+    )
+    .bind(reason)
+    .bind(by as i64)
+    .bind(gid as i64)
+    .bind(c.case_no)
+    .execute(db)
+    .await?;
+
+    // powód trzymają też tabele modułów (logi, /warns, /user)
+    if let Some(r) = case_ref(c) {
+        let sql = format!("UPDATE tss.{} SET reason = $1 WHERE id = $2 AND guild_id = $3", r.table());
+        sqlx::query(&sql)
+            .bind(reason)
+            .bind(r.id())
+            .bind(gid as i64)
+            .execute(db)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn update_evidence(db: &Pool<Postgres>, gid: u64, c: &CaseRow, evidence: &str, by: u64) -> Result<()> {
+    sqlx::query(
+        "UPDATE tss.cases SET evidence = $1, updated_at = now(), updated_by = $2 WHERE guild_id = $3 AND case_no = $4",
+    )
+    .bind(evidence)
+    .bind(by as i64)
+    .bind(gid as i64)
+    .bind(c.case_no)
+    .execute(db)
+    .await?;
+
+    // ban_cases nie ma kolumny evidence – dowód zostaje tylko w tss.cases
+    if let Some(r @ (CaseRef::Warn(_) | CaseRef::Mute(_))) = case_ref(c) {
+        let sql = format!("UPDATE tss.{} SET evidence = $1 WHERE id = $2 AND guild_id = $3", r.table());
+        sqlx::query(&sql)
+            .bind(evidence)
+            .bind(r.id())
+            .bind(gid as i64)
+            .execute(db)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn delete_case(db: &Pool<Postgres>, gid: u64, c: &CaseRow, why: &str, by: u64) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE tss.cases
+           SET deleted_at = now(), deleted_by = $1, delete_reason = $2
+         WHERE guild_id = $3 AND case_no = $4 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(by as i64)
+    .bind(why)
+    .bind(gid as i64)
+    .bind(c.case_no)
+    .execute(db)
+    .await?;
+
+    // warn przestaje liczyć się do punktów; ban/mute zostają w tabelach modułów,
+    // żeby schedulery nadal zdjęły karę tymczasową
+    if let Some(CaseRef::Warn(id)) = case_ref(c) {
+        warn::revoke_warn(db, gid, id, by, why).await?;
+    }
+    Ok(())
+}
+
+/* ========================= Helpers ========================= */
+
+async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
+
+fn log_channel(gid: u64) -> Option<u64> {
+    let id = env_channels::logs::ban_kick_mute_id(gid);
+    if id == 0 { None } else { Some(id) }
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.len() <= max {
+        s.to_string()
+    } else {
+        let mut cut = max.saturating_sub(1);
+        while !s.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        format!("{}…", &s[..cut])
+    }
+}
@@ -3,12 +3,12 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 
 use serenity::all::{
-    ChannelId, Context, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId, Interaction,
-    Member, Message, PartialMember,
+    ChannelId, Context, CreateEmbed, CreateEmbedFooter, CreateMessage, Interaction,
+    Message, PartialMember,
 };
 use tracing::warn;
 
-use crate::admin_points;
+use crate::discord::router::Routes;
 use crate::fotosystem;
 use crate::registry::{env_channels, env_roles};
 use crate::AppContext;
@@ -48,10 +48,12 @@ static HARD_INSULTS: Lazy<Vec<&'static str>> = Lazy::new(|| {
 
 pub struct ChatGuard;
 impl ChatGuard {
-    /// (Opcjonalnie) rejestracja komend – na razie no-op.
-    pub async fn register_commands(_ctx: &Context, _guild_id: GuildId) -> Result<()> {
-        Ok(())
-    }
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &[],
+        components: &["cgq:"],
+        modals: &["cgq:"],
+    };
 
     /// Wywoływane z EventHandler::message
     pub async fn on_message(ctx: &Context, app: &crate::AppContext, msg: &Message) {
@@ -66,22 +68,13 @@ impl ChatGuard {
         // Uwaga: brak obsługi komend tekstowych! Wszystko robimy tylko przez slash.
     }
 
-    /// Wywoływane z EventHandler::interaction_create
+    /// Wywoływane przez router (discord/router.rs) dla custom_id `cgq:`
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
         // 🔧 jednorazowy DDL
         fotosystem::maybe_ensure_tables(&app.db).await;
 
-        // 1) komponenty (przyciski / selecty)
+        // 1) przyciski FotoSystemu (Approve/Reject)
         if let Some(comp) = interaction.clone().message_component() {
-            // a) najpierw AdminScore (select)
-            if admin_points::is_points_component(&comp) {
-                if let Err(e) = admin_points::handle_points_component(ctx, &app.db, &comp).await {
-                    warn!(error=?e, "AdminPoints.handle_points_component failed");
-                }
-                return;
-            }
-
-            // b) FotoSystem (Approve/Reject)
             if let Err(e) = fotosystem::on_component(ctx, app, &comp).await {
                 warn!(error=?e, "FotoSystem.on_component failed");
             }
@@ -106,8 +99,8 @@ async fn moderate_message(ctx: &Context, app: &AppContext, msg: &Message) -> Res
         return Ok(());
     }
 
-    let env = app.env();
-    let is_staff = is_staff_member_msg(&env, msg.member.as_deref());
+    let gid = msg.guild_id.map(|g| g.get()).unwrap_or(0);
+    let is_staff = is_staff_member_msg(gid, msg.member.as_deref());
 
     if !is_staff && contains_link(&msg.content) {
         let _ = msg.delete(&ctx.http).await;
@@ -198,27 +191,19 @@ fn leetspeak_fold(s: &str) -> String {
 trait HasRoles {
     fn roles(&self) -> &[serenity::all::RoleId];
 }
-impl HasRoles for Member {
-    fn roles(&self) -> &[serenity::all::RoleId] {
-        &self.roles
-    }
-}
 impl HasRoles for PartialMember {
     fn roles(&self) -> &[serenity::all::RoleId] {
         &self.roles
     }
 }
-fn is_staff_member_generic<T: HasRoles>(env: &str, member: Option<&T>) -> bool {
-    let staff = env_roles::staff_set(env);
+fn is_staff_member_generic<T: HasRoles>(gid: u64, member: Option<&T>) -> bool {
+    let staff = env_roles::staff_set(gid);
     member
         .map(|m| m.roles().iter().any(|r| staff.contains(&r.get())))
         .unwrap_or(false)
 }
-pub(crate) fn is_staff_member_msg(env: &str, member: Option<&PartialMember>) -> bool {
-    is_staff_member_generic(env, member)
-}
-pub(crate) fn is_staff_member_comp(env: &str, member: Option<&Member>) -> bool {
-    is_staff_member_generic(env, member)
+pub(crate) fn is_staff_member_msg(gid: u64, member: Option<&PartialMember>) -> bool {
+    is_staff_member_generic(gid, member)
 }
 
 /* =========================================
@@ -234,9 +219,9 @@ fn clamp(s: &str, max: usize) -> String {
     out
 }
 
-pub(crate) async fn log_violation(ctx: &Context, app: &AppContext, msg: &Message, reason: &str) {
-    let env = app.env();
-    let log_ch = env_channels::logs::message_delete_id(&env);
+pub(crate) async fn log_violation(ctx: &Context, _app: &AppContext, msg: &Message, reason: &str) {
+    let gid = msg.guild_id.map(|g| g.get()).unwrap_or(0);
+    let log_ch = env_channels::logs::message_delete_id(gid);
     if log_ch == 0 {
         return;
     }
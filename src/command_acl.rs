@@ -3,12 +3,11 @@ use std::collections::HashMap;
 use anyhow::Result;
 use serenity::all::{Context, GuildId, RoleId, CreateCommandPermission, EditCommandPermissions};
 
-use crate::registry::env_roles;
+use crate::permissions::{self, Permission};
 
 pub async fn apply_permissions(ctx: &Context, guild_id: GuildId) -> Result<()> {
-    let env = std::env::var("TSS_ENV").unwrap_or_else(|_| "production".to_string());
     let cmds = guild_id.get_commands(&ctx.http).await?;
-    let map = build_map(&env);
+    let map = build_map(guild_id.get());
 
     for cmd in cmds {
         if let Some(roles) = map.get(cmd.name.as_str()) {
@@ -26,45 +25,10 @@ pub async fn apply_permissions(ctx: &Context, guild_id: GuildId) -> Result<()> {
     Ok(())
 }
 
-fn build_map(env: &str) -> HashMap<&'static str, Vec<u64>> {
-    let tm = env_roles::test_moderator_id(env);
-    let mo = env_roles::moderator_id(env);
-    let ad = env_roles::admin_id(env);
-    let op = env_roles::opiekun_id(env);
-    let tz = env_roles::technik_zarzad_id(env);
-    let co = env_roles::co_owner_id(env);
-    let ow = env_roles::owner_id(env);
-    let gu = env_roles::gumis_od_botow_id(env);
-
-    let tm_plus = vec![tm, mo, ad, op, tz, co, ow, gu];
-    let mo_plus = vec![mo, ad, op, tz, co, ow, gu];
-    let ad_plus = vec![ad, op, tz, co, ow, gu];
-    let op_plus = vec![op, tz, co, ow, gu];
-    let tz_plus = vec![tz, co, ow, gu];
-
-    let mut map: HashMap<&'static str, Vec<u64>> = HashMap::new();
-
-    for name in ["warn", "warns", "user"] {
-        map.insert(name, tm_plus.clone());
-    }
-    map.insert("mute", mo_plus.clone());
-
-    for name in ["warn-remove", "unmute", "kick", "mdel", "ban"] {
-        map.insert(name, ad_plus.clone());
-    }
-
-    map.insert("punkty", op_plus.clone());
-    map.insert("admcheck", op_plus.clone());
-
-    for name in [
-        "slash-clean",
-        "slash-resync",
-        "teach",
-        "mute-config",
-        "verify-panel",
-    ] {
-        map.insert(name, tz_plus.clone());
-    }
-
-    map
+/// ACL Discorda = role z `permissions::roles_for` (to samo źródło co `authorize`).
+fn build_map(gid: u64) -> HashMap<&'static str, Vec<u64>> {
+    Permission::ALL
+        .into_iter()
+        .filter_map(|p| p.command().map(|name| (name, permissions::roles_for(gid, p))))
+        .collect()
 }
@@ -1,41 +1,53 @@
 // src/commands_sync.rs
 
+use std::collections::HashMap;
+
 use anyhow::Result;
+use serde_json::Value as Json;
 use serenity::all::{
     Command, CommandInteraction, Context, CreateCommand, CreateInteractionResponse,
-    CreateInteractionResponseMessage, GuildId, Member,
+    CreateInteractionResponseMessage, EditInteractionResponse, GuildId, Interaction,
 };
 
-use crate::chatguard::ChatGuard;
-use crate::verify::Verify;
+use crate::admcheck::AdmCheck;
 use crate::admin_points::AdminPoints;
-
-// ⬇⬇⬇ DODAJ:
+use crate::altguard_admin::AltGuardAdmin;
 use crate::ban::Ban;
+use crate::cases::Cases;
+use crate::command_acl;
+use crate::discord::router::Routes;
+use crate::idguard::IdGuard;
 use crate::kick::Kick;
+use crate::mdel::MDel;
+use crate::mute::Mute;
+use crate::permissions::{self, Permission};
+use crate::perms_admin::Perms;
+use crate::raid::Raid;
+use crate::registry_admin::Registry;
+use crate::userinfo::UserInfo;
+use crate::verify::Verify;
 use crate::warn::Warns;
-use crate::command_acl;
 
 pub const CLEAN_NAME: &str = "slash-clean";
 pub const RESYNC_NAME: &str = "slash-resync";
 
-pub async fn register_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-    guild_id
-        .create_command(
-            &ctx.http,
-            CreateCommand::new(CLEAN_NAME)
-                .description("Wyczyść WSZYSTKIE komendy (gildyjne + globalne) dla tej aplikacji"),
-        )
-        .await?;
-    guild_id
-        .create_command(
-            &ctx.http,
-            CreateCommand::new(RESYNC_NAME).description(
-                "Wyczyść global/guild i zarejestruj na nowo tylko wymagane komendy w tej gildii",
-            ),
-        )
-        .await?;
-    Ok(())
+/// Komendy obsługiwane przez ten moduł (router w discord/router.rs).
+pub const ROUTES: Routes = Routes::commands(&[CLEAN_NAME, RESYNC_NAME]);
+
+pub fn commands() -> Vec<CreateCommand> {
+    vec![
+        CreateCommand::new(CLEAN_NAME)
+            .description("Wyczyść WSZYSTKIE komendy (gildyjne + globalne) dla tej aplikacji"),
+        CreateCommand::new(RESYNC_NAME)
+            .description("Porównaj komendy gildii z manifestem i zastosuj różnice (bulk overwrite)"),
+    ]
+}
+
+pub async fn on_interaction(ctx: &Context, interaction: Interaction) {
+    let Some(cmd) = interaction.command() else { return; };
+    if let Err(e) = handle_slash(ctx, &cmd).await {
+        tracing::warn!(error=?e, cmd = %cmd.data.name, "commands_sync failed");
+    }
 }
 
 pub async fn handle_slash(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
@@ -47,12 +59,12 @@ pub async fn handle_slash(ctx: &Context, cmd: &CommandInteraction) -> Result<()>
 }
 
 async fn handle_clean(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
-    if !is_allowed(ctx, cmd).await {
-        return reply_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
-    }
     let Some(gid) = cmd.guild_id else {
         return reply_ephemeral(ctx, cmd, "Ta komenda działa tylko w gildii.").await;
     };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::SlashClean) {
+        return reply_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
+    }
 
     let guild_before = gid.get_commands(&ctx.http).await.unwrap_or_default().len();
     let global_before = Command::get_global_commands(&ctx.http).await.unwrap_or_default().len();
@@ -75,112 +87,185 @@ async fn handle_clean(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
 }
 
 async fn handle_resync(ctx: &Context, cmd: &CommandInteraction) -> Result<()> {
-    if !is_allowed(ctx, cmd).await {
-        return reply_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
-    }
     let Some(gid) = cmd.guild_id else {
         return reply_ephemeral(ctx, cmd, "Ta komenda działa tylko w gildii.").await;
     };
-
-    // 1) Wyzeruj GLOBAL i GUILD
-    Command::set_global_commands(&ctx.http, Vec::<CreateCommand>::new()).await?;
-    gid.set_commands(&ctx.http, Vec::<CreateCommand>::new()).await?;
-
-    // 2) Rejestruj WSZYSTKIE nasze komendy GUILD
-    //    (każdą logujemy osobno – błędy nie przerwą całego procesu)
-    let mut names: Vec<String> = Vec::new();
-
-    macro_rules! reg {
-        ($fut:expr, $name:expr) => {{
-            match $fut.await {
-                Ok(_) => names.push(format!("/{}", $name)),
-                Err(e) => tracing::warn!(error=?e, "rejestracja {} nie powiodła się", $name),
-            }
-        }};
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::SlashResync) {
+        return reply_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
 
-    reg!(Verify::register_commands(ctx, gid), "verify-panel");
-    reg!(ChatGuard::register_commands(ctx, gid), "chatguard");
-    reg!(AdminPoints::register_commands(ctx, gid), "punkty");
-    // ⬇⬇⬇ TE TRZY BYŁO BRAK
-    reg!(Ban::register_commands(ctx, gid), "ban");
-    reg!(Kick::register_commands(ctx, gid), "kick");
-    reg!(Warns::register_commands(ctx, gid), "warn");
+    // komendy globalne nie są częścią manifestu – zostałyby zdublowane w gildii
+    let global_before = Command::get_global_commands(&ctx.http).await.unwrap_or_default().len();
+    if global_before > 0 {
+        Command::set_global_commands(&ctx.http, Vec::<CreateCommand>::new()).await?;
+    }
 
-    // maintenance
-    reg!(register_commands(ctx, gid), "slash-clean / slash-resync");
+    let diff = sync_guild(ctx, gid).await?;
     if let Err(e) = command_acl::apply_permissions(ctx, gid).await {
         tracing::warn!(error=?e, "apply_permissions failed");
     }
 
+    let mut text = format!("🔁 Synchronizacja komend gildii.\n{}", diff.summary());
+    if global_before > 0 {
+        text.push_str(&format!("\n• Usunięto komendy globalne: **{global_before}**"));
+    }
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(text))
+        .await?;
+    Ok(())
+}
 
-    let guild_now = gid.get_commands(&ctx.http).await.unwrap_or_default();
-    let global_now = Command::get_global_commands(&ctx.http).await.unwrap_or_default();
+/* ---------------- manifest + diff ---------------- */
 
-    let list = if guild_now.is_empty() {
-        "_(brak)_".to_string()
-    } else {
-        guild_now.iter().map(|c| format!("/{}", c.name)).collect::<Vec<_>>().join(", ")
-    };
+/// Wszystkie komendy gildyjne bota – jedyne źródło dla rejestracji i /slash-resync.
+pub fn manifest() -> Vec<CreateCommand> {
+    [
+        Verify::commands(),
+        AdminPoints::commands(),
+        Ban::commands(),
+        Kick::commands(),
+        Warns::commands(),
+        MDel::commands(),
+        Mute::commands(),
+        UserInfo::commands(),
+        AdmCheck::commands(),
+        Cases::commands(),
+        Registry::commands(),
+        Perms::commands(),
+        IdGuard::commands(),
+        AltGuardAdmin::commands(),
+        Raid::commands(),
+        commands(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
 
-    reply_ephemeral(
-        ctx,
-        cmd,
-        &format!(
-            "🔁 Przeładowano komendy.\n• Guild: {}\n• Global: {}",
-            list,
-            if global_now.is_empty() {
-                "_(brak)_".to_string()
+/// Wynik porównania manifestu z komendami zarejestrowanymi w gildii.
+#[derive(Debug, Default)]
+pub struct CommandDiff {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl CommandDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let list = |v: &[String]| {
+            if v.is_empty() {
+                "—".to_string()
             } else {
-                global_now.iter().map(|c| format!("/{}", c.name)).collect::<Vec<_>>().join(", ")
+                v.iter().map(|n| format!("/{n}")).collect::<Vec<_>>().join(", ")
             }
-        ),
-    )
-    .await
+        };
+        format!(
+            "• ➕ Dodane: {}\n• ✏️ Zmienione: {}\n• ➖ Usunięte: {}\n• Bez zmian: **{}**",
+            list(&self.added),
+            list(&self.updated),
+            list(&self.removed),
+            self.unchanged
+        )
+    }
 }
 
-/* ---------------- helpers ---------------- */
+/// Porównaj manifest z gildią i – tylko gdy coś się różni – zastosuj go jednym bulk overwrite.
+pub async fn sync_guild(ctx: &Context, gid: GuildId) -> Result<CommandDiff> {
+    let wanted = manifest();
+    let existing = gid.get_commands(&ctx.http).await?;
+    let diff = diff_commands(&wanted, &existing)?;
 
-async fn is_allowed(ctx: &Context, cmd: &CommandInteraction) -> bool {
-    let Some(gid) = cmd.guild_id else { return false; };
+    if !diff.is_empty() {
+        gid.set_commands(&ctx.http, wanted).await?;
+    }
+    Ok(diff)
+}
 
-    if let Ok(pg) = gid.to_partial_guild(&ctx.http).await {
-        if pg.owner_id == cmd.user.id {
-            return true;
-        }
+fn diff_commands(wanted: &[CreateCommand], existing: &[Command]) -> Result<CommandDiff> {
+    let mut current: HashMap<String, Json> = HashMap::new();
+    for c in existing {
+        current.insert(c.name.clone(), normalize_command(serde_json::to_value(c)?));
     }
 
-    let env = std::env::var("TSS_ENV").unwrap_or_else(|_| "production".to_string());
-    if let Ok(member) = gid.member(&ctx.http, cmd.user.id).await {
-        let allowed_roles = [
-            crate::registry::env_roles::owner_id(&env),
-            crate::registry::env_roles::co_owner_id(&env),
-            crate::registry::env_roles::opiekun_id(&env),
-            crate::registry::env_roles::technik_zarzad_id(&env),
-        ];
-        if member.roles.iter().any(|r| allowed_roles.contains(&r.get())) {
-            return true;
-        }
-        if has_administrator(ctx, gid, &member).await {
-            return true;
+    let mut diff = CommandDiff::default();
+    for c in wanted {
+        let v = normalize_command(serde_json::to_value(c)?);
+        let name = v.get("name").and_then(Json::as_str).unwrap_or_default().to_string();
+        match current.remove(&name) {
+            None => diff.added.push(name),
+            Some(old) if old != v => diff.updated.push(name),
+            Some(_) => diff.unchanged += 1,
         }
     }
-    false
+    diff.removed = current.into_keys().collect();
+    diff.removed.sort();
+    Ok(diff)
 }
 
-async fn has_administrator(ctx: &Context, gid: GuildId, member: &Member) -> bool {
-    if let Ok(roles_map) = gid.roles(&ctx.http).await {
-        for rid in &member.roles {
-            if let Some(role) = roles_map.get(rid) {
-                if role.permissions.administrator() {
-                    return true;
-                }
-            }
-        }
+/// Pola istotne dla Discorda (bez id, wersji, lokalizacji i wartości domyślnych).
+const COMPARED_KEYS: &[&str] = &[
+    "name",
+    "description",
+    "type",
+    "options",
+    "required",
+    "choices",
+    "value",
+    "autocomplete",
+    "channel_types",
+    "min_value",
+    "max_value",
+    "min_length",
+    "max_length",
+    "default_member_permissions",
+    "nsfw",
+];
+
+fn normalize_command(v: Json) -> Json {
+    let mut v = normalize(&v);
+    // CreateCommand bez `kind` = CHAT_INPUT (1), Discord zwraca je jawnie
+    if let Json::Object(map) = &mut v {
+        map.entry("type").or_insert(Json::from(1.0));
+    }
+    v
+}
+
+fn normalize(v: &Json) -> Json {
+    match v {
+        Json::Object(map) => Json::Object(
+            map.iter()
+                .filter(|(k, _)| COMPARED_KEYS.contains(&k.as_str()))
+                .map(|(k, val)| (k.clone(), normalize(val)))
+                .filter(|(_, val)| !is_default(val))
+                .collect(),
+        ),
+        Json::Array(items) => Json::Array(items.iter().map(normalize).collect()),
+        // 1 vs 1.0 (builder trzyma liczby inaczej niż odpowiedź API)
+        Json::Number(n) => n.as_f64().map(Json::from).unwrap_or_else(|| v.clone()),
+        other => other.clone(),
     }
-    false
 }
 
+fn is_default(v: &Json) -> bool {
+    match v {
+        Json::Null | Json::Bool(false) => true,
+        Json::Array(a) => a.is_empty(),
+        Json::Object(o) => o.is_empty(),
+        _ => false,
+    }
+}
+
+/* ---------------- helpers ---------------- */
+
 async fn reply_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
     cmd.create_response(
         &ctx.http,
@@ -193,3 +278,68 @@ async fn reply_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) ->
     .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serenity::all::{CommandOptionType, CreateCommandOption};
+
+    #[test]
+    fn api_shape_matches_builder_shape() {
+        let built = normalize_command(json!({
+            "name": "kick",
+            "description": "Wyrzuć",
+            "options": [{ "type": 6, "name": "user", "description": "Kto", "required": true }]
+        }));
+        let from_api = normalize_command(json!({
+            "id": "1", "application_id": "2", "version": "3", "type": 1.0,
+            "name": "kick", "description": "Wyrzuć", "nsfw": false, "name_localizations": null,
+            "options": [{ "type": 6.0, "name": "user", "description": "Kto", "required": true, "autocomplete": false }]
+        }));
+        assert_eq!(built, from_api);
+
+        let changed = normalize_command(json!({ "name": "kick", "description": "Inny opis" }));
+        assert_ne!(built, changed);
+    }
+
+    /// Komenda tak, jak zwraca ją API (id, wersja, liczby jako float).
+    fn registered(name: &str, description: &str) -> Command {
+        serde_json::from_value(json!({
+            "id": "10", "application_id": "20", "version": "30", "type": 1,
+            "name": name, "description": description, "nsfw": false,
+            "default_member_permissions": null,
+            "options": [{ "type": 6, "name": "user", "description": "Kto", "required": true }]
+        }))
+        .unwrap()
+    }
+
+    fn wanted(name: &str, description: &str) -> CreateCommand {
+        CreateCommand::new(name).description(description).add_option(
+            CreateCommandOption::new(CommandOptionType::User, "user", "Kto").required(true),
+        )
+    }
+
+    #[test]
+    fn diff_classifies_added_updated_removed_unchanged() {
+        let existing = [registered("kick", "Wyrzuć"), registered("ban", "Zbanuj"), registered("old", "Stara")];
+        let manifest = [wanted("kick", "Wyrzuć"), wanted("ban", "Zbanuj użytkownika"), wanted("warn", "Ostrzeż")];
+
+        let diff = diff_commands(&manifest, &existing).unwrap();
+        assert_eq!(diff.added, vec!["warn"]);
+        assert_eq!(diff.updated, vec!["ban"]);
+        assert_eq!(diff.removed, vec!["old"]);
+        assert_eq!(diff.unchanged, 1);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn identical_manifest_skips_overwrite() {
+        let existing = [registered("kick", "Wyrzuć"), registered("ban", "Zbanuj")];
+        let manifest = [wanted("ban", "Zbanuj"), wanted("kick", "Wyrzuć")];
+
+        let diff = diff_commands(&manifest, &existing).unwrap();
+        assert!(diff.is_empty(), "{diff:?}");
+        assert_eq!(diff.unchanged, 2);
+    }
+}
@@ -69,6 +69,7 @@ impl Settings {
                 intents: vec![
                     "GUILDS".into(),
                     "GUILD_MEMBERS".into(),
+                    "GUILD_INVITES".into(),
                     "GUILD_MESSAGES".into(),
                     "MESSAGE_CONTENT".into(),
                     "GUILD_MESSAGE_REACTIONS".into(),
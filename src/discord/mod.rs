@@ -1,9 +1,12 @@
 // src/discord/mod.rs
+pub mod router;
+
 use std::sync::Arc;
 use anyhow::Result;
 
-use crate::{altguard, AppContext};
+use crate::{altguard, idguard, invites, raid, AppContext};
 use crate::altguard::{JoinMeta, ScoreInput};
+use crate::idguard::IdgInput;
 
 use serenity::all::*;
 use serenity::async_trait;
@@ -12,25 +15,20 @@ use crate::stats_channels::StatsChannels;
 use crate::new_channels::NewChannels;
 use crate::welcome::Welcome;
 use crate::verify::Verify;
-use crate::chatguard::ChatGuard;
-use crate::ban::Ban;
-use crate::kick::Kick;
 use crate::warn::Warns;
-use crate::mdel::MDel;
 use crate::mute::Mute;
-use crate::userinfo::UserInfo;
-use crate::admcheck::AdmCheck;
+use crate::chatguard::ChatGuard;
 
 // --- AdminScore (/points)
 use crate::admin_points::AdminPoints;
 
 // --- Commands Sync (/slash-clean, /slash-resync)
 use crate::commands_sync;
-use crate::command_acl;
 
 pub struct Handler {
     pub app: Arc<AppContext>,
     pub altguard: Arc<altguard::AltGuard>,
+    pub idguard: Arc<idguard::IdGuard>,
 }
 
 #[async_trait]
@@ -64,23 +62,17 @@ impl EventHandler for Handler {
         NewChannels::on_channel_delete(&ctx, &self.app, &channel, messages).await;
     }
 
-    // brama interakcji: slash + komponenty
+    async fn invite_create(&self, _ctx: Context, data: InviteCreateEvent) {
+        invites::on_invite_create(&data).await;
+    }
+
+    async fn invite_delete(&self, _ctx: Context, data: InviteDeleteEvent) {
+        invites::on_invite_delete(&data).await;
+    }
+
+    // brama interakcji: slash + komponenty + modale → dokładnie jeden moduł (router.rs)
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        // Pamiętaj: ostatni handler dostaje "goły" interaction bez klonowania.
-        AdminPoints::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        ChatGuard::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        Ban::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        Kick::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        Warns::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        Mute::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        UserInfo::on_interaction(&ctx, &self.app, interaction.clone()).await;
-        AdmCheck::on_interaction(&ctx, &self.app, interaction.clone()).await;
-
-        // /mdel – PRZED Verify, bo Verify zużywa Interaction
-        MDel::on_interaction(&ctx, &self.app, interaction.clone()).await;
-
-        // Verify (panel weryfikacji) — NA KOŃCU
-        Verify::on_interaction(&ctx, &self.app, interaction).await;
+        router::dispatch(&ctx, &self.app, interaction).await;
     }
 
     async fn message(&self, ctx: Context, msg: Message) {
@@ -99,8 +91,30 @@ impl EventHandler for Handler {
     async fn guild_create(&self, ctx: Context, guild: Guild, _is_new: Option<bool>) {
         let gid = guild.id.get();
 
-        // AltGuard warmup
+        // Profil gildii + rejestr ról/kanałów (przed wszystkim, co czyta env_roles/env_channels)
+        if let Err(e) = crate::guilds::warmup(&self.app.db, gid, &guild.name).await {
+            tracing::warn!(error=?e, gid, "guild profile warmup failed");
+        }
+        if let Err(e) = crate::registry::warmup(&self.app.db, gid).await {
+            tracing::warn!(error=?e, gid, "registry warmup failed");
+        }
+        if let Err(e) = crate::permissions::warmup(&self.app.db, gid).await {
+            tracing::warn!(error=?e, gid, "permissions warmup failed");
+        }
+
+        // AltGuard + IdGuard warmup
         self.altguard.warmup_cache(gid).await;
+        self.idguard.warmup_cache(gid).await;
+
+        // Zrzut zaproszeń (diff użyć przy wejściu -> invite affinity)
+        if let Err(e) = crate::invites::warmup(&ctx, gid).await {
+            tracing::warn!(error=?e, gid, "invite snapshot failed (Manage Server?)");
+        }
+
+        // Raid przerwany restartem – lockdown trwa, watcher ciszy od nowa
+        if let Err(e) = raid::resume(&ctx, &self.app, gid).await {
+            tracing::warn!(error=?e, gid, "raid state resume failed");
+        }
 
         // Statystyki kanałów
         StatsChannels::sync_on_ready(&ctx, &self.app, gid).await;
@@ -118,33 +132,44 @@ impl EventHandler for Handler {
             tracing::warn!(error=?e, gid, "register_commands_for_guild failed (on guild_create)");
         }
 
-        tracing::info!(guild=%guild.name, gid, "AltGuard cache warmed + stats synced + verify panel ensured + commands registered (see warnings if any failed)");
+        tracing::info!(guild=%guild.name, gid, "AltGuard/IdGuard cache warmed + stats synced + verify panel ensured + commands registered (see warnings if any failed)");
     }
 
     async fn guild_member_addition(&self, ctx: Context, member: Member) {
         let gid = member.guild_id.get();
         let uid = member.user.id.get();
 
+        // Zaproszenie: diff liczników użyć względem zrzutu
+        let invite = invites::detect_join(&ctx, gid).await;
+        if let Err(e) = invites::record_join(&self.app.db, gid, uid, invite.as_ref()).await {
+            tracing::warn!(error=?e, gid, uid, "member_joins insert failed");
+        }
+        let invite_code = invite.as_ref().map(|i| i.code.clone());
+        let inviter_id = invite.as_ref().and_then(|i| i.inviter_id);
+
         self.altguard.record_join(JoinMeta {
             guild_id: gid,
             user_id: uid,
-            invite_code: None,
-            inviter_id: None,
+            invite_code: invite_code.clone(),
+            inviter_id,
             at: None,
         }).await;
 
-        let avatar_url = member.user.avatar_url();
-        match self.altguard.score_user(&ScoreInput {
+        // Fala wejść -> tryb raidu (lockdown); w trakcie raidu każdy nowy trafia do kwarantanny
+        raid::on_join(&ctx, &self.app, &member).await;
+
+        let input = ScoreInput {
             guild_id: gid,
             user_id: uid,
             username: Some(member.user.name.clone()),
             display_name: member.nick.clone(),
             global_name: member.user.global_name.clone(),
-            invite_code: None,
-            inviter_id: None,
+            invite_code,
+            inviter_id,
             has_trusted_role: false,
-            avatar_url,
-        }).await {
+            avatar_url: member.user.avatar_url(),
+        };
+        match self.altguard.score_user(&input).await {
             Ok(score) => {
                 tracing::info!(
                     gid,
@@ -154,15 +179,44 @@ impl EventHandler for Handler {
                     explain=%score.explain,
                     "JOIN scored"
                 );
+                // MEDIUM/HIGH -> akcja z polityki gildii + log z przyciskami
+                if !member.user.bot {
+                    self.altguard.enforce(&ctx, &input, &score).await;
+                }
             }
             Err(e) => {
                 tracing::warn!(error=?e, gid, uid, "AltGuard scoring failed");
             }
         }
 
+        // IdGuard: nick/avatar (Watch/Block -> log z przyciskami; Block zatrzyma weryfikację)
+        self.idguard.screen(&ctx, &IdgInput::from_member(&member)).await;
+
         StatsChannels::handle_member_join(&ctx, &self.app, &member).await;
     }
 
+    // Bez cache `old_if_available` jest puste – IdGuard sam porównuje odcisk nicka/avatara,
+    // więc zmiany samych ról nie wywołują ponownego skanu.
+    async fn guild_member_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Member>,
+        _new: Option<Member>,
+        event: GuildMemberUpdateEvent,
+    ) {
+        if event.user.bot { return; }
+
+        let input = IdgInput {
+            guild_id: event.guild_id.get(),
+            user_id: event.user.id.get(),
+            username: Some(event.user.name.clone()),
+            display_name: event.nick.clone(),
+            global_name: event.user.global_name.clone(),
+            avatar_url: event.user.avatar_url(),
+        };
+        self.idguard.screen(&ctx, &input).await;
+    }
+
     async fn guild_member_removal(
         &self,
         ctx: Context,
@@ -170,6 +224,7 @@ impl EventHandler for Handler {
         user: User,
         _member: Option<Member>,
     ) {
+        self.idguard.forget(guild_id.get(), user.id.get());
         Welcome::send_goodbye(&ctx, &self.app, guild_id, &user).await;
         StatsChannels::handle_member_remove(&ctx, &self.app, guild_id.get()).await;
     }
@@ -181,6 +236,7 @@ fn intents_from_settings(names: &[String]) -> GatewayIntents {
         match n.as_str() {
             "GUILDS" => i |= GatewayIntents::GUILDS,
             "GUILD_MEMBERS" => i |= GatewayIntents::GUILD_MEMBERS,
+            "GUILD_INVITES" => i |= GatewayIntents::GUILD_INVITES,
             "GUILD_MESSAGES" => i |= GatewayIntents::GUILD_MESSAGES,
             "GUILD_MESSAGE_REACTIONS" => i |= GatewayIntents::GUILD_MESSAGE_REACTIONS,
             "GUILD_PRESENCES" => i |= GatewayIntents::GUILD_PRESENCES,
@@ -202,6 +258,7 @@ pub async fn run_bot(ctx: Arc<AppContext>) -> Result<()> {
     let handler = Handler {
         app: ctx.clone(),
         altguard: ctx.altguard(),
+        idguard: ctx.idguard(),
     };
 
     let mut client = serenity::Client::builder(token, intents)
@@ -217,42 +274,19 @@ pub async fn run_bot(ctx: Arc<AppContext>) -> Result<()> {
    REJESTR KOMEND
    ============================================================ */
 async fn register_commands_for_guild(ctx: &Context, guild_id: GuildId) -> Result<()> {
-    // Każdą komendę rejestruj osobno, z nazwą w logu.
-    if let Err(e) = Verify::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register verify failed");
-    }
-    if let Err(e) = ChatGuard::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register chatguard failed");
-    }
-    if let Err(e) = AdminPoints::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register punkty failed");
-    }
-    if let Err(e) = Ban::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register ban failed");
+    // Cały manifest jednym bulk overwrite – i tylko wtedy, gdy różni się od stanu gildii.
+    let diff = commands_sync::sync_guild(ctx, guild_id).await?;
+    if diff.is_empty() {
+        tracing::debug!(gid=%guild_id.get(), unchanged = diff.unchanged, "slash commands up to date");
+    } else {
+        tracing::info!(
+            gid=%guild_id.get(),
+            added = ?diff.added,
+            updated = ?diff.updated,
+            removed = ?diff.removed,
+            unchanged = diff.unchanged,
+            "slash commands synced"
+        );
     }
-    if let Err(e) = Kick::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register kick failed");
-    }
-    if let Err(e) = Warns::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register warn failed");
-    }
-    if let Err(e) = MDel::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register mdel failed");
-    }
-    if let Err(e) = Mute::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register mute failed");
-    }
-    if let Err(e) = UserInfo::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register mute failed");
-    }
-    if let Err(e) = AdmCheck::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "register mute failed");
-    }
-
-    // maintenance (zostawiamy jak było)
-    if let Err(e) = commands_sync::register_commands(ctx, guild_id).await {
-        tracing::warn!(error=?e, gid=%guild_id.get(), "commands_sync::register_commands failed");
-    }
-
     Ok(())
 }
@@ -0,0 +1,252 @@
+// src/discord/router.rs
+//
+// Jeden dispatcher interakcji. Każdy moduł deklaruje swoje komendy i prefiksy custom_id
+// (`ROUTES`), a router oddaje interakcję dokładnie jednemu handlerowi (bez klonowania do
+// wszystkich modułów) i mierzy czas jego obsługi. Nieobsłużone interakcje trafiają do logu.
+
+use std::time::Instant;
+
+use serenity::all::{Context, Interaction};
+
+use crate::admcheck::AdmCheck;
+use crate::admin_points::AdminPoints;
+use crate::altguard::AltGuard;
+use crate::altguard_admin::AltGuardAdmin;
+use crate::appeals::Appeals;
+use crate::ban::Ban;
+use crate::cases::Cases;
+use crate::chatguard::ChatGuard;
+use crate::commands_sync;
+use crate::idguard::IdGuard;
+use crate::kick::Kick;
+use crate::mdel::MDel;
+use crate::mute::Mute;
+use crate::perms_admin::Perms;
+use crate::raid::Raid;
+use crate::registry_admin::Registry;
+use crate::userinfo::UserInfo;
+use crate::verify::Verify;
+use crate::warn::Warns;
+use crate::AppContext;
+
+/// Powyżej tego czasu handler i tak nie zdąży z odpowiedzią bez defer (limit Discorda: 3 s).
+const SLOW_HANDLER_MS: u128 = 2_500;
+
+/// Co obsługuje moduł: nazwy slash-komend (także ich autocomplete) i prefiksy custom_id.
+pub struct Routes {
+    pub commands: &'static [&'static str],
+    pub components: &'static [&'static str],
+    pub modals: &'static [&'static str],
+}
+
+impl Routes {
+    pub const fn commands(commands: &'static [&'static str]) -> Self {
+        Self { commands, components: &[], modals: &[] }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    AdminPoints,
+    ChatGuard,
+    Ban,
+    Kick,
+    Warns,
+    Mute,
+    UserInfo,
+    AdmCheck,
+    Cases,
+    Appeals,
+    Registry,
+    Perms,
+    MDel,
+    Verify,
+    IdGuard,
+    AltGuard,
+    AltGuardAdmin,
+    Raid,
+    CommandsSync,
+}
+
+static TABLE: &[(Target, Routes)] = &[
+    (Target::AdminPoints, AdminPoints::ROUTES),
+    (Target::ChatGuard, ChatGuard::ROUTES),
+    (Target::Ban, Ban::ROUTES),
+    (Target::Kick, Kick::ROUTES),
+    (Target::Warns, Warns::ROUTES),
+    (Target::Mute, Mute::ROUTES),
+    (Target::UserInfo, UserInfo::ROUTES),
+    (Target::AdmCheck, AdmCheck::ROUTES),
+    (Target::Cases, Cases::ROUTES),
+    (Target::Appeals, Appeals::ROUTES),
+    (Target::Registry, Registry::ROUTES),
+    (Target::Perms, Perms::ROUTES),
+    (Target::MDel, MDel::ROUTES),
+    (Target::Verify, Verify::ROUTES),
+    (Target::IdGuard, IdGuard::ROUTES),
+    (Target::AltGuard, AltGuard::ROUTES),
+    (Target::AltGuardAdmin, AltGuardAdmin::ROUTES),
+    (Target::Raid, Raid::ROUTES),
+    (Target::CommandsSync, commands_sync::ROUTES),
+];
+
+impl Target {
+    fn name(self) -> &'static str {
+        match self {
+            Target::AdminPoints => "admin_points",
+            Target::ChatGuard => "chatguard",
+            Target::Ban => "ban",
+            Target::Kick => "kick",
+            Target::Warns => "warn",
+            Target::Mute => "mute",
+            Target::UserInfo => "userinfo",
+            Target::AdmCheck => "admcheck",
+            Target::Cases => "cases",
+            Target::Appeals => "appeals",
+            Target::Registry => "registry",
+            Target::Perms => "perms",
+            Target::MDel => "mdel",
+            Target::Verify => "verify",
+            Target::IdGuard => "idguard",
+            Target::AltGuard => "altguard",
+            Target::AltGuardAdmin => "altguard_admin",
+            Target::Raid => "raid",
+            Target::CommandsSync => "commands_sync",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Command,
+    Autocomplete,
+    Component,
+    Modal,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Command => "command",
+            Kind::Autocomplete => "autocomplete",
+            Kind::Component => "component",
+            Kind::Modal => "modal",
+        }
+    }
+}
+
+/// Rodzaj interakcji + klucz routingu (nazwa komendy albo custom_id).
+fn describe(interaction: &Interaction) -> Option<(Kind, &str)> {
+    match interaction {
+        Interaction::Command(c) => Some((Kind::Command, c.data.name.as_str())),
+        Interaction::Autocomplete(a) => Some((Kind::Autocomplete, a.data.name.as_str())),
+        Interaction::Component(c) => Some((Kind::Component, c.data.custom_id.as_str())),
+        Interaction::Modal(m) => Some((Kind::Modal, m.data.custom_id.as_str())),
+        _ => None,
+    }
+}
+
+fn resolve(kind: Kind, key: &str) -> Option<Target> {
+    TABLE.iter().find_map(|(target, routes)| {
+        let hit = match kind {
+            Kind::Command | Kind::Autocomplete => routes.commands.contains(&key),
+            Kind::Component => routes.components.iter().any(|p| key.starts_with(p)),
+            Kind::Modal => routes.modals.iter().any(|p| key.starts_with(p)),
+        };
+        hit.then_some(*target)
+    })
+}
+
+/// Brama z `interaction_create`.
+pub async fn dispatch(ctx: &Context, app: &AppContext, interaction: Interaction) {
+    let Some((kind, key)) = describe(&interaction) else {
+        tracing::debug!(kind = ?interaction.kind(), "ignored interaction");
+        return;
+    };
+    let Some(target) = resolve(kind, key) else {
+        tracing::warn!(kind = kind.as_str(), key, guild = ?interaction_guild(&interaction), "unrouted interaction");
+        return;
+    };
+    let key = key.to_string();
+
+    let started = Instant::now();
+    run(target, ctx, app, interaction).await;
+    let elapsed_ms = started.elapsed().as_millis();
+
+    if elapsed_ms > SLOW_HANDLER_MS {
+        tracing::warn!(handler = target.name(), kind = kind.as_str(), key = %key, elapsed_ms, "slow interaction handler");
+    } else {
+        tracing::debug!(handler = target.name(), kind = kind.as_str(), key = %key, elapsed_ms, "interaction handled");
+    }
+}
+
+async fn run(target: Target, ctx: &Context, app: &AppContext, interaction: Interaction) {
+    match target {
+        Target::AdminPoints => AdminPoints::on_interaction(ctx, app, interaction).await,
+        Target::ChatGuard => ChatGuard::on_interaction(ctx, app, interaction).await,
+        Target::Ban => Ban::on_interaction(ctx, app, interaction).await,
+        Target::Kick => Kick::on_interaction(ctx, app, interaction).await,
+        Target::Warns => Warns::on_interaction(ctx, app, interaction).await,
+        Target::Mute => Mute::on_interaction(ctx, app, interaction).await,
+        Target::UserInfo => UserInfo::on_interaction(ctx, app, interaction).await,
+        Target::AdmCheck => AdmCheck::on_interaction(ctx, app, interaction).await,
+        Target::Cases => Cases::on_interaction(ctx, app, interaction).await,
+        Target::Appeals => Appeals::on_interaction(ctx, app, interaction).await,
+        Target::Registry => Registry::on_interaction(ctx, app, interaction).await,
+        Target::Perms => Perms::on_interaction(ctx, app, interaction).await,
+        Target::MDel => MDel::on_interaction(ctx, app, interaction).await,
+        Target::Verify => Verify::on_interaction(ctx, app, interaction).await,
+        Target::IdGuard => app.idguard().on_interaction(ctx, app, interaction).await,
+        Target::AltGuard => app.altguard().on_interaction(ctx, app, interaction).await,
+        Target::AltGuardAdmin => AltGuardAdmin::on_interaction(ctx, app, interaction).await,
+        Target::Raid => Raid::on_interaction(ctx, app, interaction).await,
+        Target::CommandsSync => commands_sync::on_interaction(ctx, interaction).await,
+    }
+}
+
+fn interaction_guild(interaction: &Interaction) -> Option<u64> {
+    match interaction {
+        Interaction::Command(c) | Interaction::Autocomplete(c) => c.guild_id,
+        Interaction::Component(c) => c.guild_id,
+        Interaction::Modal(m) => m.guild_id,
+        _ => None,
+    }
+    .map(|g| g.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_routes_to_exactly_one_handler() {
+        for (i, (a, ra)) in TABLE.iter().enumerate() {
+            for (b, rb) in &TABLE[i + 1..] {
+                for c in ra.commands {
+                    assert!(!rb.commands.contains(c), "/{c}: {a:?} i {b:?}");
+                }
+                let overlaps = |x: &[&str], y: &[&str]| {
+                    x.iter().any(|p| y.iter().any(|q| p.starts_with(q) || q.starts_with(p)))
+                };
+                assert!(!overlaps(ra.components, rb.components), "component prefix: {a:?} i {b:?}");
+                assert!(!overlaps(ra.modals, rb.modals), "modal prefix: {a:?} i {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_known_keys() {
+        assert_eq!(resolve(Kind::Command, "unban"), Some(Target::Ban));
+        assert_eq!(resolve(Kind::Autocomplete, "warn-config"), Some(Target::Warns));
+        assert_eq!(resolve(Kind::Command, "teach"), Some(Target::IdGuard));
+        assert_eq!(resolve(Kind::Command, "slash-resync"), Some(Target::CommandsSync));
+        assert_eq!(resolve(Kind::Component, "banp:confirm:abc"), Some(Target::Ban));
+        assert_eq!(resolve(Kind::Component, "cgq:12:approve"), Some(Target::ChatGuard));
+        assert_eq!(resolve(Kind::Modal, "appeal:modal:1"), Some(Target::Appeals));
+        assert_eq!(resolve(Kind::Component, "verify_accept"), Some(Target::Verify));
+        assert_eq!(resolve(Kind::Component, "altg_ok:1"), Some(Target::AltGuard));
+        assert_eq!(resolve(Kind::Command, "altguard"), Some(Target::AltGuardAdmin));
+        assert_eq!(resolve(Kind::Command, "raid"), Some(Target::Raid));
+        assert_eq!(resolve(Kind::Component, "nope"), None);
+    }
+}
@@ -17,7 +17,8 @@ use url::Url;
 use crate::admin_points::AdminPoints;
 use crate::registry::env_channels;
 use crate::AppContext;
-use crate::chatguard::{BRAND_FOOTER, is_staff_member_comp, log_violation};
+use crate::chatguard::{BRAND_FOOTER, log_violation};
+use crate::permissions::{self, Permission};
 
 const MAX_ATTACHMENTS: usize = 10; // limit Discorda
 const HTTP_TIMEOUT_SECS: u64 = 15;
@@ -45,10 +46,9 @@ pub async fn maybe_ensure_tables(db: &Pool<Postgres>) {
 }
 
 pub async fn on_component(ctx: &Context, app: &AppContext, comp: &ComponentInteraction) -> Result<()> {
-    let Some(_gid) = comp.guild_id else { return Ok(()); };
-    let env = app.env();
+    let Some(gid) = comp.guild_id else { return Ok(()); };
 
-    if !is_staff_member_comp(&env, comp.member.as_ref()) {
+    if !permissions::authorize(gid, comp.member.as_ref(), Permission::Review) {
         let _ = comp
             .create_response(
                 &ctx.http,
@@ -353,19 +353,18 @@ async fn approve_flow_button(
 }
 
 pub async fn handle_attachments(ctx: &Context, app: &AppContext, msg: &Message, is_staff: bool) {
-    let env = app.env();
-
     if is_staff {
         return;
     }
+    let gid = msg.guild_id.map(|g| g.get()).unwrap_or(0);
 
     let allowed_media = vec![
-        env_channels::fun::clips_id(&env),
-        env_channels::fun::photos_id(&env),
-        env_channels::fun::memes_id(&env),
-        env_channels::fun::show_off_id(&env),
-        env_channels::fun::selfie_id(&env),
-        env_channels::fun::nsfw_id(&env),
+        env_channels::fun::clips_id(gid),
+        env_channels::fun::photos_id(gid),
+        env_channels::fun::memes_id(gid),
+        env_channels::fun::show_off_id(gid),
+        env_channels::fun::selfie_id(gid),
+        env_channels::fun::nsfw_id(gid),
     ];
 
     let is_media_channel = allowed_media.contains(&msg.channel_id.get());
@@ -401,7 +400,7 @@ pub async fn handle_attachments(ctx: &Context, app: &AppContext, msg: &Message,
     .await
     .unwrap_or(0);
 
-    let verify_chan = verify_channel_shortcut::photos_id(&env);
+    let verify_chan = env_channels::verify_photos_id(gid);
     if verify_chan != 0 {
         let title = "🖼️ Nowe zdjęcie do akceptacji";
         let desc = format!(
@@ -888,15 +887,3 @@ async fn ensure_tables(db: &Pool<Postgres>) -> Result<()> {
     info!("Fotosystem: tables ensured / migrated.");
     Ok(())
 }
-
-mod verify_channel_shortcut {
-    pub fn photos_id(env: &str) -> u64 {
-        use crate::registry::channels;
-        let prod = env.eq_ignore_ascii_case("production") || env.eq_ignore_ascii_case("prod");
-        if prod {
-            channels::prod::VERIFY_PHOTOS
-        } else {
-            channels::dev::VERIFY_PHOTOS
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,132 @@
+// src/guilds.rs
+//
+// Profil gildii (tss.guilds.profile) – zastępuje globalny TSS_ENV.
+// Profil mówi, które stałe z registry.rs są fallbackiem dla danej gildii; dzięki temu
+// jeden proces obsługuje serwer główny, testowy i partnerski (custom = tylko /registry).
+
+use anyhow::Result;
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+use sqlx::{Pool, Postgres, Row};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuildProfile {
+    Production,
+    Development,
+    Custom,
+}
+
+impl GuildProfile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GuildProfile::Production => "production",
+            GuildProfile::Development => "development",
+            GuildProfile::Custom => "custom",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GuildProfile::Production => "serwer główny",
+            GuildProfile::Development => "serwer testowy",
+            GuildProfile::Custom => "własny (tylko /registry)",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "production" | "prod" => Some(GuildProfile::Production),
+            "development" | "dev" => Some(GuildProfile::Development),
+            "custom" => Some(GuildProfile::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// guild_id → profil (ładowany w guild_create)
+static PROFILES: Lazy<DashMap<u64, GuildProfile>> = Lazy::new(DashMap::new);
+
+/// Profil dla gildii, której jeszcze nie ma w tss.guilds (z ustawień procesu).
+static DEFAULT_PROFILE: OnceCell<GuildProfile> = OnceCell::new();
+
+/// Ustaw domyślny profil nowych gildii (raz, przy starcie).
+pub fn init_default(env: &str) {
+    let p = GuildProfile::parse(env).unwrap_or(GuildProfile::Development);
+    let _ = DEFAULT_PROFILE.set(p);
+}
+
+pub fn default_profile() -> GuildProfile {
+    DEFAULT_PROFILE.get().copied().unwrap_or(GuildProfile::Development)
+}
+
+/// Profil gildii (cache → domyślny).
+pub fn profile(gid: u64) -> GuildProfile {
+    PROFILES.get(&gid).map(|p| *p).unwrap_or_else(default_profile)
+}
+
+/// Upewnij się, że gildia ma wiersz w tss.guilds, i załaduj jej profil do cache.
+/// Wiersz sprzed profili (profile IS NULL) dostaje domyślny profil procesu.
+pub async fn warmup(db: &Pool<Postgres>, gid: u64, name: &str) -> Result<GuildProfile> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO tss.guilds (guild_id, name, profile)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id) DO UPDATE
+           SET name = EXCLUDED.name,
+               profile = COALESCE(tss.guilds.profile, EXCLUDED.profile),
+               updated_at = now()
+        RETURNING profile
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(name)
+    .bind(default_profile().as_str())
+    .fetch_one(db)
+    .await?;
+
+    let p = row
+        .get::<Option<String>, _>("profile")
+        .and_then(|s| GuildProfile::parse(&s))
+        .unwrap_or_else(default_profile);
+    PROFILES.insert(gid, p);
+    Ok(p)
+}
+
+/// Załaduj profile wszystkich znanych gildii (start procesu – zanim ruszą workery,
+/// a guild_create jeszcze nie przyszedł). Zwraca ich ID.
+pub async fn warmup_all(db: &Pool<Postgres>) -> Result<Vec<u64>> {
+    let rows = sqlx::query("SELECT guild_id, profile FROM tss.guilds")
+        .fetch_all(db)
+        .await?;
+
+    let mut ids = Vec::with_capacity(rows.len());
+    for r in rows {
+        let gid = r.get::<i64, _>("guild_id") as u64;
+        let p = r
+            .get::<Option<String>, _>("profile")
+            .and_then(|s| GuildProfile::parse(&s))
+            .unwrap_or_else(default_profile);
+        PROFILES.insert(gid, p);
+        ids.push(gid);
+    }
+    Ok(ids)
+}
+
+/// Zmień profil gildii (wiersz istnieje po warmup, ale upsert na wszelki wypadek).
+pub async fn set_profile(db: &Pool<Postgres>, gid: u64, name: &str, p: GuildProfile) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tss.guilds (guild_id, name, profile)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id) DO UPDATE SET profile = EXCLUDED.profile, updated_at = now()
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(name)
+    .bind(p.as_str())
+    .execute(db)
+    .await?;
+
+    PROFILES.insert(gid, p);
+    Ok(())
+}
@@ -3,6 +3,7 @@
 use std::{
     cmp::Ordering,
     collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
     sync::Arc,
     time::Duration,
 };
@@ -20,13 +21,18 @@ use serenity::all::{
     ButtonStyle, ChannelId, CommandDataOption, CommandDataOptionValue, CommandInteraction,
     CommandOptionType, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateCommand,
     CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
-    CreateInteractionResponseMessage, CreateMessage, EditInteractionResponse, GuildId, Interaction,
+    CreateInteractionResponseMessage, CreateMessage, EditInteractionResponse, EditMember, GuildId, Interaction, Member, RoleId,
+    UserId,
 };
 
 use url::Url;
 
+use crate::discord::router::Routes;
 use crate::{
     AppContext,
+    avatar_nsfw::{self, NsfwScore},
+    avatar_ocr,
+    permissions::{self, Permission},
     registry::{env_channels, env_roles},
 };
 
@@ -39,12 +45,48 @@ const BRAND_FOOTER: &str = "Tigris Security System™ • IdGuard";
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum IdgMode {
     Monitor, // tylko loguj
-    Auto,    // (placeholder) auto-reakcje – obecnie tylko raport + przyciski
+    Auto,    // BLOCK -> `auto_action`, WATCH -> kolejka przeglądu (tss.idg_actions)
 }
 impl Default for IdgMode {
     fn default() -> Self { IdgMode::Monitor }
 }
 
+/// Akcja trybu Auto dla werdyktu BLOCK (cofana przyciskiem z logu).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IdgAutoAction {
+    #[default]
+    Rename,     // nick -> neutralny
+    Quarantine, // rola z rejestru `role.quarantine`
+    Kick,
+}
+
+impl IdgAutoAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IdgAutoAction::Rename => "rename",
+            IdgAutoAction::Quarantine => "quarantine",
+            IdgAutoAction::Kick => "kick",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "rename" => Some(IdgAutoAction::Rename),
+            "quarantine" => Some(IdgAutoAction::Quarantine),
+            "kick" => Some(IdgAutoAction::Kick),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IdgAutoAction::Rename => "✏️ Reset nicku",
+            IdgAutoAction::Quarantine => "🚧 Kwarantanna",
+            IdgAutoAction::Kick => "👢 Kick",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum IdgPreset {
     Lenient,
@@ -67,11 +109,17 @@ pub struct IdgConfig {
     pub thresholds: IdgThresholds,
     #[serde(default = "default_weights")]
     pub weights: IdgWeights,
+    #[serde(default)]
+    pub auto_action: IdgAutoAction,
+    /// Pewność heurystyki NSFW (%), od której avatar dostaje sygnał AvatarNSFW.
+    #[serde(default = "default_nsfw_threshold")]
+    pub nsfw_threshold: u8,
 }
 
 fn default_true() -> bool { true }
 fn default_thresholds() -> IdgThresholds { IdgConfig::default().thresholds }
 fn default_weights() -> IdgWeights { IdgConfig::default().weights }
+fn default_nsfw_threshold() -> u8 { 80 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdgThresholds {
@@ -102,6 +150,8 @@ impl Default for IdgConfig {
                 avatar_ocr: 30,
                 avatar_nsfw: 50,
             },
+            auto_action: IdgAutoAction::default(),
+            nsfw_threshold: default_nsfw_threshold(),
         }
     }
 }
@@ -139,6 +189,26 @@ pub struct IdgInput {
     pub avatar_url: Option<String>,
 }
 
+impl IdgInput {
+    pub fn from_member(m: &Member) -> Self {
+        Self {
+            guild_id: m.guild_id.get(),
+            user_id: m.user.id.get(),
+            username: Some(m.user.name.clone()),
+            display_name: m.nick.clone(),
+            global_name: m.user.global_name.clone(),
+            avatar_url: m.user.avatar_url(),
+        }
+    }
+
+    /// Odcisk tego, co IdGuard ocenia (nazwy + avatar) – zmiana = ponowny skan.
+    fn fingerprint(&self) -> u64 {
+        let mut h = DefaultHasher::new();
+        (&self.username, &self.display_name, &self.global_name, &self.avatar_url).hash(&mut h);
+        h.finish()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdgReport {
     pub score: u8,
@@ -186,8 +256,13 @@ pub struct IdGuard {
     cfg_cache: DashMap<u64, IdgConfig>,                 // per-guild
     nick_rules: DashMap<u64, Arc<RwLock<Vec<NickRule>>>>,
     avatar_deny: DashMap<u64, Arc<RwLock<Vec<AvatarDenyHash>>>>,
+    /// Ostatni skan (gid, uid) -> (odcisk tożsamości, raport); bez zmian = bez ponownego logu.
+    screened: DashMap<(u64, u64), (u64, IdgReport)>,
 }
 
+/// Górna granica pamięci ostatnich skanów (po przekroczeniu czyścimy całość).
+const SCREENED_MAX: usize = 50_000;
+
 /* ===========================
    Globalny HTTP client + throttle + DDL once
    =========================== */
@@ -215,12 +290,20 @@ fn img_sem() -> &'static Semaphore {
    =========================== */
 
 impl IdGuard {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &["idguard", "teach"],
+        components: &["idg_"],
+        modals: &[],
+    };
+
     pub fn new(ctx: Arc<AppContext>) -> Arc<Self> {
         Arc::new(Self {
             ctx,
             cfg_cache: DashMap::new(),
             nick_rules: DashMap::new(),
             avatar_deny: DashMap::new(),
+            screened: DashMap::new(),
         })
     }
 
@@ -266,111 +349,142 @@ impl IdGuard {
         }
     }
 
-    pub async fn register_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-        guild_id
-            .create_command(
-                &ctx.http,
-                CreateCommand::new("idguard")
-                    .description("Zarządzanie IdGuard")
-                    .add_option(
-                        CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "setup",
-                            "Utwórz logi i włącz monitor mode",
-                        ),
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new("idguard")
+                .description("Zarządzanie IdGuard")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "setup",
+                        "Utwórz logi i włącz monitor mode",
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "preset",
+                        "Ustaw predefiniowane progi/wagi",
                     )
-                    .add_option(
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "preset",
-                            "Ustaw predefiniowane progi/wagi",
+                            CommandOptionType::String,
+                            "value",
+                            "lenient|balanced|strict",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::String,
-                                "value",
-                                "lenient|balanced|strict",
-                            )
-                            .required(true)
-                            .add_string_choice("lenient", "lenient")
-                            .add_string_choice("balanced", "balanced")
-                            .add_string_choice("strict", "strict"),
-                        ),
+                        .required(true)
+                        .add_string_choice("lenient", "lenient")
+                        .add_string_choice("balanced", "balanced")
+                        .add_string_choice("strict", "strict"),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "mode",
+                        "Monitor czy Auto",
                     )
-                    .add_option(
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "mode",
-                            "Monitor czy Auto",
+                            CommandOptionType::String,
+                            "value",
+                            "monitor|auto",
                         )
-                        .add_sub_option(
-                            CreateCommandOption::new(
-                                CommandOptionType::String,
-                                "value",
-                                "monitor|auto",
-                            )
-                            .required(true)
-                            .add_string_choice("monitor", "monitor")
-                            .add_string_choice("auto", "auto"),
-                        ),
+                        .required(true)
+                        .add_string_choice("monitor", "monitor")
+                        .add_string_choice("auto", "auto"),
                     ),
-            )
-            .await?;
-
-        // /teach allow|deny [nick?] [avatar?] [reason?]
-        guild_id
-            .create_command(
-                &ctx.http,
-                CreateCommand::new("teach")
-                    .description("Ucz IdGuard (allow/deny nick lub avatar)")
-                    .add_option(
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "action",
+                        "Akcja trybu Auto dla BLOCK",
+                    )
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "allow",
-                            "Zezwól (whitelist)",
-                        )
-                        .add_sub_option(CreateCommandOption::new(
                             CommandOptionType::String,
-                            "nick",
-                            "Tekst nicku (token lub regex:/.../flags)",
-                        ))
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "avatar",
-                            "URL avatara",
-                        ))
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "reason",
-                            "Powód (opcjonalnie)",
-                        )),
+                            "value",
+                            "rename|quarantine|kick",
+                        )
+                        .required(true)
+                        .add_string_choice("rename", "rename")
+                        .add_string_choice("quarantine", "quarantine")
+                        .add_string_choice("kick", "kick"),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "nsfw",
+                        "Próg pewności heurystyki NSFW avatara",
                     )
-                    .add_option(
+                    .add_sub_option(
                         CreateCommandOption::new(
-                            CommandOptionType::SubCommand,
-                            "deny",
-                            "Zablokuj (blacklist)",
+                            CommandOptionType::Integer,
+                            "value",
+                            "Próg w % (1–100, domyślnie 80)",
                         )
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "nick",
-                            "Tekst nicku (token lub regex:/.../flags)",
-                        ))
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "avatar",
-                            "URL avatara",
-                        ))
-                        .add_sub_option(CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "reason",
-                            "Powód (opcjonalnie)",
-                        )),
+                        .required(true)
+                        .min_int_value(1)
+                        .max_int_value(100),
                     ),
-            )
-            .await?;
-
-        Ok(())
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "queue",
+                        "Kolejka przeglądu (WATCH w trybie Auto)",
+                    ),
+                ),
+            // /teach allow|deny [nick?] [avatar?] [reason?]
+            CreateCommand::new("teach")
+                .description("Ucz IdGuard (allow/deny nick lub avatar)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "allow",
+                        "Zezwól (whitelist)",
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "nick",
+                        "Tekst nicku (token lub regex:/.../flags)",
+                    ))
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "avatar",
+                        "URL avatara",
+                    ))
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "reason",
+                        "Powód (opcjonalnie)",
+                    )),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "deny",
+                        "Zablokuj (blacklist)",
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "nick",
+                        "Tekst nicku (token lub regex:/.../flags)",
+                    ))
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "avatar",
+                        "URL avatara",
+                    ))
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "reason",
+                        "Powód (opcjonalnie)",
+                    )),
+                ),
+        ]
     }
 
     /// Główna brama interakcji (slash + przyciski).
@@ -411,6 +525,17 @@ impl IdGuard {
                 self.on_btn_allow_deny_avatar(ctx, app, &comp, false).await;
                 return;
             }
+            if comp.data.custom_id.starts_with("idg_undo:") {
+                self.on_btn_undo(ctx, &comp).await;
+                return;
+            }
+            if comp.data.custom_id.starts_with("idg_rv_ok:") {
+                self.on_btn_review(ctx, &comp, false).await;
+                return;
+            }
+            if comp.data.custom_id.starts_with("idg_rv_act:") {
+                self.on_btn_review(ctx, &comp, true).await;
+            }
         }
     }
 
@@ -418,6 +543,66 @@ impl IdGuard {
        Skan podczas weryfikacji / na żądanie
        =========================== */
 
+    /// Skan dla zdarzeń gildii (join, zmiana nicka/avatara, weryfikacja).
+    /// Watch/Block trafia do logu z przyciskami; niezmieniona tożsamość zwraca poprzedni
+    /// raport bez ponownego skanu i logu.
+    pub async fn screen(&self, ctx: &Context, input: &IdgInput) -> IdgReport {
+        let key = (input.guild_id, input.user_id);
+        let fp = input.fingerprint();
+        if let Some(prev) = self.screened.get(&key)
+            && prev.0 == fp
+        {
+            return prev.1.clone();
+        }
+
+        let report = self.check_user(input).await;
+        if report.verdict != IdgVerdict::Clean {
+            tracing::info!(
+                gid = input.guild_id,
+                uid = input.user_id,
+                verdict = ?report.verdict,
+                score = report.score,
+                explain = %report.explain,
+                "IdGuard flagged identity"
+            );
+            if report.verdict == IdgVerdict::Block && self.already_enforced(input).await {
+                // rename z Auto zmienia odcisk – bez tego BLOCK egzekwowałby się w kółko
+                tracing::debug!(gid = input.guild_id, uid = input.user_id, "idguard block already enforced");
+            } else {
+                let auto = self.auto_enforce(ctx, input, &report).await;
+                self.send_review(ctx, input, &report, auto.as_ref()).await;
+            }
+        }
+
+        if self.screened.len() >= SCREENED_MAX {
+            self.screened.clear();
+        }
+        self.screened.insert(key, (fp, report.clone()));
+        report
+    }
+
+    /// Staff cofnął akcję Auto albo zatwierdził użytkownika z kolejki – Auto go pomija,
+    /// a BLOCK nie wstrzymuje już weryfikacji.
+    pub async fn is_exempt(&self, guild_id: u64, user_id: u64) -> bool {
+        match is_exempt_db(self.db(), guild_id, user_id).await {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::warn!(error=?e, guild_id, user_id, "idguard exemption lookup failed");
+                false
+            }
+        }
+    }
+
+    /// Zapomnij wynik skanu (np. użytkownik opuścił serwer).
+    pub fn forget(&self, guild_id: u64, user_id: u64) {
+        self.screened.remove(&(guild_id, user_id));
+    }
+
+    /// Reguły/konfig gildii się zmieniły – poprzednie werdykty są nieaktualne.
+    fn invalidate_screens(&self, guild_id: u64) {
+        self.screened.retain(|(g, _), _| *g != guild_id);
+    }
+
     pub async fn check_user(&self, input: &IdgInput) -> IdgReport {
         let mut cfg = self.cfg_cache.get(&input.guild_id).map(|e| e.clone()).unwrap_or_default();
         cfg = sanitize_cfg(cfg);
@@ -449,14 +634,9 @@ impl IdGuard {
             let guard = rules.read().await;
 
             // ALLOW -> pomijamy DENY/builtin dla nicka (ale nie dokładaj ujemnych punktów)
-            let allowed_by_rule = guard
-                .iter()
-                .filter(|r| r.action == RuleAction::Allow)
-                .any(|r| r.matches(&norm_joined, &lowered));
-
-            if !allowed_by_rule {
+            if let Some(hits) = deny_hits(&guard, &norm_joined, &lowered) {
                 // Zbierz WSZYSTKIE dopasowane DENY i zsumuj
-                for hit in guard.iter().filter(|r| r.action == RuleAction::Deny).filter(|r| r.matches(&norm_joined, &lowered)) {
+                for hit in hits {
                     let w = match hit.kind {
                         RuleKind::Regex => cfg.weights.nick_regex,
                         RuleKind::Token => cfg.weights.nick_token,
@@ -468,21 +648,31 @@ impl IdGuard {
                     });
                 }
 
-                // Wbudowane tokens – jeden prekompilowany regex, zwraca faktyczny match
-                if let Some(m) = BUILTIN_BAD_RE.find(&lowered) {
+                // Wbudowane tokeny – jeden prekompilowany regex + ręczna granica słowa
+                if let Some(m) = find_builtin_bad(&lowered) {
                     signals.push(IdgSignal {
                         kind: IdgSignalKind::NickToken,
                         weight: cfg.weights.nick_token,
-                        detail: format!("builtin: {}", m.as_str()),
+                        detail: format!("builtin: {}", m),
                     });
                 }
             }
         }
 
-        // 2) AVATAR – hash deny + (stub) OCR + (stub) NSFW
+        // 2) AVATAR – jedno pobranie: hash deny + OCR + NSFW
         let mut avatar_hash: Option<u64> = None;
         if let Some(url) = &input.avatar_url {
-            if let Ok(Some(h)) = fetch_and_ahash(url).await {
+            let scan = match fetch_avatar_bytes(url).await {
+                Some(bytes) => {
+                    // analiza (dekodowanie, OCR, NSFW) w tym samym limicie co pobrania
+                    let _permit = img_sem().acquire().await.ok();
+                    tokio::task::spawn_blocking(move || scan_avatar(&bytes)).await.ok().flatten()
+                }
+                None => None,
+            };
+            let scan = scan.unwrap_or_default();
+
+            if let Some(h) = scan.hash {
                 avatar_hash = Some(h);
                 let deny = self
                     .avatar_deny
@@ -509,16 +699,39 @@ impl IdGuard {
                 }
             }
 
-            // OCR/NSFW stuby (utrzymane jako placeholdery)
-            if let Some(_txt) = ocr_from_url_stub(url).await {
-                // opcjonalnie: dodać heurystyki na podstawie OCR
+            // OCR – tekst z avatara przez te same reguły co nick (jeden sygnał na avatar)
+            if let Some(text) = &scan.ocr_text {
+                let lowered = text.to_lowercase();
+                let guard = rules.read().await;
+                if let Some(hits) = deny_hits(&guard, text, &lowered) {
+                    let mut matched: Vec<String> = hits.iter().map(|r| r.pattern.clone()).collect();
+                    if let Some(m) = find_builtin_bad(&lowered) {
+                        matched.push(format!("builtin {m}"));
+                    }
+                    if !matched.is_empty() {
+                        signals.push(IdgSignal {
+                            kind: IdgSignalKind::AvatarOCR,
+                            weight: cfg.weights.avatar_ocr,
+                            detail: format!("ocr „{}”: {}", clamp_chars(text, 60), matched.join(", ")),
+                        });
+                    }
+                }
             }
-            if let Some(true) = nsfw_from_url_stub(url).await {
-                signals.push(IdgSignal {
-                    kind: IdgSignalKind::AvatarNSFW,
-                    weight: cfg.weights.avatar_nsfw,
-                    detail: "nsfw detector (stub)".into(),
-                });
+
+            // NSFW – heurystyka skóry (avatar_nsfw.rs), próg per gildia
+            if let Some(nsfw) = scan.nsfw {
+                let pct = (nsfw.confidence * 100.0).round() as u8;
+                if pct >= cfg.nsfw_threshold {
+                    signals.push(IdgSignal {
+                        kind: IdgSignalKind::AvatarNSFW,
+                        weight: cfg.weights.avatar_nsfw,
+                        detail: format!(
+                            "nsfw {pct}% (próg {}%, skóra {:.0}%)",
+                            cfg.nsfw_threshold,
+                            nsfw.skin_ratio * 100.0
+                        ),
+                    });
+                }
             }
         }
 
@@ -562,16 +775,14 @@ impl IdGuard {
         IdgReport { score: score_u8, verdict, signals: s_sorted, explain, avatar_hash }
     }
 
-    /// Log (embed) do kanału logów (LOGS_ALTGUARD / env_channels::logs::altguard_id).
-    pub async fn log_review_embed(
+    async fn send_review(
         &self,
         ctx: &Context,
-        app: &AppContext,
         input: &IdgInput,
         report: &IdgReport,
+        auto: Option<&AutoOutcome>,
     ) {
-        let env = app.env();
-        let log_id = env_channels::logs::altguard_id(&env);
+        let log_id = env_channels::logs::altguard_id(input.guild_id);
         if log_id == 0 { return; }
 
         let user_mention = format!("<@{}>", input.user_id);
@@ -593,9 +804,20 @@ impl IdGuard {
         };
         signals = clamp_chars(&signals, 1024); // pole embeda ma limit 1024 znaków
 
-        // Przygotuj przyciski: tokeny z nicka (max 4) + avatar (jeśli jest)
+        // Przygotuj przyciski: Auto (cofnij / kolejka) + tokeny z nicka (max 4) + avatar (jeśli jest)
         let mut buttons: Vec<CreateButton> = Vec::new();
 
+        if let Some(a) = auto
+            && let Some(id) = a.id
+        {
+            if a.action == ACTION_REVIEW {
+                buttons.push(CreateButton::new(format!("idg_rv_ok:{id}")).label("Zatwierdź").style(ButtonStyle::Success));
+                buttons.push(CreateButton::new(format!("idg_rv_act:{id}")).label("Zastosuj akcję").style(ButtonStyle::Danger));
+            } else {
+                buttons.push(CreateButton::new(format!("idg_undo:{id}")).label("↩️ Cofnij akcję").style(ButtonStyle::Primary));
+            }
+        }
+
         if let Some(nick_preview) = input.username.as_ref().or(input.display_name.as_ref()).or(input.global_name.as_ref()) {
             let tokens = tokens_for_buttons(nick_preview);
             let mut tokens_added = 0usize;
@@ -633,6 +855,9 @@ impl IdGuard {
             .footer(CreateEmbedFooter::new(BRAND_FOOTER))
             .colour(serenity::all::Colour::new(colour));
 
+        if let Some(a) = auto {
+            embed = embed.field("Tryb Auto", clamp_chars(&a.note, 1024), false);
+        }
         if let Some(url) = &input.avatar_url {
             embed = embed.thumbnail(url.clone());
         }
@@ -649,13 +874,10 @@ impl IdGuard {
    =========================== */
 
 impl IdGuard {
-    async fn on_cmd_idguard(&self, ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    async fn on_cmd_idguard(&self, ctx: &Context, _app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
         maybe_ensure_tables(self.db()).await;
 
-        // wymóg: staff
-        let env = app.env();
-        let staff = env_roles::staff_set(&env);
-        let allowed = cmd.member.as_ref().map(|m| m.roles.iter().any(|r| staff.contains(&r.get()))).unwrap_or(false);
+        let allowed = cmd.guild_id.is_some_and(|g| permissions::authorize(g, cmd.member.as_deref(), Permission::Idguard));
         if !allowed {
             let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::new().content("Brak uprawnień.").ephemeral(true)
@@ -688,6 +910,7 @@ impl IdGuard {
                 if let Err(e) = save_cfg_db(idg.db(), gid.get(), &cfg).await {
                     tracing::warn!(?e, "save_cfg_db failed on setup");
                 }
+                idg.invalidate_screens(gid.get());
 
                 let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new()
@@ -725,6 +948,7 @@ impl IdGuard {
                 if let Err(e) = save_cfg_db(idg.db(), gid.get(), &cfg).await {
                     tracing::warn!(?e, "save_cfg_db failed on preset");
                 }
+                idg.invalidate_screens(gid.get());
 
                 let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new().content(format!("Preset ustawiony: **{:?}**.", preset)).ephemeral(true)
@@ -741,11 +965,73 @@ impl IdGuard {
                 if let Err(e) = save_cfg_db(idg.db(), gid.get(), &cfg).await {
                     tracing::warn!(?e, "save_cfg_db failed on mode");
                 }
+                idg.invalidate_screens(gid.get());
 
                 let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new().content(format!("Tryb: **{:?}**.", mode)).ephemeral(true)
                 )).await;
             }
+            Some("action") => {
+                let action = sval.as_deref().and_then(IdgAutoAction::parse).unwrap_or_default();
+                let idg = self;
+                let mut cfg = idg.cfg_cache.get(&gid.get()).map(|c| c.clone()).unwrap_or_default();
+                cfg.auto_action = action;
+                idg.cfg_cache.insert(gid.get(), cfg.clone());
+                if let Err(e) = save_cfg_db(idg.db(), gid.get(), &cfg).await {
+                    tracing::warn!(?e, "save_cfg_db failed on action");
+                }
+
+                let mut msg = format!("Akcja Auto dla BLOCK: **{}**.", action.label());
+                if action == IdgAutoAction::Quarantine && env_roles::quarantine_id(gid.get()) == 0 {
+                    msg.push_str("\n⚠️ Brak roli kwarantanny – ustaw `role.quarantine` przez /registry.");
+                }
+                if cfg.mode != IdgMode::Auto {
+                    msg.push_str("\nTryb to nadal **Monitor** – włącz `/idguard mode auto`.");
+                }
+                let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content(msg).ephemeral(true)
+                )).await;
+            }
+            Some("nsfw") => {
+                let threshold = sval.as_deref().and_then(|v| v.parse::<u8>().ok()).unwrap_or_else(default_nsfw_threshold);
+                let idg = self;
+                let mut cfg = idg.cfg_cache.get(&gid.get()).map(|c| c.clone()).unwrap_or_default();
+                cfg.nsfw_threshold = threshold;
+                cfg = sanitize_cfg(cfg);
+                idg.cfg_cache.insert(gid.get(), cfg.clone());
+                if let Err(e) = save_cfg_db(idg.db(), gid.get(), &cfg).await {
+                    tracing::warn!(?e, "save_cfg_db failed on nsfw");
+                }
+                idg.invalidate_screens(gid.get());
+
+                let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(format!("Próg NSFW: **{}%** (waga sygnału: {}).", cfg.nsfw_threshold, cfg.weights.avatar_nsfw))
+                        .ephemeral(true)
+                )).await;
+            }
+            Some("queue") => {
+                let rows = list_open_reviews(self.db(), gid.get(), 15).await.unwrap_or_else(|e| {
+                    tracing::warn!(?e, "list_open_reviews failed");
+                    Vec::new()
+                });
+                let desc = if rows.is_empty() {
+                    "Kolejka jest pusta.".to_string()
+                } else {
+                    rows.iter()
+                        .map(|r| format!("`#{}` <@{}> – score **{}** • <t:{}:R>", r.id, r.user_id, r.score, r.created_unix))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                let embed = CreateEmbed::new()
+                    .title("IdGuard: kolejka przeglądu")
+                    .description(desc)
+                    .footer(CreateEmbedFooter::new(BRAND_FOOTER))
+                    .colour(serenity::all::Colour::new(0xf1c40f));
+                let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().embed(embed).ephemeral(true)
+                )).await;
+            }
             _ => {
                 let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new().content("Nieznana subkomenda.").ephemeral(true)
@@ -756,13 +1042,10 @@ impl IdGuard {
         Ok(())
     }
 
-    async fn on_cmd_teach(&self, ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    async fn on_cmd_teach(&self, ctx: &Context, _app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
         maybe_ensure_tables(self.db()).await;
 
-        // ACL
-        let env = app.env();
-        let staff = env_roles::staff_set(&env);
-        let allowed = cmd.member.as_ref().map(|m| m.roles.iter().any(|r| staff.contains(&r.get()))).unwrap_or(false);
+        let allowed = cmd.guild_id.is_some_and(|g| permissions::authorize(g, cmd.member.as_deref(), Permission::Teach));
         if !allowed {
             let _ = cmd.create_response(&ctx.http, CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::new().content("Brak uprawnień.").ephemeral(true)
@@ -809,6 +1092,7 @@ impl IdGuard {
             if let Err(e) = upsert_nick_rule(self.db(), gid.get(), &rule).await {
                 tracing::warn!(?e, "upsert_nick_rule failed");
             }
+            self.invalidate_screens(gid.get());
 
             let list = self.nick_rules
                 .entry(gid.get())
@@ -834,6 +1118,7 @@ impl IdGuard {
                 if let Err(e) = upsert_avatar_hash_deny_allow(self.db(), gid.get(), h, action, &reason).await {
                     tracing::warn!(?e, "upsert_avatar_hash_deny_allow failed");
                 }
+                self.invalidate_screens(gid.get());
 
                 // pamięć: DENY dodajemy, ALLOW zdejmujemy
                 if action == RuleAction::Deny {
@@ -874,9 +1159,8 @@ impl IdGuard {
         Ok(())
     }
 
-    async fn on_btn_allow_deny_nick(&self, ctx: &Context, app: &AppContext, i: &ComponentInteraction, allow: bool) {
-        let env = app.env();
-        if !ensure_staff_ephemeral(ctx, &env, i).await { return; }
+    async fn on_btn_allow_deny_nick(&self, ctx: &Context, _app: &AppContext, i: &ComponentInteraction, allow: bool) {
+        if !ensure_staff_ephemeral(ctx, i).await { return; }
 
         let _ = i.create_response(&ctx.http, CreateInteractionResponse::Message(
             CreateInteractionResponseMessage::new().content(if allow { "Zapisuję allow…" } else { "Zapisuję deny…" }).ephemeral(true)
@@ -900,6 +1184,7 @@ impl IdGuard {
         if let Err(e) = upsert_nick_rule(self.db(), gid.get(), &rule).await {
             tracing::warn!(?e, "upsert_nick_rule failed from button");
         }
+        self.invalidate_screens(gid.get());
 
         let list = self.nick_rules
             .entry(gid.get())
@@ -916,9 +1201,8 @@ impl IdGuard {
         )).await;
     }
 
-    async fn on_btn_allow_deny_avatar(&self, ctx: &Context, app: &AppContext, i: &ComponentInteraction, allow: bool) {
-        let env = app.env();
-        if !ensure_staff_ephemeral(ctx, &env, i).await { return; }
+    async fn on_btn_allow_deny_avatar(&self, ctx: &Context, _app: &AppContext, i: &ComponentInteraction, allow: bool) {
+        if !ensure_staff_ephemeral(ctx, i).await { return; }
 
         let _ = i.create_response(&ctx.http, CreateInteractionResponse::Message(
             CreateInteractionResponseMessage::new().content(if allow { "Zapisuję allow…" } else { "Zapisuję deny…" }).ephemeral(true)
@@ -936,6 +1220,7 @@ impl IdGuard {
         if let Err(e) = upsert_avatar_hash_deny_allow(self.db(), gid.get(), h, action, "button").await {
             tracing::warn!(?e, "upsert_avatar_hash_deny_allow failed from button");
         }
+        self.invalidate_screens(gid.get());
 
         if allow {
             if let Some(av) = self.avatar_deny.get(&gid.get()) {
@@ -959,29 +1244,330 @@ impl IdGuard {
 }
 
 /* ===========================
-   Implementacje pomocnicze
+   Tryb Auto: akcje + kolejka przeglądu
    =========================== */
 
-// Cache regexów dla tokenów (granice słów Unicode)
-static TOKEN_RE_CACHE: Lazy<DashMap<String, Regex>> = Lazy::new(|| DashMap::new());
+const ACTION_REVIEW: &str = "review";
+
+/// Co zrobił tryb Auto – trafia do embeda logu; `id` = wpis w tss.idg_actions (przyciski).
+struct AutoOutcome {
+    id: Option<i64>,
+    action: &'static str,
+    note: String,
+}
 
-// Wbudowane „złe” słowa – pojedynczy prekompilowany regex (granice słów Unicode)
+/// Wynik wykonania akcji na członku (do zapisu, żeby dało się ją cofnąć).
+struct Applied {
+    note: String,
+    previous_nick: Option<String>,
+    role_id: Option<u64>,
+}
+
+impl IdGuard {
+    /// Auto już zadziałało: otwarta akcja rename/kwarantanny albo nick już zresetowany.
+    /// Kick nie blokuje – po powrocie użytkownik jest oceniany od nowa.
+    async fn already_enforced(&self, input: &IdgInput) -> bool {
+        let mode = self.cfg_cache.get(&input.guild_id).map(|c| c.mode).unwrap_or_default();
+        if mode != IdgMode::Auto {
+            return false;
+        }
+        if nick_is_sanitized(input.display_name.as_deref(), input.user_id) {
+            return true;
+        }
+        match open_action_id(self.db(), input.guild_id, input.user_id).await {
+            Ok(id) => id.is_some(),
+            Err(e) => {
+                tracing::warn!(error=?e, gid = input.guild_id, uid = input.user_id, "idguard open action lookup failed");
+                false
+            }
+        }
+    }
+
+    /// BLOCK -> skonfigurowana akcja, WATCH -> kolejka. Tylko tryb Auto; staff i osoby
+    /// zwolnione przez staff (cofnięta akcja / zatwierdzenie z kolejki) są pomijane.
+    async fn auto_enforce(&self, ctx: &Context, input: &IdgInput, report: &IdgReport) -> Option<AutoOutcome> {
+        let cfg = self.cfg_cache.get(&input.guild_id).map(|c| c.clone()).unwrap_or_default();
+        if cfg.mode != IdgMode::Auto || report.verdict == IdgVerdict::Clean {
+            return None;
+        }
+        let (gid, uid) = (input.guild_id, input.user_id);
+        if self.is_exempt(gid, uid).await {
+            return Some(AutoOutcome { id: None, action: ACTION_REVIEW, note: "Pominięto – użytkownik zwolniony przez staff.".into() });
+        }
+        if permissions::authorize_user(ctx, GuildId::new(gid), UserId::new(uid), Permission::Review).await {
+            return Some(AutoOutcome { id: None, action: ACTION_REVIEW, note: "Pominięto – członek staffu.".into() });
+        }
+
+        let signals = serde_json::to_value(&report.signals).unwrap_or_default();
+
+        if report.verdict == IdgVerdict::Watch {
+            // jedna otwarta pozycja kolejki na użytkownika
+            let id = match open_review_id(self.db(), gid, uid).await {
+                Ok(Some(id)) => Ok(id),
+                Ok(None) => insert_action(self.db(), &NewAction {
+                    guild_id: gid,
+                    user_id: uid,
+                    action: ACTION_REVIEW,
+                    score: report.score,
+                    signals: &signals,
+                    previous_nick: None,
+                    role_id: None,
+                }).await,
+                Err(e) => Err(e),
+            };
+            return Some(match id {
+                Ok(id) => AutoOutcome { id: Some(id), action: ACTION_REVIEW, note: format!("W kolejce przeglądu (`#{id}`, `/idguard queue`).") },
+                Err(e) => {
+                    tracing::warn!(error=?e, gid, uid, "idguard review enqueue failed");
+                    AutoOutcome { id: None, action: ACTION_REVIEW, note: "Nie udało się dodać do kolejki (błąd bazy).".into() }
+                }
+            });
+        }
+
+        let action = cfg.auto_action;
+        let audit = format!("IdGuard Auto: score {}/100", report.score);
+        let applied = match apply_action(ctx, gid, uid, action, input.display_name.clone(), &audit).await {
+            Ok(a) => a,
+            Err(e) => {
+                tracing::warn!(error=?e, gid, uid, action = action.as_str(), "idguard auto action failed");
+                return Some(AutoOutcome { id: None, action: action.as_str(), note: format!("{} – nie udało się: {e}", action.label()) });
+            }
+        };
+        tracing::info!(gid, uid, action = action.as_str(), score = report.score, "idguard auto action applied");
+
+        let id = insert_action(self.db(), &NewAction {
+            guild_id: gid,
+            user_id: uid,
+            action: action.as_str(),
+            score: report.score,
+            signals: &signals,
+            previous_nick: applied.previous_nick.as_deref(),
+            role_id: applied.role_id,
+        })
+        .await
+        .map_err(|e| tracing::warn!(error=?e, gid, uid, "idguard action insert failed"))
+        .ok();
+
+        Some(AutoOutcome { id, action: action.as_str(), note: applied.note })
+    }
+
+    async fn on_btn_undo(&self, ctx: &Context, i: &ComponentInteraction) {
+        if !ensure_staff_ephemeral(ctx, i).await { return; }
+        let _ = i.create_response(&ctx.http, CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new().content("Cofam akcję…").ephemeral(true)
+        )).await;
+
+        let Some(row) = self.load_button_row(ctx, i).await else { return; };
+        if row.action == ACTION_REVIEW {
+            let _ = i.edit_response(&ctx.http, EditInteractionResponse::new().content("To wpis kolejki – użyj „Zatwierdź”.")).await;
+            return;
+        }
+
+        let gid = GuildId::new(row.guild_id);
+        let uid = UserId::new(row.user_id);
+        let audit = format!("IdGuard: cofnięcie akcji #{} przez {}", row.id, i.user.id.get());
+        let undone: Result<&str> = match row.action.as_str() {
+            "rename" => {
+                let nick = row.previous_nick.clone().unwrap_or_default(); // "" = zdejmij nick
+                gid.edit_member(&ctx.http, uid, EditMember::new().nickname(nick).audit_log_reason(&audit))
+                    .await
+                    .map(|_| "Przywrócono poprzedni nick.")
+                    .map_err(Into::into)
+            }
+            "quarantine" => match row.role_id {
+                Some(r) => ctx.http
+                    .remove_member_role(gid, uid, RoleId::new(r), Some(&audit))
+                    .await
+                    .map(|_| "Zdjęto rolę kwarantanny.")
+                    .map_err(Into::into),
+                None => Ok("Brak zapisanej roli – nic do zdjęcia."),
+            },
+            // kicka nie da się odwrócić po stronie Discorda – zwalniamy z Auto, może wrócić
+            "kick" => Ok("Użytkownik może wrócić – IdGuard Auto nie wyrzuci go ponownie."),
+            _ => Ok("—"),
+        };
+
+        let msg = match undone {
+            Ok(note) => {
+                if let Err(e) = resolve_action(self.db(), row.id, i.user.id.get(), "reverted").await {
+                    tracing::warn!(error=?e, id = row.id, "idguard resolve_action failed");
+                }
+                tracing::info!(id = row.id, by = i.user.id.get(), action = %row.action, "idguard auto action reverted");
+                format!("↩️ Cofnięto `#{}` dla <@{}>. {note}", row.id, row.user_id)
+            }
+            Err(e) => format!("Nie udało się cofnąć `#{}`: {e}", row.id),
+        };
+        let _ = i.edit_response(&ctx.http, EditInteractionResponse::new().content(msg)).await;
+    }
+
+    /// Kolejka: „Zatwierdź” (zwolnij z Auto) albo „Zastosuj akcję” (skonfigurowana akcja BLOCK).
+    async fn on_btn_review(&self, ctx: &Context, i: &ComponentInteraction, act: bool) {
+        if !ensure_staff_ephemeral(ctx, i).await { return; }
+        let _ = i.create_response(&ctx.http, CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new().content("Zapisuję…").ephemeral(true)
+        )).await;
+
+        let Some(row) = self.load_button_row(ctx, i).await else { return; };
+        if row.action != ACTION_REVIEW {
+            let _ = i.edit_response(&ctx.http, EditInteractionResponse::new().content("To nie jest wpis kolejki.")).await;
+            return;
+        }
+
+        if !act {
+            let msg = match resolve_action(self.db(), row.id, i.user.id.get(), "dismissed").await {
+                Ok(_) => format!("✅ <@{}> zatwierdzony (`#{}`) – IdGuard Auto go pominie.", row.user_id, row.id),
+                Err(e) => format!("Błąd zapisu: {e}"),
+            };
+            let _ = i.edit_response(&ctx.http, EditInteractionResponse::new().content(msg)).await;
+            return;
+        }
+
+        let cfg = self.cfg_cache.get(&row.guild_id).map(|c| c.clone()).unwrap_or_default();
+        let action = cfg.auto_action;
+        let previous_nick = GuildId::new(row.guild_id)
+            .member(&ctx.http, UserId::new(row.user_id))
+            .await
+            .ok()
+            .and_then(|m| m.nick);
+        let audit = format!("IdGuard: akcja z kolejki #{} przez {}", row.id, i.user.id.get());
+
+        let applied = match apply_action(ctx, row.guild_id, row.user_id, action, previous_nick, &audit).await {
+            Ok(a) => a,
+            Err(e) => {
+                let msg = format!("{} – nie udało się: {e}", action.label());
+                let _ = i.edit_response(&ctx.http, EditInteractionResponse::new().content(msg)).await;
+                return;
+            }
+        };
+        if let Err(e) = resolve_action(self.db(), row.id, i.user.id.get(), "actioned").await {
+            tracing::warn!(error=?e, id = row.id, "idguard resolve_action failed");
+        }
+        let new_id = insert_action(self.db(), &NewAction {
+            guild_id: row.guild_id,
+            user_id: row.user_id,
+            action: action.as_str(),
+            score: row.score,
+            signals: &row.signals,
+            previous_nick: applied.previous_nick.as_deref(),
+            role_id: applied.role_id,
+        })
+        .await;
+
+        // nowy wpis akcji = osobny przycisk cofnięcia (kolejka jest już zamknięta)
+        let mut edit = EditInteractionResponse::new().content(format!("<@{}>: {}", row.user_id, applied.note));
+        match new_id {
+            Ok(id) => {
+                edit = edit.components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new(format!("idg_undo:{id}")).label("↩️ Cofnij akcję").style(ButtonStyle::Primary),
+                ])]);
+            }
+            Err(e) => tracing::warn!(error=?e, id = row.id, "idguard action insert failed"),
+        }
+        let _ = i.edit_response(&ctx.http, edit).await;
+    }
+
+    /// Wpis z custom_id `idg_*:<id>` – tylko otwarty i z tej gildii.
+    async fn load_button_row(&self, ctx: &Context, i: &ComponentInteraction) -> Option<ActionRow> {
+        let id = i.data.custom_id.split_once(':').and_then(|(_, v)| v.parse::<i64>().ok());
+        let row = match id {
+            Some(id) => load_action(self.db(), id).await.unwrap_or_else(|e| {
+                tracing::warn!(error=?e, id, "idguard load_action failed");
+                None
+            }),
+            None => None,
+        };
+        let msg = match &row {
+            None => "Nie znaleziono wpisu.",
+            Some(r) if Some(r.guild_id) != i.guild_id.map(|g| g.get()) => "Wpis z innej gildii.",
+            Some(r) if r.resolved => "Ten wpis jest już zamknięty.",
+            Some(_) => return row,
+        };
+        let _ = i.edit_response(&ctx.http, EditInteractionResponse::new().content(msg)).await;
+        None
+    }
+}
+
+/// Neutralny nick po resecie (bez śladu oryginału).
+fn sanitized_nick(user_id: u64) -> String {
+    format!("user-{:04}", user_id % 10_000)
+}
+
+fn nick_is_sanitized(nick: Option<&str>, user_id: u64) -> bool {
+    nick == Some(sanitized_nick(user_id).as_str())
+}
+
+async fn apply_action(
+    ctx: &Context,
+    guild_id: u64,
+    user_id: u64,
+    action: IdgAutoAction,
+    previous_nick: Option<String>,
+    audit: &str,
+) -> Result<Applied> {
+    let gid = GuildId::new(guild_id);
+    let uid = UserId::new(user_id);
+    match action {
+        IdgAutoAction::Rename => {
+            let nick = sanitized_nick(user_id);
+            gid.edit_member(&ctx.http, uid, EditMember::new().nickname(nick.clone()).audit_log_reason(audit))
+                .await?;
+            Ok(Applied { note: format!("{} → `{nick}`", action.label()), previous_nick, role_id: None })
+        }
+        IdgAutoAction::Quarantine => {
+            let role = env_roles::quarantine_id(guild_id);
+            if role == 0 {
+                anyhow::bail!("brak roli `role.quarantine` w /registry");
+            }
+            ctx.http.add_member_role(gid, uid, RoleId::new(role), Some(audit)).await?;
+            Ok(Applied { note: format!("{} (<@&{role}>)", action.label()), previous_nick: None, role_id: Some(role) })
+        }
+        IdgAutoAction::Kick => {
+            gid.kick_with_reason(&ctx.http, uid, audit).await?;
+            Ok(Applied { note: action.label().to_string(), previous_nick: None, role_id: None })
+        }
+    }
+}
+
+/* ===========================
+   Implementacje pomocnicze
+   =========================== */
+
+// Wbudowane „złe” słowa – jeden prekompilowany regex; granice słów sprawdzamy ręcznie,
+// bo crate `regex` nie obsługuje look-around.
 static BUILTIN_BAD_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(
-        r"(?u)(?<!\p{Alnum})(hitler|nazi|swast|heil|adolf|kkk|nsfw|porn|sex|cum|kurwa|jebac|huj|chuj|pierdole|spierdalaj)(?!\p{Alnum})"
-    ).unwrap()
+    Regex::new(r"hitler|nazi|swast|heil|adolf|kkk|nsfw|porn|sex|cum|kurwa|jebac|huj|chuj|pierdole|spierdalaj").unwrap()
 });
 
-fn contains_token_cached(haystack_lower: &str, needle_lower: &str) -> bool {
-    let pat = format!(r"(?u)(?<!\p{{Alnum}}){}(?!\p{{Alnum}})", regex::escape(needle_lower));
-    let re = if let Some(r) = TOKEN_RE_CACHE.get(&pat) {
-        r.clone()
-    } else {
-        let compiled = Regex::new(&pat).unwrap();
-        TOKEN_RE_CACHE.insert(pat.clone(), compiled);
-        TOKEN_RE_CACHE.get(&pat).unwrap().clone()
-    };
-    re.is_match(haystack_lower)
+/// Dopasowanie [start, end) nie sąsiaduje z literą/cyfrą (granica słowa Unicode).
+fn is_word_bounded(hay: &str, start: usize, end: usize) -> bool {
+    let before = hay[..start].chars().next_back();
+    let after = hay[end..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+}
+
+/// Pierwsze wbudowane „złe” słowo występujące jako osobny token.
+fn find_builtin_bad(haystack_lower: &str) -> Option<&str> {
+    BUILTIN_BAD_RE
+        .find_iter(haystack_lower)
+        .find(|m| is_word_bounded(haystack_lower, m.start(), m.end()))
+        .map(|m| m.as_str())
+}
+
+/// None = tekst przepuszczony przez ALLOW; inaczej wszystkie pasujące DENY.
+fn deny_hits<'a>(rules: &'a [NickRule], text_raw: &str, text_lower: &str) -> Option<Vec<&'a NickRule>> {
+    if rules.iter().any(|r| r.action == RuleAction::Allow && r.matches(text_raw, text_lower)) {
+        return None;
+    }
+    Some(rules.iter().filter(|r| r.action == RuleAction::Deny && r.matches(text_raw, text_lower)).collect())
+}
+
+fn contains_token(haystack_lower: &str, needle_lower: &str) -> bool {
+    if needle_lower.is_empty() {
+        return false;
+    }
+    haystack_lower
+        .match_indices(needle_lower)
+        .any(|(start, m)| is_word_bounded(haystack_lower, start, start + m.len()))
 }
 
 impl NickRule {
@@ -1008,7 +1594,7 @@ impl NickRule {
         match self.kind {
             RuleKind::Token => {
                 let pat = self.pattern_lower.as_ref().map(|s| s.as_str()).unwrap_or("");
-                contains_token_cached(text_lower, pat)
+                contains_token(text_lower, pat)
             }
             RuleKind::Regex => self.compiled.as_ref().map(|r| r.is_match(text_raw)).unwrap_or(false),
         }
@@ -1129,25 +1715,31 @@ fn host_is_discord_cdn(url: &str) -> bool {
 }
 
 async fn fetch_and_ahash(url: &str) -> Result<Option<u64>> {
+    let Some(bytes) = fetch_avatar_bytes(url).await else { return Ok(None) };
+    ahash_from_bytes(&bytes)
+}
+
+/// Pobierz avatar (tylko Discord CDN, limit rozmiaru, throttle). None = nie da się/nie wolno.
+async fn fetch_avatar_bytes(url: &str) -> Option<Vec<u8>> {
     const MAX_IMAGE_BYTES: u64 = 1_500_000;
 
     if !host_is_discord_cdn(url) {
-        return Ok(None);
+        return None;
     }
     let _permit = img_sem().acquire().await.ok(); // delikatny throttle
 
     let resp = match http().get(url).send().await {
         Ok(r) => r,
-        Err(_) => return Ok(None),
+        Err(_) => return None,
     };
 
     // Sprawdź finalny URL po ewentualnych redirectach (ochrona whitelisty)
     if !host_is_discord_cdn(resp.url().as_str()) {
-        return Ok(None);
+        return None;
     }
 
     if !resp.status().is_success() {
-        return Ok(None);
+        return None;
     }
 
     // Nie trzymaj &str do nagłówka poza tym blokiem
@@ -1159,26 +1751,26 @@ async fn fetch_and_ahash(url: &str) -> Result<Option<u64>> {
         matches!(ct_opt, Some(v) if CT_IMAGE_RE.is_match(v))
     };
     if !is_image {
-        return Ok(None);
+        return None;
     }
 
     // content-length limit
     if let Some(len) = resp.content_length() {
         if len > MAX_IMAGE_BYTES {
-            return Ok(None);
+            return None;
         }
     }
 
     // Teraz możemy skonsumować response
     let bytes = match resp.bytes().await {
         Ok(b) => b,
-        Err(_) => return Ok(None),
+        Err(_) => return None,
     };
     if (bytes.len() as u64) > MAX_IMAGE_BYTES {
-        return Ok(None);
+        return None;
     }
 
-    Ok(ahash_from_bytes(&bytes).ok().flatten())
+    Some(bytes.to_vec())
 }
 
 fn ahash_from_bytes(bytes: &[u8]) -> Result<Option<u64>> {
@@ -1186,6 +1778,10 @@ fn ahash_from_bytes(bytes: &[u8]) -> Result<Option<u64>> {
         Ok(i) => i,
         Err(_) => return Ok(None),
     };
+    Ok(Some(ahash_from_image(&img)))
+}
+
+fn ahash_from_image(img: &image::DynamicImage) -> u64 {
     use image::imageops::FilterType;
     let gray = img.resize_exact(8, 8, FilterType::Triangle).to_luma8();
     let mut sum: u64 = 0;
@@ -1200,18 +1796,24 @@ fn ahash_from_bytes(bytes: &[u8]) -> Result<Option<u64>> {
     for (i, &v) in px.iter().enumerate() {
         if v > avg { bits |= 1u64 << i; }
     }
-    Ok(Some(bits))
+    bits
 }
 
-/* ===========================
-   Stuby OCR/NSFW (do podmiany)
-   =========================== */
-
-async fn ocr_from_url_stub(_url: &str) -> Option<String> {
-    None
+/// Wynik analizy pobranego avatara (CPU – wołane przez spawn_blocking).
+#[derive(Debug, Default)]
+struct AvatarScan {
+    hash: Option<u64>,
+    ocr_text: Option<String>,
+    nsfw: Option<NsfwScore>,
 }
-async fn nsfw_from_url_stub(_url: &str) -> Option<bool> {
-    None
+
+fn scan_avatar(bytes: &[u8]) -> Option<AvatarScan> {
+    let img = image::load_from_memory(bytes).ok()?;
+    Some(AvatarScan {
+        hash: Some(ahash_from_image(&img)),
+        ocr_text: avatar_ocr::read_text(&img),
+        nsfw: Some(avatar_nsfw::classify(&img)),
+    })
 }
 
 /* ===========================
@@ -1424,6 +2026,142 @@ async fn upsert_avatar_hash_deny_allow(
     Ok(())
 }
 
+struct NewAction<'a> {
+    guild_id: u64,
+    user_id: u64,
+    action: &'a str,
+    score: u8,
+    signals: &'a JsonValue,
+    previous_nick: Option<&'a str>,
+    role_id: Option<u64>,
+}
+
+struct ActionRow {
+    id: i64,
+    guild_id: u64,
+    user_id: u64,
+    action: String,
+    score: u8,
+    signals: JsonValue,
+    previous_nick: Option<String>,
+    role_id: Option<u64>,
+    resolved: bool,
+    created_unix: i64,
+}
+
+const ACTION_COLS: &str = "id, guild_id, user_id, action, score, signals, previous_nick, role_id, \
+     resolved_at IS NOT NULL AS resolved, EXTRACT(EPOCH FROM created_at)::BIGINT AS created_unix";
+
+fn action_row(r: &sqlx::postgres::PgRow) -> Result<ActionRow> {
+    Ok(ActionRow {
+        id: r.try_get("id")?,
+        guild_id: r.try_get::<i64, _>("guild_id")? as u64,
+        user_id: r.try_get::<i64, _>("user_id")? as u64,
+        action: r.try_get("action")?,
+        score: r.try_get::<i16, _>("score")?.clamp(0, 100) as u8,
+        signals: r.try_get("signals")?,
+        previous_nick: r.try_get("previous_nick")?,
+        role_id: r.try_get::<Option<i64>, _>("role_id")?.map(|v| v as u64),
+        resolved: r.try_get("resolved")?,
+        created_unix: r.try_get("created_unix")?,
+    })
+}
+
+async fn insert_action(db: &Pool<Postgres>, a: &NewAction<'_>) -> Result<i64> {
+    let id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO tss.idg_actions (guild_id, user_id, action, score, signals, previous_nick, role_id)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING id"#,
+    )
+    .bind(a.guild_id as i64)
+    .bind(a.user_id as i64)
+    .bind(a.action)
+    .bind(a.score as i16)
+    .bind(a.signals)
+    .bind(a.previous_nick)
+    .bind(a.role_id.map(|v| v as i64))
+    .fetch_one(db)
+    .await?;
+    Ok(id)
+}
+
+async fn load_action(db: &Pool<Postgres>, id: i64) -> Result<Option<ActionRow>> {
+    let row = sqlx::query(&format!("SELECT {ACTION_COLS} FROM tss.idg_actions WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+    row.as_ref().map(action_row).transpose()
+}
+
+/// Zamknij wpis (tylko otwarty – podwójne kliknięcie nic nie zmienia).
+async fn resolve_action(db: &Pool<Postgres>, id: i64, by: u64, resolution: &str) -> Result<bool> {
+    let res = sqlx::query(
+        r#"UPDATE tss.idg_actions
+           SET resolved_at = now(), resolved_by = $2, resolution = $3
+           WHERE id = $1 AND resolved_at IS NULL"#,
+    )
+    .bind(id)
+    .bind(by as i64)
+    .bind(resolution)
+    .execute(db)
+    .await?;
+    Ok(res.rows_affected() == 1)
+}
+
+async fn open_review_id(db: &Pool<Postgres>, guild_id: u64, user_id: u64) -> Result<Option<i64>> {
+    let id = sqlx::query_scalar(
+        r#"SELECT id FROM tss.idg_actions
+           WHERE guild_id = $1 AND user_id = $2 AND action = 'review' AND resolved_at IS NULL
+           ORDER BY id DESC LIMIT 1"#,
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .fetch_optional(db)
+    .await?;
+    Ok(id)
+}
+
+/// Otwarta (niecofnięta) akcja, która zostawia ślad na członku – rename albo kwarantanna.
+async fn open_action_id(db: &Pool<Postgres>, guild_id: u64, user_id: u64) -> Result<Option<i64>> {
+    let id = sqlx::query_scalar(
+        r#"SELECT id FROM tss.idg_actions
+           WHERE guild_id = $1 AND user_id = $2 AND action IN ('rename','quarantine') AND resolved_at IS NULL
+           ORDER BY id DESC LIMIT 1"#,
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .fetch_optional(db)
+    .await?;
+    Ok(id)
+}
+
+async fn list_open_reviews(db: &Pool<Postgres>, guild_id: u64, limit: i64) -> Result<Vec<ActionRow>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {ACTION_COLS} FROM tss.idg_actions
+         WHERE guild_id = $1 AND action = 'review' AND resolved_at IS NULL
+         ORDER BY created_at ASC LIMIT $2"
+    ))
+    .bind(guild_id as i64)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+    rows.iter().map(action_row).collect()
+}
+
+async fn is_exempt_db(db: &Pool<Postgres>, guild_id: u64, user_id: u64) -> Result<bool> {
+    let v: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (
+             SELECT 1 FROM tss.idg_actions
+             WHERE guild_id = $1 AND user_id = $2 AND resolution IN ('reverted','dismissed')
+           )"#,
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .fetch_one(db)
+    .await?;
+    Ok(v)
+}
+
 /* ===========================
    Parser opcji
    =========================== */
@@ -1435,8 +2173,10 @@ fn extract_idguard_sub(options: &[CommandDataOption]) -> (Option<String>, Option
             // szukamy parametru "value"
             for p in params {
                 if p.name == "value" {
-                    if let CommandDataOptionValue::String(s) = &p.value {
-                        return (Some(op.name.clone()), Some(s.clone()));
+                    match &p.value {
+                        CommandDataOptionValue::String(s) => return (Some(op.name.clone()), Some(s.clone())),
+                        CommandDataOptionValue::Integer(n) => return (Some(op.name.clone()), Some(n.to_string())),
+                        _ => {}
                     }
                 }
             }
@@ -1469,13 +2209,8 @@ fn extract_teach_params(options: &[CommandDataOption]) -> (Option<String>, HashM
    ACL helpers (staff)
    =========================== */
 
-fn is_staff_member_roles(env: &str, member_roles: &[serenity::all::RoleId]) -> bool {
-    let staff = env_roles::staff_set(env);
-    member_roles.iter().any(|r| staff.contains(&r.get()))
-}
-
-async fn ensure_staff_ephemeral(ctx: &Context, env: &str, i: &ComponentInteraction) -> bool {
-    let ok = i.member.as_ref().map(|m| is_staff_member_roles(env, &m.roles)).unwrap_or(false);
+async fn ensure_staff_ephemeral(ctx: &Context, i: &ComponentInteraction) -> bool {
+    let ok = i.guild_id.is_some_and(|g| permissions::authorize(g, i.member.as_ref(), Permission::Review));
     if !ok {
         let _ = i.create_response(
             &ctx.http,
@@ -1503,5 +2238,32 @@ fn sanitize_cfg(mut cfg: IdgConfig) -> IdgConfig {
     cfg.weights.avatar_hash = clamp_w(cfg.weights.avatar_hash);
     cfg.weights.avatar_ocr  = clamp_w(cfg.weights.avatar_ocr);
     cfg.weights.avatar_nsfw = clamp_w(cfg.weights.avatar_nsfw);
+
+    cfg.nsfw_threshold = cfg.nsfw_threshold.clamp(1, 100);
     cfg
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_respect_word_boundaries() {
+        assert!(contains_token("xx nazi xx", "nazi"));
+        assert!(contains_token("nazi_fan", "nazi"));
+        assert!(!contains_token("nazional", "nazi"));
+        assert!(!contains_token("żnazi", "nazi"));
+
+        assert_eq!(find_builtin_bad("sexy | sex"), Some("sex"));
+        assert_eq!(find_builtin_bad("document | essex"), None);
+        assert_eq!(find_builtin_bad("kurwa!"), Some("kurwa"));
+    }
+
+    #[test]
+    fn sanitized_nick_is_not_enforced_again() {
+        assert!(nick_is_sanitized(Some("user-5678"), 12_345_678));
+        assert!(!nick_is_sanitized(Some("user-5678"), 12_345_679));
+        assert!(!nick_is_sanitized(Some("nazi"), 12_345_678));
+        assert!(!nick_is_sanitized(None, 12_345_678));
+    }
+}
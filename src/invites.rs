@@ -0,0 +1,219 @@
+// src/invites.rs
+//
+// Śledzenie zaproszeń: cache użyć zaproszeń per gildia (odświeżany przy INVITE_CREATE/DELETE),
+// a przy wejściu członka – porównanie z aktualnym stanem, żeby ustalić użyte zaproszenie.
+// Wynik trafia do tss.member_joins (/user) i do AltGuarda (invite affinity).
+// Pobranie listy zaproszeń wymaga uprawnienia Manage Server – bez niego zaproszenie jest nieznane.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serenity::all::{Context, GuildId, InviteCreateEvent, InviteDeleteEvent};
+use sqlx::{Pool, Postgres, Row};
+use tokio::sync::Mutex;
+
+/// Jak długo po INVITE_DELETE zaproszenie bliskie limitu może jeszcze zostać uznane
+/// za użyte – wyczerpanie i wejście przychodzą niemal równocześnie.
+const DELETED_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+struct InviteSnap {
+    uses: u64,
+    max_uses: u64, // 0 = bez limitu
+    inviter_id: Option<u64>,
+    deleted_at: Option<Instant>, // INVITE_DELETE już przyszedł
+}
+
+impl InviteSnap {
+    /// Zniknęło z listy – mogło je wyczerpać właśnie to wejście.
+    fn maybe_exhausted(&self) -> bool {
+        self.max_uses > 0
+            && self.uses + 1 >= self.max_uses
+            && self.deleted_at.is_none_or(|t| t.elapsed() <= DELETED_GRACE)
+    }
+}
+
+/// Zaproszenie, którym ktoś wszedł.
+#[derive(Debug, Clone)]
+pub struct UsedInvite {
+    pub code: String,
+    pub inviter_id: Option<u64>,
+}
+
+/// Ostatnie wejście z tss.member_joins.
+#[derive(Debug, Clone)]
+pub struct JoinRecord {
+    pub invite_code: Option<String>,
+    pub inviter_id: Option<u64>,
+    pub joined_unix: i64,
+}
+
+#[derive(Debug, Default)]
+struct GuildInvites {
+    codes: HashMap<String, InviteSnap>, // code → stan
+    fetched_seq: u64,                   // numer pobrania, z którego pochodzi `codes`
+}
+
+/// Mutex per gildia: równoległe wejścia diffujemy po kolei. Trzymany tylko na czas
+/// porównania – pobranie listy (HTTP) idzie poza nim, kolejność pilnuje `fetched_seq`.
+type Snapshot = Arc<Mutex<GuildInvites>>;
+
+static SNAPSHOTS: Lazy<DashMap<u64, Snapshot>> = Lazy::new(DashMap::new);
+static FETCH_SEQ: AtomicU64 = AtomicU64::new(1);
+
+fn guild_snap(gid: u64) -> Snapshot {
+    SNAPSHOTS.entry(gid).or_default().clone()
+}
+
+/// Numer pobrania brany przed zapytaniem – późniejsze pobranie widzi nowszy stan.
+async fn fetch(ctx: &Context, gid: u64) -> Result<(u64, HashMap<String, InviteSnap>)> {
+    let seq = FETCH_SEQ.fetch_add(1, Ordering::Relaxed);
+    let list = GuildId::new(gid).invites(&ctx.http).await?;
+    let codes = list
+        .into_iter()
+        .map(|inv| {
+            let snap = InviteSnap {
+                uses: inv.uses,
+                max_uses: inv.max_uses as u64,
+                inviter_id: inv.inviter.map(|u| u.id.get()),
+                deleted_at: None,
+            };
+            (inv.code, snap)
+        })
+        .collect();
+    Ok((seq, codes))
+}
+
+/// Zrzut zaproszeń gildii (guild_create).
+pub async fn warmup(ctx: &Context, gid: u64) -> Result<usize> {
+    let (seq, fresh) = fetch(ctx, gid).await?;
+    let n = fresh.len();
+    let snap = guild_snap(gid);
+    let mut state = snap.lock().await;
+    if seq > state.fetched_seq {
+        *state = GuildInvites { codes: fresh, fetched_seq: seq };
+    }
+    Ok(n)
+}
+
+pub async fn on_invite_create(ev: &InviteCreateEvent) {
+    let Some(gid) = ev.guild_id else { return; };
+    guild_snap(gid.get()).lock().await.codes.insert(
+        ev.code.clone(),
+        InviteSnap {
+            uses: ev.uses,
+            max_uses: ev.max_uses as u64,
+            inviter_id: ev.inviter.as_ref().map(|u| u.id.get()),
+            deleted_at: None,
+        },
+    );
+}
+
+/// Usunięte ręcznie albo wygasłe – zaproszenie wyczerpane limitem usuwa Discord
+/// tuż przy wejściu, więc takie (uses + 1 == max_uses) zostaje na `DELETED_GRACE`;
+/// ręcznie usunięte jednorazowe zaproszenie nie przejmie późniejszych wejść.
+pub async fn on_invite_delete(ev: &InviteDeleteEvent) {
+    let Some(gid) = ev.guild_id else { return; };
+    let snap = guild_snap(gid.get());
+    let mut state = snap.lock().await;
+    let Some(s) = state.codes.get_mut(&ev.code) else { return; };
+    if s.max_uses > 0 && s.uses + 1 >= s.max_uses {
+        s.deleted_at = Some(Instant::now());
+    } else {
+        state.codes.remove(&ev.code);
+    }
+}
+
+/// Ustal zaproszenie użyte przy wejściu: rosnący licznik albo zaproszenie, które zniknęło
+/// po osiągnięciu limitu. Kilka kandydatów (równoczesne wejścia) = nie zgadujemy.
+/// Nowy zrzut zastępuje stary – usunięte zaproszenia znikają po pierwszym wejściu.
+/// Jeśli równoległe wejście zapisało już nowszy zrzut, nasz wynik jest nieaktualny – bez zgadywania.
+pub async fn detect_join(ctx: &Context, gid: u64) -> Option<UsedInvite> {
+    let (seq, fresh) = match fetch(ctx, gid).await {
+        Ok(f) => f,
+        Err(e) => {
+            tracing::debug!(error=?e, gid, "invite fetch failed (brak Manage Server?)");
+            return None;
+        }
+    };
+    let snap = guild_snap(gid);
+    let mut state = snap.lock().await;
+    if seq < state.fetched_seq {
+        return None;
+    }
+    let map = &state.codes;
+
+    let mut used: Vec<UsedInvite> = fresh
+        .iter()
+        .filter(|(code, now)| now.uses > map.get(*code).map(|s| s.uses).unwrap_or(0))
+        .map(|(code, now)| UsedInvite { code: code.clone(), inviter_id: now.inviter_id })
+        .collect();
+    if used.is_empty() {
+        used = map
+            .iter()
+            .filter(|(code, s)| !fresh.contains_key(*code) && s.maybe_exhausted())
+            .map(|(code, s)| UsedInvite { code: code.clone(), inviter_id: s.inviter_id })
+            .collect();
+    }
+
+    *state = GuildInvites { codes: fresh, fetched_seq: seq };
+    if used.len() == 1 { used.pop() } else { None }
+}
+
+/// `abc123` od @inviter – do /user i logów AltGuard.
+pub fn label(code: Option<&str>, inviter_id: Option<u64>) -> String {
+    match (code, inviter_id) {
+        (Some(c), Some(by)) => format!("`{c}` od <@{by}>"),
+        (Some(c), None) => format!("`{c}`"),
+        (None, _) => "nieznane".into(),
+    }
+}
+
+/* ========================= tss.member_joins ========================= */
+
+pub async fn record_join(db: &Pool<Postgres>, gid: u64, uid: u64, invite: Option<&UsedInvite>) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tss.member_joins (guild_id, user_id, invite_code, inviter_id)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .bind(invite.map(|i| i.code.as_str()))
+    .bind(invite.and_then(|i| i.inviter_id).map(|v| v as i64))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn last_join(db: &Pool<Postgres>, gid: u64, uid: u64) -> Result<Option<JoinRecord>> {
+    let row = sqlx::query(
+        r#"
+        SELECT invite_code, inviter_id, EXTRACT(EPOCH FROM joined_at)::BIGINT AS joined_unix
+          FROM tss.member_joins
+         WHERE guild_id = $1 AND user_id = $2
+         ORDER BY joined_at DESC
+         LIMIT 1
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .fetch_optional(db)
+    .await?;
+    let Some(r) = row else { return Ok(None); };
+    Ok(Some(JoinRecord {
+        invite_code: r.try_get("invite_code")?,
+        inviter_id: r.try_get::<Option<i64>, _>("inviter_id")?.map(|v| v as u64),
+        joined_unix: r.try_get("joined_unix")?,
+    }))
+}
@@ -10,7 +10,12 @@ use serenity::all::{
     Permissions, User, UserId,
 };
 
-use crate::{AppContext, registry::env_channels};
+use crate::discord::router::Routes;
+use crate::{
+    cases::{self, CaseAction, NewCase},
+    permissions::{self, Permission},
+    AppContext, registry::env_channels,
+};
 
 const SYSTEM_NAME: &str = "Tigris Kick System™";
 const SERVER_NAME: &str = "Unfaithful";
@@ -18,33 +23,32 @@ const SERVER_NAME: &str = "Unfaithful";
 pub struct Kick;
 
 impl Kick {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["kick"]);
+
     /// Rejestr /kick (per gildia)
-    pub async fn register_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-        guild_id
-            .create_command(
-                &ctx.http,
-                CreateCommand::new("kick")
-                    .description("Wyrzuć użytkownika z serwera (z powodem)")
-                    .add_option(
-                        CreateCommandOption::new(
-                            CommandOptionType::User,
-                            "user",
-                            "Kogo chcesz wyrzucić",
-                        )
-                        .required(true),
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new("kick")
+                .description("Wyrzuć użytkownika z serwera (z powodem)")
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::User,
+                        "user",
+                        "Kogo chcesz wyrzucić",
                     )
-                    .add_option(
-                        CreateCommandOption::new(
-                            CommandOptionType::String,
-                            "reason",
-                            "Powód wyrzucenia",
-                        )
-                        .required(true),
+                    .required(true),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "reason",
+                        "Powód wyrzucenia",
                     )
-                    .default_member_permissions(Permissions::KICK_MEMBERS),
-            )
-            .await?;
-        Ok(())
+                    .required(true),
+                )
+                .default_member_permissions(Permissions::KICK_MEMBERS),
+        ]
     }
 
     /// Router interakcji
@@ -90,7 +94,7 @@ async fn handle_kick(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     let reason_text = reason.unwrap_or_else(|| "Brak powodu".into());
 
     // 2) Walidacje: permission + self/bot/owner + hierarchia
-    if !user_can_kick(ctx, gid, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Kick) {
         return edit_ephemeral_text(ctx, cmd, "⛔ Brak uprawnień do wyrzucania.").await;
     }
     if target_id == cmd.user.id || target_id.get() == ctx.cache.current_user().id.get() {
@@ -105,26 +109,68 @@ async fn handle_kick(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         return edit_ephemeral_text(ctx, cmd, "⛔ Moje uprawnienia/pozycja ról nie pozwalają wyrzucić tego użytkownika.").await;
     }
 
-    // 3) DM – elegancka wiadomość (ignore error)
-    let _ = send_kick_dm(ctx, target_id, &reason_text).await;
-
-    // 4) Kick
-    let audit_reason = format!("[{}] {}", SYSTEM_NAME, &reason_text);
-    if let Err(e) = gid.kick_with_reason(&ctx.http, target_id, &audit_reason).await {
+    // 3–5) DM + kick + log
+    if let Err(e) = apply_kick(ctx, app, gid, cmd.user.id, target_id, &reason_text, None).await {
         return edit_ephemeral_text(ctx, cmd, &format!("⛔ Nie udało się wyrzucić użytkownika: {e}")).await;
     }
 
-    // 5) Log na kanale LOGS_BAN_KICK_MUTE (jeśli ustawiono)
-    if let Some(log_ch) = log_channel_bkm(app) {
-        let embed = kick_log_embed(ctx, gid, cmd.user.id, target_id, &reason_text).await;
+    // 6) Potwierdzenie dla moda – estetyczny embed
+    let confirm = kick_confirm_embed(ctx, gid, cmd.user.id, target_id, &reason_text).await;
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().embeds(vec![confirm])).await?;
+    Ok(())
+}
+
+/// Wspólna ścieżka kicka (slash /kick oraz automatyczne eskalacje):
+/// DM do użytkownika → kick z powodem w audit logu → log na LOGS_BAN_KICK_MUTE.
+/// `source` (np. „Auto-eskalacja z warna (sprawa #12)”) trafia do logu jako osobne pole.
+pub(crate) async fn apply_kick(
+    ctx: &Context,
+    app: &AppContext,
+    gid: GuildId,
+    moderator_id: UserId,
+    target_id: UserId,
+    reason: &str,
+    source: Option<&str>,
+) -> Result<()> {
+    // DM – elegancka wiadomość (ignore error)
+    let _ = send_kick_dm(ctx, target_id, reason).await;
+
+    // Kick
+    let audit_reason = format!("[{}] {}", SYSTEM_NAME, reason);
+    gid.kick_with_reason(&ctx.http, target_id, &audit_reason).await?;
+
+    // Sprawa w tss.cases (kick nie ma własnej tabeli)
+    let case_no = cases::record_case_logged(
+        &app.db,
+        &NewCase {
+            guild_id: gid.get(),
+            user_id: target_id.get(),
+            moderator_id: moderator_id.get(),
+            action: CaseAction::Kick,
+            reason,
+            evidence: None,
+            source,
+            reference: None,
+        },
+    )
+    .await;
+
+    // Log na kanale LOGS_BAN_KICK_MUTE (jeśli ustawiono)
+    if let Some(log_ch) = log_channel_bkm(gid.get()) {
+        let mut embed = kick_log_embed(ctx, gid, moderator_id, target_id, reason).await;
+        if let Some(no) = case_no {
+            embed = embed.field("Sprawa", format!("#{no}"), true);
+        }
+        if let Some(src) = source {
+            embed = embed.field("Źródło", src, false);
+        }
         let _ = ChannelId::new(log_ch)
             .send_message(&ctx.http, CreateMessage::new().embed(embed))
             .await;
     }
 
-    // 6) Potwierdzenie dla moda – estetyczny embed
-    let confirm = kick_confirm_embed(ctx, gid, cmd.user.id, target_id, &reason_text).await;
-    cmd.edit_response(&ctx.http, EditInteractionResponse::new().embeds(vec![confirm])).await?;
+    // AltGuard: nazwy + avatar wyrzuconego -> wykrywanie powrotu na multikoncie
+    app.altguard().record_punished(ctx, gid.get(), target_id.get(), "kick");
     Ok(())
 }
 
@@ -234,15 +280,6 @@ fn truncate_code(s: &str, max: usize) -> String {
     out
 }
 
-async fn user_can_kick(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    if let Ok(member) = gid.member(&ctx.http, uid).await {
-        if let Ok(perms) = member.permissions(&ctx.cache) { // (deprecated, ale OK)
-            return perms.kick_members() || perms.administrator();
-        }
-    }
-    false
-}
-
 /// Czy BOT może celować w tego użytkownika – sprawdzamy hierarchię ról.
 async fn bot_can_target(ctx: &Context, gid: GuildId, target: UserId) -> bool {
     let Ok(bot_id) = ctx.http.get_current_user().await.map(|u| u.id) else { return false; };
@@ -271,8 +308,7 @@ fn highest_role_position(
 }
 
 /// Id kanału logów z env (LOGS_BAN_KICK_MUTE). Zwraca None jeśli 0/nieustawione.
-fn log_channel_bkm(app: &AppContext) -> Option<u64> {
-    let env = app.env();
-    let id = env_channels::logs::ban_kick_mute_id(&env);
+fn log_channel_bkm(gid: u64) -> Option<u64> {
+    let id = env_channels::logs::ban_kick_mute_id(gid);
     if id == 0 { None } else { Some(id) }
 }
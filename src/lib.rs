@@ -3,19 +3,29 @@
 pub mod admcheck;
 pub mod admin_points;
 pub mod altguard; // ← udostępniamy moduł AltGuard
+pub mod altguard_admin;
+pub mod appeals;
+pub mod avatar_nsfw;
+pub mod avatar_ocr;
 pub mod ban;
+pub mod cases;
 pub mod chatguard;
 pub mod config;
 pub mod db;
 pub mod discord;
 pub mod fotosystem;
+pub mod guilds; // ← profil gildii (zamiast TSS_ENV)
+pub mod invites;
 pub mod kick;
 pub mod logging;
 pub mod mdel;
 pub mod mute;
 pub mod new_channels;
 pub mod permissions;
+pub mod raid;
 pub mod registry; // ← rejestr ról/kanałów PROD/DEV
+pub mod registry_admin;
+pub mod perms_admin;
 pub mod stats_channels;
 pub mod userinfo;
 pub mod warn;
@@ -53,15 +63,20 @@ impl AppContext {
     /// - logi
     /// - połączenie z DB + migracje
     /// - stworzenie i wstrzyknięcie AltGuard oraz IdGuard do OnceCell
+    /// - profile i rejestr zasobów znanych gildii (z tss.guilds)
+    /// - schedulery w tle (zdejmowanie banów tymczasowych, wygasłych wyciszeń rolą)
     pub async fn bootstrap(settings: Settings) -> Result<Arc<Self>> {
         // 1) logi
         logging::init(&settings);
 
-        // 2) DB
+        // 2) profil gildii bez wpisu w tss.guilds (TSS_ENV służy już tylko jako domyślny)
+        guilds::init_default(&settings.env);
+
+        // 3) DB
         let db = db::connect(&settings.database.url, settings.database.max_connections).await?;
         db::migrate(&db).await?;
 
-        // 3) kontekst (na razie z pustymi OnceCell)
+        // 4) kontekst (na razie z pustymi OnceCell)
         let ctx = Arc::new(Self {
             settings,
             db,
@@ -69,14 +84,30 @@ impl AppContext {
             idguard: OnceCell::new(),
         });
 
-        // 4) AltGuard
+        // 5) AltGuard
         let ag = altguard::AltGuard::new(ctx.clone());
         let _ = ctx.altguard.set(ag); // set() można wołać tylko raz
 
-        // 5) IdGuard
+        // 6) IdGuard
         let idg = idguard::IdGuard::new(ctx.clone());
         let _ = ctx.idguard.set(idg);
 
+        // 7) profile + rejestr znanych gildii – workery mogą ruszyć przed guild_create
+        match guilds::warmup_all(&ctx.db).await {
+            Ok(ids) => {
+                for gid in ids {
+                    if let Err(e) = registry::warmup(&ctx.db, gid).await {
+                        tracing::warn!(error=?e, gid, "registry warmup failed (bootstrap)");
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error=?e, "guild profiles warmup failed (bootstrap)"),
+        }
+
+        // 8) schedulery w tle
+        ban::BanPanel::spawn_expiry_scheduler(ctx.clone());
+        mute::Mute::spawn_expiry_worker(ctx.clone());
+
         Ok(ctx)
     }
 
@@ -92,23 +123,18 @@ impl AppContext {
     pub fn idguard(&self) -> Arc<idguard::IdGuard> {
         self.idguard.get().expect("IdGuard not initialized").clone()
     }
-
-    /// Środowisko: "production" | "development".
-    /// Czytamy z ENV `TSS_ENV`; brak → "development".
-    #[inline]
-    pub fn env(&self) -> String {
-        std::env::var("TSS_ENV").unwrap_or_else(|_| "development".to_string())
-    }
 }
 
 /// Gotowy zestaw intents do użycia w kliencie Discord:
 /// - GUILDS, GUILD_MESSAGES, MESSAGE_CONTENT (konieczne do filtrowania treści),
-/// - GUILD_MEMBERS (role – potrzebne do sprawdzania staffu).
+/// - GUILD_MEMBERS (role – potrzebne do sprawdzania staffu),
+/// - GUILD_INVITES (zrzut zaproszeń – które zaproszenie użyto przy wejściu).
 pub fn default_gateway_intents() -> GatewayIntents {
     GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT
         | GatewayIntents::GUILD_MEMBERS
+        | GatewayIntents::GUILD_INVITES
 }
 
 /// Start klienta Discorda (Gateway + slash commands).
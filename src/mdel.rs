@@ -2,14 +2,20 @@
 use anyhow::Result;
 use serenity::all::*;
 
-use crate::{AppContext, registry::env_channels};
+use crate::discord::router::Routes;
+use crate::{
+    permissions::{self, Permission},
+    AppContext, registry::env_channels,
+};
 
 pub struct MDel;
 
 impl MDel {
-    pub async fn register_commands(ctx: &Context, gid: GuildId) -> Result<()> {
-        gid.create_command(
-            &ctx.http,
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["mdel"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
             CreateCommand::new("mdel")
                 .description("Masowe usuwanie wiadomości (≤14 dni)")
                 .add_option(
@@ -28,9 +34,7 @@ impl MDel {
                     )
                 )
                 .default_member_permissions(Permissions::MANAGE_MESSAGES),
-        )
-        .await?;
-        Ok(())
+        ]
     }
 
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
@@ -51,7 +55,7 @@ impl MDel {
     }
 }
 
-async fn handle_mdel(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+async fn handle_mdel(ctx: &Context, _app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
     // 1) NATYCHMIASTOWY ACK (żeby Discord nie pokazał „Aplikacja nie reaguje”)
     cmd.create_response(
         &ctx.http,
@@ -63,8 +67,8 @@ async fn handle_mdel(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     let Some(gid) = cmd.guild_id else {
         return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
     };
-    if !user_can_manage_messages(ctx, gid, cmd.user.id).await {
-        return edit_ephemeral(ctx, cmd, "⛔ Wymagane **Zarządzanie wiadomościami**.").await;
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Mdel) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
     // 3) Parametry
@@ -127,7 +131,7 @@ async fn handle_mdel(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     }
 
     // 5) Logi
-    if let Some(log_ch) = log_channel(app) {
+    if let Some(log_ch) = log_channel(gid.get()) {
         let embed = CreateEmbed::new()
             .title("🧹 Masowe usuwanie wiadomości")
             .colour(Colour::new(0xE74C3C))
@@ -154,23 +158,13 @@ async fn handle_mdel(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     Ok(())
 }
 
-async fn user_can_manage_messages(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    if let Ok(m) = gid.member(&ctx.http, uid).await {
-        if let Ok(p) = m.permissions(&ctx.cache) {
-            return p.manage_messages() || p.administrator();
-        }
-    }
-    false
-}
-
 async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
     cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg)).await?;
     Ok(())
 }
 
-fn log_channel(app: &AppContext) -> Option<u64> {
-    let env = app.env();
+fn log_channel(gid: u64) -> Option<u64> {
     // LOGS_MESSAGE_DELETE w registry.rs
-    let id = env_channels::logs::message_delete_id(&env);
+    let id = env_channels::logs::message_delete_id(gid);
     if id == 0 { None } else { Some(id) }
 }
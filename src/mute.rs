@@ -1,5 +1,6 @@
 // src/mute.rs
 
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
@@ -10,10 +11,23 @@ use serenity::all::*;
 use sqlx::{Pool, Postgres, Row};
 use chrono::{Utc, Duration};
 
-use crate::{AppContext, registry::env_channels};
+use crate::discord::router::Routes;
+use crate::{
+    cases::{self, CaseAction, CaseRef, NewCase},
+    permissions::{self, Permission},
+    AppContext, registry::env_channels,
+};
 
 const SYSTEM_NAME: &str = "Tigris Mute System";
 
+/// Co ile worker sprawdza wygasłe wyciszenia rolą.
+const EXPIRY_TICK: std::time::Duration = std::time::Duration::from_secs(60);
+/// Ile wygasłych case'ów obsługujemy w jednym przebiegu (reszta w kolejnym).
+const EXPIRY_BATCH: i64 = 50;
+
+/// Od tylu minut mute trafia do AltGuarda jako kara (jak ban/kick).
+const ALTGUARD_MIN_MUTE_MINUTES: i64 = 24 * 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MuteConfig {
     /// Rola „Muted” – jeśli ustawiona, używamy jej; w innym wypadku próbujemy timeout.
@@ -46,6 +60,9 @@ static CFG: Lazy<DashMap<u64, MuteConfig>> = Lazy::new(DashMap::new);
 pub struct Mute;
 
 impl Mute {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["mute", "unmute", "mute-config"]);
+
     /* ===================== DB bootstrap ===================== */
 
     pub async fn ensure_tables(db: &Pool<Postgres>) -> Result<()> {
@@ -124,11 +141,33 @@ impl Mute {
         Ok(())
     }
 
+    /* ===================== Expiry worker ===================== */
+
+    /// Worker w tle (startowany z `AppContext::bootstrap`): zdejmuje rolę Muted po upływie `until`
+    /// i zamyka case w imieniu bota – tak, jak Discord sam zdejmuje timeout.
+    pub fn spawn_expiry_worker(app: Arc<AppContext>) {
+        let http = Arc::new(Http::new(&app.settings.discord.token));
+        tokio::spawn(async move {
+            let mut bot_id: Option<UserId> = None;
+            loop {
+                if bot_id.is_none() {
+                    bot_id = http.get_current_user().await.ok().map(|u| u.id);
+                }
+                if let Some(bot) = bot_id
+                    && let Err(e) = lift_expired_role_mutes(&http, &app, bot).await
+                {
+                    tracing::warn!(target: "tigris_security::mute", ?e, "mute expiry tick failed");
+                }
+                tokio::time::sleep(EXPIRY_TICK).await;
+            }
+        });
+    }
+
     /* ===================== Commands ===================== */
 
-    pub async fn register_commands(ctx: &Context, gid: GuildId) -> Result<()> {
-        // /mute — wymagane parametry przed opcjonalnymi
-        gid.create_command(&ctx.http,
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            // /mute — wymagane parametry przed opcjonalnymi
             CreateCommand::new("mute")
                 .description("Wycisz użytkownika (rola Muted lub timeout).")
                 .default_member_permissions(Permissions::MODERATE_MEMBERS)
@@ -143,11 +182,8 @@ impl Mute {
                     ))
                 .add_option(CreateCommandOption::new(
                         CommandOptionType::String, "evidence", "Dowód/URL (opcjonalnie)"
-                    ))
-        ).await?;
-
-        // /unmute
-        gid.create_command(&ctx.http,
+                    )),
+            // /unmute
             CreateCommand::new("unmute")
                 .description("Zdejmij wyciszenie (rola/timeout).")
                 .default_member_permissions(Permissions::MODERATE_MEMBERS)
@@ -156,11 +192,8 @@ impl Mute {
                     ).required(true))
                 .add_option(CreateCommandOption::new(
                         CommandOptionType::String, "reason", "Powód (opcjonalnie)"
-                    ))
-        ).await?;
-
-        // /mute-config
-        gid.create_command(&ctx.http,
+                    )),
+            // /mute-config
             CreateCommand::new("mute-config")
                 .description("Konfiguracja systemu mute")
                 .default_member_permissions(Permissions::ADMINISTRATOR)
@@ -172,10 +205,8 @@ impl Mute {
                         .add_sub_option(CreateCommandOption::new(
                                 CommandOptionType::String, "role_id", "ID roli Muted (opcjonalnie)"
                             ))
-                )
-        ).await?;
-
-        Ok(())
+                ),
+        ]
     }
 
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
@@ -202,7 +233,7 @@ async fn handle_mute(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     ).await?;
     let Some(gid) = cmd.guild_id else { return edit(ctx, cmd, "Użyj na serwerze.").await; };
 
-    if !moderate_permission(ctx, gid, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Mute) {
         return edit(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
@@ -229,61 +260,27 @@ async fn handle_mute(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         Some(s) => parse_duration_minutes(s).unwrap_or(cfg.default_minutes as i64),
         None => cfg.default_minutes as i64,
     };
-    let until_opt = if minutes > 0 { Some(Utc::now() + Duration::minutes(minutes)) } else { None };
 
-    // Zastosuj mute
-    let (method, used_role) = if let Some(role_id) = cfg.role_id {
-        // metoda: ROLA
-        if let Ok(mut member) = gid.member(&ctx.http, uid).await {
-            let _ = member.add_role(&ctx.http, RoleId::new(role_id)).await;
-        }
-        ("role".to_string(), Some(role_id))
-    } else {
-        // metoda: TIMEOUT (jeśli mamy czas > 0), inaczej brak akcji
-        if let Some(until) = until_opt {
-            if let Ok(mut member) = gid.member(&ctx.http, uid).await {
-                let _ = member.disable_communication_until_datetime(&ctx.http, until.into()).await;
-            }
-            ("timeout".to_string(), None)
-        } else {
-            return edit(ctx, cmd, "Konfiguracja nie ma roli Muted, a czas = 0. Ustaw rolę w /mute-config lub podaj czas.").await;
-        }
+    let req = MuteRequest {
+        guild_id: gid,
+        moderator_id: cmd.user.id,
+        user_id: uid,
+        reason: &reason,
+        evidence: evidence.as_deref(),
+        minutes,
+        source: None,
+        force_timeout: false,
+    };
+    let Some(applied) = apply_mute(ctx, app, &req).await? else {
+        return edit(ctx, cmd, "Konfiguracja nie ma roli Muted, a czas = 0. Ustaw rolę w /mute-config lub podaj czas.").await;
     };
-
-    // Zapisz sprawę
-    let until_unix: Option<i64> = until_opt.map(|dt| dt.timestamp());
-
-    let case_id: i64 = sqlx::query_scalar(
-        r#"
-        INSERT INTO tss.mute_cases
-            (guild_id, user_id, moderator_id, reason, evidence, until, method, role_id)
-        VALUES
-            ($1,$2,$3,$4,$5, CASE WHEN $6 IS NULL THEN NULL ELSE to_timestamp($6) END, $7, $8)
-        RETURNING id
-        "#
-    )
-    .bind(gid.get() as i64)
-    .bind(uid.get() as i64)
-    .bind(cmd.user.id.get() as i64)
-    .bind(&reason)
-    .bind(evidence.as_deref())
-    .bind(until_unix) // Option<i64>
-    .bind(&method)
-    .bind(used_role.map(|v| v as i64))
-    .fetch_one(&app.db)
-    .await?;
-
-    // Log
-    if let Some(log_ch) = log_channel(app) {
-        let e = embed_muted(ctx, gid, cmd.user.id, uid, &reason, evidence.as_deref(), minutes, &method, used_role).await;
-        let _ = ChannelId::new(log_ch).send_message(&ctx.http, CreateMessage::new().embed(e)).await;
-    }
 
     // Potwierdzenie
+    let case_txt = applied.case_no.map(|no| format!(" (sprawa `#{no}`)")).unwrap_or_default();
     let txt = if minutes > 0 {
-        format!("✅ Uciszono <@{}> na **{}** (case `#{}`)", uid.get(), human_minutes(minutes), case_id)
+        format!("✅ Uciszono <@{}> na **{}**{}", uid.get(), human_minutes(minutes), case_txt)
     } else {
-        format!("✅ Uciszono <@{}> **bezterminowo** (case `#{}`)", uid.get(), case_id)
+        format!("✅ Uciszono <@{}> **bezterminowo**{}", uid.get(), case_txt)
     };
     edit(ctx, cmd, &txt).await
 }
@@ -298,7 +295,7 @@ async fn handle_unmute(ctx: &Context, app: &AppContext, cmd: &CommandInteraction
     ).await?;
     let Some(gid) = cmd.guild_id else { return edit(ctx, cmd, "Użyj na serwerze.").await; };
 
-    if !moderate_permission(ctx, gid, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Unmute) {
         return edit(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
@@ -326,7 +323,7 @@ async fn handle_unmute(ctx: &Context, app: &AppContext, cmd: &CommandInteraction
     }
 
     // Zapis w DB – zamknij najnowszy otwarty case
-    let _ = sqlx::query(
+    let closed: Option<i64> = sqlx::query_scalar(
         r#"
         WITH last AS (
             SELECT id
@@ -342,18 +339,35 @@ async fn handle_unmute(ctx: &Context, app: &AppContext, cmd: &CommandInteraction
                unmuted_by = $1,
                unmute_reason = $2
          WHERE mc.id IN (SELECT id FROM last)
+        RETURNING mc.id
         "#
     )
     .bind(cmd.user.id.get() as i64)
     .bind(&reason)
     .bind(gid.get() as i64)
     .bind(uid.get() as i64)
-    .execute(&app.db)
+    .fetch_optional(&app.db)
     .await?;
 
+    let case_no = cases::record_case_logged(
+        &app.db,
+        &NewCase {
+            guild_id: gid.get(),
+            user_id: uid.get(),
+            moderator_id: cmd.user.id.get(),
+            action: CaseAction::Unmute,
+            reason: &reason,
+            evidence: None,
+            source: None,
+            reference: closed.map(CaseRef::Mute),
+        },
+    )
+    .await;
+
     // Log
-    if let Some(log_ch) = log_channel(app) {
-        let e = embed_unmuted(ctx, gid, cmd.user.id, uid, &reason).await;
+    if let Some(log_ch) = log_channel(gid.get()) {
+        let mut e = embed_unmuted(&ctx.http, gid, cmd.user.id, uid, &reason).await;
+        if let Some(no) = case_no { e = e.field("Sprawa", format!("#{no}"), true); }
         let _ = ChannelId::new(log_ch).send_message(&ctx.http, CreateMessage::new().embed(e)).await;
     }
 
@@ -370,8 +384,8 @@ async fn handle_mute_config(ctx: &Context, app: &AppContext, cmd: &CommandIntera
     ).await?;
     let Some(gid) = cmd.guild_id else { return edit(ctx, cmd, "Użyj na serwerze.").await; };
 
-    if !admin_permission(ctx, gid, cmd.user.id).await {
-        return edit(ctx, cmd, "⛔ Tylko administrator.").await;
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::MuteConfig) {
+        return edit(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
     let mut cfg = load_cfg_from_cache_or_default(gid.get());
@@ -397,7 +411,7 @@ async fn handle_mute_config(ctx: &Context, app: &AppContext, cmd: &CommandIntera
     CFG.insert(gid.get(), cfg.clone());
 
     // Log: zmiana konfiguracji
-    if let Some(log_ch) = log_channel(app) {
+    if let Some(log_ch) = log_channel(gid.get()) {
         let e = CreateEmbed::new()
             .title("🛠️ Zmieniono konfigurację Mute")
             .colour(Colour::new(0x95A5A6))
@@ -414,6 +428,115 @@ async fn handle_mute_config(ctx: &Context, app: &AppContext, cmd: &CommandIntera
     edit(ctx, cmd, "✅ Zapisano konfigurację Mute.").await
 }
 
+/* ========================= Core ========================= */
+
+/// Parametry pojedynczego wyciszenia (wspólne dla /mute i automatycznych eskalacji).
+pub(crate) struct MuteRequest<'a> {
+    pub guild_id: GuildId,
+    pub moderator_id: UserId,
+    pub user_id: UserId,
+    pub reason: &'a str,
+    pub evidence: Option<&'a str>,
+    /// 0 = bezterminowo (tylko z rolą Muted)
+    pub minutes: i64,
+    /// Skąd pochodzi akcja (np. „Auto-eskalacja z warna (sprawa #12)”) – trafia do logu.
+    pub source: Option<&'a str>,
+    /// Zawsze timeout Discorda, nawet gdy skonfigurowano rolę Muted (eskalacja warnów „timeout”).
+    pub force_timeout: bool,
+}
+
+/// Wynik `apply_mute`: id w `tss.mute_cases` i numer sprawy gildii (None, gdy zapis do tss.cases się nie udał).
+pub(crate) struct AppliedMute {
+    pub case_id: i64,
+    pub case_no: Option<i64>,
+}
+
+/// Nałóż wyciszenie (rola Muted albo timeout), zapisz case i wyślij log.
+/// Zwraca `None`, gdy nie ma czym wyciszyć (brak roli Muted i czas = 0).
+pub(crate) async fn apply_mute(ctx: &Context, app: &AppContext, req: &MuteRequest<'_>) -> Result<Option<AppliedMute>> {
+    Mute::ensure_runtime(&app.db).await.ok();
+
+    let gid = req.guild_id;
+    let uid = req.user_id;
+    let minutes = req.minutes;
+    let cfg = load_cfg(&app.db, gid.get()).await;
+    let until_opt = if minutes > 0 { Some(Utc::now() + Duration::minutes(minutes)) } else { None };
+
+    // Zastosuj mute
+    let role = if req.force_timeout { None } else { cfg.role_id };
+    let (method, used_role) = if let Some(role_id) = role {
+        // metoda: ROLA
+        if let Ok(member) = gid.member(&ctx.http, uid).await {
+            let _ = member.add_role(&ctx.http, RoleId::new(role_id)).await;
+        }
+        ("role".to_string(), Some(role_id))
+    } else {
+        // metoda: TIMEOUT (jeśli mamy czas > 0), inaczej brak akcji
+        if let Some(until) = until_opt {
+            if let Ok(mut member) = gid.member(&ctx.http, uid).await {
+                let _ = member.disable_communication_until_datetime(&ctx.http, until.into()).await;
+            }
+            ("timeout".to_string(), None)
+        } else {
+            return Ok(None);
+        }
+    };
+
+    // Zapisz sprawę
+    let until_unix: Option<i64> = until_opt.map(|dt| dt.timestamp());
+
+    let case_id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO tss.mute_cases
+            (guild_id, user_id, moderator_id, reason, evidence, until, method, role_id)
+        VALUES
+            ($1,$2,$3,$4,$5, CASE WHEN $6 IS NULL THEN NULL ELSE to_timestamp($6) END, $7, $8)
+        RETURNING id
+        "#
+    )
+    .bind(gid.get() as i64)
+    .bind(uid.get() as i64)
+    .bind(req.moderator_id.get() as i64)
+    .bind(req.reason)
+    .bind(req.evidence)
+    .bind(until_unix) // Option<i64>
+    .bind(&method)
+    .bind(used_role.map(|v| v as i64))
+    .fetch_one(&app.db)
+    .await?;
+
+    let case_no = cases::record_case_logged(
+        &app.db,
+        &NewCase {
+            guild_id: gid.get(),
+            user_id: uid.get(),
+            moderator_id: req.moderator_id.get(),
+            action: if method == "timeout" { CaseAction::Timeout } else { CaseAction::Mute },
+            reason: req.reason,
+            evidence: req.evidence,
+            source: req.source,
+            reference: Some(CaseRef::Mute(case_id)),
+        },
+    )
+    .await;
+
+    // Log
+    if let Some(log_ch) = log_channel(gid.get()) {
+        let mut e = embed_muted(ctx, gid, req.moderator_id, uid, req.reason, req.evidence, minutes, &method, used_role).await;
+        if let Some(no) = case_no { e = e.field("Sprawa", format!("#{no}"), true); }
+        if let Some(src) = req.source { e = e.field("Źródło", src, false); }
+        let _ = ChannelId::new(log_ch).send_message(&ctx.http, CreateMessage::new().embed(e)).await;
+    }
+
+    // AltGuard: długi (albo bezterminowy) mute też zasila wykrywanie multikont
+    if minutes == 0 || minutes >= ALTGUARD_MIN_MUTE_MINUTES {
+        let action = if method == "timeout" { "timeout" } else { "mute" };
+        app.altguard().record_punished(ctx, gid.get(), uid.get(), action);
+    }
+
+    Ok(Some(AppliedMute { case_id, case_no }))
+}
+
 /* ========================= Embeds ========================= */
 
 async fn embed_muted(
@@ -448,7 +571,7 @@ async fn embed_muted(
 }
 
 async fn embed_unmuted(
-    ctx: &Context,
+    http: &Http,
     _gid: GuildId,
     mod_id: UserId,
     uid: UserId,
@@ -464,12 +587,111 @@ async fn embed_unmuted(
         .field("Powód", format!("```{}```", truncate(reason, 900)), false)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
-    if let Ok(user) = uid.to_user(&ctx.http).await {
-        if let Some(avatar) = user.avatar_url() { e = e.thumbnail(avatar); }
+    if let Ok(user) = uid.to_user(http).await
+        && let Some(avatar) = user.avatar_url()
+    {
+        e = e.thumbnail(avatar);
     }
     e
 }
 
+/* ========================= Expiry ========================= */
+
+/// Jeden przebieg workera: wygasłe, otwarte wyciszenia rolą → zdejmij rolę, zamknij case,
+/// zapisz UNMUTE w `tss.cases`, zaloguj.
+async fn lift_expired_role_mutes(http: &Arc<Http>, app: &AppContext, bot: UserId) -> Result<()> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, guild_id, user_id, role_id
+        FROM tss.mute_cases
+        WHERE method = 'role'
+          AND unmuted_at IS NULL
+          AND until IS NOT NULL
+          AND until <= now()
+        ORDER BY until ASC
+        LIMIT $1
+        "#
+    )
+    .bind(EXPIRY_BATCH)
+    .fetch_all(&app.db)
+    .await?;
+
+    let reason = "Wyciszenie wygasło (automatycznie)";
+
+    for r in rows {
+        let case_id: i64 = r.get("id");
+        let gid = GuildId::new(r.get::<i64, _>("guild_id") as u64);
+        let uid = UserId::new(r.get::<i64, _>("user_id") as u64);
+
+        // rola zapisana w case; gdy brak – bieżąca z konfiguracji
+        let role_id = match r.get::<Option<i64>, _>("role_id") {
+            Some(v) => Some(v as u64),
+            None => load_cfg(&app.db, gid.get()).await.role_id,
+        };
+
+        if let Some(rid) = role_id {
+            match http.remove_member_role(gid, uid, RoleId::new(rid), Some(reason)).await {
+                Ok(()) => {}
+                // użytkownik wyszedł z serwera – roli i tak już nie ma
+                Err(e) if is_unknown_member(&e) => {}
+                Err(e) => {
+                    tracing::warn!(target: "tigris_security::mute", ?e, case_id, "mute expiry: remove role failed; retry next tick");
+                    continue;
+                }
+            }
+        }
+
+        let closed = sqlx::query(
+            r#"
+            UPDATE tss.mute_cases
+               SET unmuted_at = now(),
+                   unmuted_by = $2,
+                   unmute_reason = $3
+             WHERE id = $1 AND unmuted_at IS NULL
+            "#
+        )
+        .bind(case_id)
+        .bind(bot.get() as i64)
+        .bind(reason)
+        .execute(&app.db)
+        .await?
+        .rows_affected() > 0;
+
+        if !closed { continue; }
+
+        let case_no = cases::record_case_logged(
+            &app.db,
+            &NewCase {
+                guild_id: gid.get(),
+                user_id: uid.get(),
+                moderator_id: bot.get(),
+                action: CaseAction::Unmute,
+                reason,
+                evidence: None,
+                source: Some("Automatycznie: wyciszenie wygasło"),
+                reference: Some(CaseRef::Mute(case_id)),
+            },
+        )
+        .await;
+
+        if let Some(log_ch) = log_channel(gid.get()) {
+            let mut e = embed_unmuted(http, gid, bot, uid, reason).await;
+            if let Some(no) = case_no { e = e.field("Sprawa", format!("#{no}"), true); }
+            let _ = ChannelId::new(log_ch).send_message(http, CreateMessage::new().embed(e)).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Discord: 10007 = Unknown Member.
+fn is_unknown_member(e: &serenity::Error) -> bool {
+    matches!(
+        e,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(resp)) if resp.error.code == 10007
+    )
+}
+
 /* ========================= DB + CFG + utils ========================= */
 
 fn load_cfg_from_cache_or_default(gid: u64) -> MuteConfig {
@@ -555,25 +777,8 @@ fn truncate(s: &str, max: usize) -> String {
     else { let mut t = s[..max.saturating_sub(1)].to_string(); t.push('…'); t }
 }
 
-fn log_channel(app: &AppContext) -> Option<u64> {
-    let env = app.env();
-    let id = env_channels::logs::ban_kick_mute_id(&env);
+fn log_channel(gid: u64) -> Option<u64> {
+    let id = env_channels::logs::ban_kick_mute_id(gid);
     if id == 0 { None } else { Some(id) }
 }
 
-async fn moderate_permission(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    if let Ok(m) = gid.member(&ctx.http, uid).await {
-        if let Ok(p) = m.permissions(&ctx.cache) {
-            return p.moderate_members() || p.kick_members() || p.administrator();
-        }
-    }
-    false
-}
-async fn admin_permission(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    if let Ok(m) = gid.member(&ctx.http, uid).await {
-        if let Ok(p) = m.permissions(&ctx.cache) {
-            return p.administrator();
-        }
-    }
-    false
-}
@@ -12,8 +12,7 @@ pub struct NewChannels;
 
 impl NewChannels {
     /// Loguje utworzenie nowego kanału (tylko jeśli jego parent znajduje się na liście obserwowanych kategorii).
-    pub async fn on_channel_create(ctx: &Context, app: &AppContext, ch: &GuildChannel) {
-        let env = app.env();
+    pub async fn on_channel_create(ctx: &Context, _app: &AppContext, ch: &GuildChannel) {
 
         let parent_id = match ch.parent_id {
             Some(p) => p,
@@ -21,7 +20,7 @@ impl NewChannels {
         };
 
         // Czy kategoria jest obserwowana?
-        if !env_channels::watch::categories(&env)
+        if !env_channels::watch::categories(ch.guild_id.get())
             .into_iter()
             .any(|id| id == parent_id.get())
         {
@@ -29,9 +28,9 @@ impl NewChannels {
         }
 
         // Kanał do logów "nowe-kanały" (DEV), ewentualnie utwórz pod wskazaną kategorią.
-        let mut log_id = env_channels::new_channels_id(&env);
+        let mut log_id = env_channels::new_channels_id(ch.guild_id.get());
         if log_id == 0 {
-            let parent_cat = env_channels::new_channels_parent_id(&env);
+            let parent_cat = env_channels::new_channels_parent_id(ch.guild_id.get());
             if parent_cat == 0 {
                 tracing::warn!("Brak LOGS_NEW_CHANNELS i LOGS_NEW_CHANNELS_PARENT – pomijam log.");
                 return;
@@ -97,27 +96,26 @@ impl NewChannels {
     /// Loguje usunięcie kanału (analogicznie jak utworzenie).
     pub async fn on_channel_delete(
         ctx: &Context,
-        app: &AppContext,
+        _app: &AppContext,
         ch: &GuildChannel,
         _messages: Option<Vec<Message>>,
     ) {
-        let env = app.env();
 
         let parent_id = match ch.parent_id {
             Some(p) => p,
             None => return,
         };
 
-        if !env_channels::watch::categories(&env)
+        if !env_channels::watch::categories(ch.guild_id.get())
             .into_iter()
             .any(|id| id == parent_id.get())
         {
             return;
         }
 
-        let mut log_id = env_channels::new_channels_id(&env);
+        let mut log_id = env_channels::new_channels_id(ch.guild_id.get());
         if log_id == 0 {
-            let parent_cat = env_channels::new_channels_parent_id(&env);
+            let parent_cat = env_channels::new_channels_parent_id(ch.guild_id.get());
             if parent_cat == 0 {
                 tracing::warn!("Brak LOGS_NEW_CHANNELS i LOGS_NEW_CHANNELS_PARENT – pomijam log (delete).");
                 return;
@@ -1,11 +1,26 @@
+// src/permissions.rs
+//
+// Jedno źródło prawdy dla uprawnień komend: `authorize(gildia, członek, Permission)`.
+// Uprawnienia (capabilities) przypisuje się rolom w tss.role_capabilities. Dopóki gildia
+// nie skonfiguruje danej capability przez /perms, obowiązuje szablon PERMISSIONS
+// (role rozwiązywane przez registry). Właściciel serwera i Administrator przechodzą zawsze.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use dashmap::DashMap;
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use serenity::all::{Context, GuildId, Member, UserId};
+use sqlx::{Pool, Postgres, Row, Transaction};
+
+use crate::registry;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Role {
     Wlasciciel,
     WspolWlasciciel,
     TechnikZarzad,
+    GumisOdBotow,
     Opiekun,
     HeadAdmin,
     Admin,
@@ -14,48 +29,223 @@ pub enum Role {
     TestModerator,
 }
 
+impl Role {
+    /// Klucz roli w registry.
+    pub fn registry_key(self) -> &'static str {
+        match self {
+            Role::Wlasciciel => "role.owner",
+            Role::WspolWlasciciel => "role.co_owner",
+            Role::TechnikZarzad => "role.technik_zarzad",
+            Role::GumisOdBotow => "role.gumis_od_botow",
+            Role::Opiekun => "role.opiekun",
+            Role::HeadAdmin => "role.head_admin",
+            Role::Admin => "role.admin",
+            Role::HeadModerator => "role.head_moderator",
+            Role::Moderator => "role.moderator",
+            Role::TestModerator => "role.test_moderator",
+        }
+    }
+
+    /// ID roli w gildii (0 = nie ustawiono).
+    pub fn id(self, gid: u64) -> u64 {
+        registry::resolve(gid, self.registry_key())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Permission {
     Admcheck,
+    Altguard,
+    Appeals,
     Ban,
+    Case,
+    CaseDelete,
     Idguard,
     Kick,
     Mdel,
     Mute,
     MuteConfig,
+    Perms,
     Punkty,
+    PunktyAdjust,
+    Raid,
+    Registry,
+    Review,
     SlashClean,
     SlashResync,
     Teach,
+    Unban,
     Unmute,
     User,
     VerifyPanel,
     Warn,
+    WarnConfig,
     WarnRemove,
     Warns,
 }
 
+impl Permission {
+    pub const ALL: [Permission; 28] = [
+        Permission::Admcheck,
+        Permission::Altguard,
+        Permission::Appeals,
+        Permission::Ban,
+        Permission::Case,
+        Permission::CaseDelete,
+        Permission::Idguard,
+        Permission::Kick,
+        Permission::Mdel,
+        Permission::Mute,
+        Permission::MuteConfig,
+        Permission::Perms,
+        Permission::Punkty,
+        Permission::PunktyAdjust,
+        Permission::Raid,
+        Permission::Registry,
+        Permission::Review,
+        Permission::SlashClean,
+        Permission::SlashResync,
+        Permission::Teach,
+        Permission::Unban,
+        Permission::Unmute,
+        Permission::User,
+        Permission::VerifyPanel,
+        Permission::Warn,
+        Permission::WarnConfig,
+        Permission::WarnRemove,
+        Permission::Warns,
+    ];
+
+    /// Nazwa capability w tss.role_capabilities.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::Admcheck => "admcheck",
+            Permission::Altguard => "altguard",
+            Permission::Appeals => "appeals",
+            Permission::Ban => "ban",
+            Permission::Case => "case",
+            Permission::CaseDelete => "case-delete",
+            Permission::Idguard => "idguard",
+            Permission::Kick => "kick",
+            Permission::Mdel => "mdel",
+            Permission::Mute => "mute",
+            Permission::MuteConfig => "mute-config",
+            Permission::Perms => "perms",
+            Permission::Punkty => "punkty",
+            Permission::PunktyAdjust => "punkty-adjust",
+            Permission::Raid => "raid",
+            Permission::Registry => "registry",
+            Permission::Review => "review",
+            Permission::SlashClean => "slash-clean",
+            Permission::SlashResync => "slash-resync",
+            Permission::Teach => "teach",
+            Permission::Unban => "unban",
+            Permission::Unmute => "unmute",
+            Permission::User => "user",
+            Permission::VerifyPanel => "verify-panel",
+            Permission::Warn => "warn",
+            Permission::WarnConfig => "warn-config",
+            Permission::WarnRemove => "warn-remove",
+            Permission::Warns => "warns",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Permission::Admcheck => "/admcheck",
+            Permission::Altguard => "/altguard",
+            Permission::Appeals => "rozpatrywanie odwołań",
+            Permission::Ban => "/ban",
+            Permission::Case => "/case",
+            Permission::CaseDelete => "/case delete",
+            Permission::Idguard => "/idguard",
+            Permission::Kick => "/kick",
+            Permission::Mdel => "/mdel",
+            Permission::Mute => "/mute",
+            Permission::MuteConfig => "/mute-config",
+            Permission::Perms => "/perms",
+            Permission::Punkty => "/punkty",
+            Permission::PunktyAdjust => "ręczna korekta punktów",
+            Permission::Raid => "/raid",
+            Permission::Registry => "/registry",
+            Permission::Review => "przyciski weryfikacji (IdGuard, AltGuard, Fotosystem)",
+            Permission::SlashClean => "/slash-clean",
+            Permission::SlashResync => "/slash-resync",
+            Permission::Teach => "/teach",
+            Permission::Unban => "/unban",
+            Permission::Unmute => "/unmute",
+            Permission::User => "/user",
+            Permission::VerifyPanel => "/verify-panel",
+            Permission::Warn => "/warn",
+            Permission::WarnConfig => "/warn-config",
+            Permission::WarnRemove => "/warn-remove",
+            Permission::Warns => "/warns",
+        }
+    }
+
+    /// Slash-komenda, której widoczność (ACL Discorda) wynika z tej capability.
+    pub fn command(self) -> Option<&'static str> {
+        match self {
+            Permission::Appeals
+            | Permission::CaseDelete
+            | Permission::PunktyAdjust
+            | Permission::Review => None,
+            p => Some(p.as_str()),
+        }
+    }
+}
+
+/// Domyślne przypisania (gdy gildia nie skonfigurowała capability przez /perms).
 pub static PERMISSIONS: Lazy<HashMap<Permission, Vec<Role>>> = Lazy::new(|| {
     use Permission::*;
     use Role::*;
+
+    let tm_plus = vec![
+        TestModerator, Moderator, HeadModerator, Admin, HeadAdmin,
+        Opiekun, TechnikZarzad, WspolWlasciciel, Wlasciciel, GumisOdBotow,
+    ];
+    let mo_plus = vec![
+        Moderator, HeadModerator, Admin, HeadAdmin,
+        Opiekun, TechnikZarzad, WspolWlasciciel, Wlasciciel, GumisOdBotow,
+    ];
+    let ad_plus = vec![Admin, HeadAdmin, Opiekun, TechnikZarzad, WspolWlasciciel, Wlasciciel, GumisOdBotow];
+    let op_plus = vec![Opiekun, TechnikZarzad, WspolWlasciciel, Wlasciciel, GumisOdBotow];
+    let tz_plus = vec![TechnikZarzad, WspolWlasciciel, Wlasciciel, GumisOdBotow];
+
     HashMap::from([
-        (Admcheck, vec![Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (Ban, vec![HeadAdmin, Admin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (Idguard, vec![TechnikZarzad]),
-        (Kick, vec![Admin, HeadAdmin, HeadModerator]),
-        (Mdel, vec![Moderator, HeadModerator, Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (Mute, vec![TestModerator, Moderator, HeadModerator, Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (MuteConfig, vec![TechnikZarzad]),
-        (Punkty, vec![Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (SlashClean, vec![TechnikZarzad]),
-        (SlashResync, vec![TechnikZarzad]),
-        (Teach, vec![TechnikZarzad]),
-        (Unmute, vec![HeadModerator, Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (User, vec![Moderator, HeadModerator, Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (VerifyPanel, vec![TechnikZarzad]),
-        (Warn, vec![TestModerator, Moderator, HeadModerator, Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (WarnRemove, vec![Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
-        (Warns, vec![Moderator, HeadModerator, Admin, HeadAdmin, Opiekun, Wlasciciel, WspolWlasciciel, TechnikZarzad]),
+        (Warn, tm_plus.clone()),
+        (Warns, tm_plus.clone()),
+        (User, tm_plus.clone()),
+        (Case, tm_plus.clone()),
+        (Review, tm_plus),
+        (Mute, mo_plus),
+        (WarnRemove, ad_plus.clone()),
+        (Unmute, ad_plus.clone()),
+        (Kick, ad_plus.clone()),
+        (Mdel, ad_plus.clone()),
+        (Ban, ad_plus.clone()),
+        (Unban, ad_plus.clone()),
+        (CaseDelete, ad_plus.clone()),
+        (Raid, ad_plus.clone()),
+        (Appeals, ad_plus),
+        (Punkty, op_plus.clone()),
+        (Admcheck, op_plus),
+        (PunktyAdjust, vec![Wlasciciel, Opiekun]),
+        (SlashClean, tz_plus.clone()),
+        (SlashResync, tz_plus.clone()),
+        (Teach, tz_plus.clone()),
+        (MuteConfig, tz_plus.clone()),
+        (VerifyPanel, tz_plus.clone()),
+        (WarnConfig, tz_plus.clone()),
+        (Idguard, tz_plus.clone()),
+        (Altguard, tz_plus.clone()),
+        (Registry, tz_plus.clone()),
+        (Perms, tz_plus),
     ])
 });
 
@@ -64,4 +254,158 @@ pub fn role_has_permission(role: Role, permission: Permission) -> bool {
         .get(&permission)
         .map(|roles| roles.contains(&role))
         .unwrap_or(false)
-}
\ No newline at end of file
+}
+
+/* ==========================================
+   tss.role_capabilities
+   ========================================== */
+
+// Wiersz z role_id = 0 oznacza „capability skonfigurowana w bazie” – dzięki niemu
+// odebranie ostatniej roli nie przywraca po cichu szablonu.
+const CONFIGURED_MARKER: u64 = 0;
+
+/// guild_id → capability → role z bazy (ładowane w guild_create)
+static GRANTS: Lazy<DashMap<u64, HashMap<Permission, HashSet<u64>>>> = Lazy::new(DashMap::new);
+
+/// Załaduj capabilities gildii do cache.
+pub async fn warmup(db: &Pool<Postgres>, gid: u64) -> Result<()> {
+    let rows = sqlx::query("SELECT role_id, capability FROM tss.role_capabilities WHERE guild_id = $1")
+        .bind(gid as i64)
+        .fetch_all(db)
+        .await?;
+
+    let mut map: HashMap<Permission, HashSet<u64>> = HashMap::new();
+    for r in rows {
+        let cap: String = r.get("capability");
+        let Some(perm) = Permission::parse(&cap) else {
+            tracing::warn!(gid, capability=%cap, "unknown capability in tss.role_capabilities");
+            continue;
+        };
+        map.entry(perm).or_default().insert(r.get::<i64, _>("role_id") as u64);
+    }
+    GRANTS.insert(gid, map);
+    Ok(())
+}
+
+/// Czy capability ma własną konfigurację w bazie (a nie szablon).
+pub fn is_configured(gid: u64, perm: Permission) -> bool {
+    GRANTS.get(&gid).is_some_and(|m| m.contains_key(&perm))
+}
+
+/// Role uprawnione do capability w gildii (bez zer).
+pub fn roles_for(gid: u64, perm: Permission) -> Vec<u64> {
+    let mut out: Vec<u64> = match GRANTS.get(&gid).and_then(|m| m.get(&perm).cloned()) {
+        Some(set) => set.into_iter().collect(),
+        None => default_roles(gid, perm),
+    };
+    out.retain(|&r| r != CONFIGURED_MARKER);
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+fn default_roles(gid: u64, perm: Permission) -> Vec<u64> {
+    PERMISSIONS
+        .get(&perm)
+        .map(|roles| roles.iter().map(|r| r.id(gid)).filter(|&id| id != 0).collect())
+        .unwrap_or_default()
+}
+
+/// Nadaj capability roli. Pierwsza zmiana przepisuje szablon do bazy – razem z nadaniem
+/// w jednej transakcji; cache zmieniamy dopiero po commicie.
+pub async fn grant(db: &Pool<Postgres>, gid: u64, role_id: u64, perm: Permission) -> Result<bool> {
+    let mut tx = db.begin().await?;
+    let template = materialize(&mut tx, gid, perm).await?;
+    let added = insert_row(&mut tx, gid, role_id, perm).await?;
+    tx.commit().await?;
+
+    let mut m = GRANTS.entry(gid).or_default();
+    let set = m.entry(perm).or_default();
+    set.extend(template);
+    set.insert(role_id);
+    Ok(added)
+}
+
+/// Odbierz capability roli. Pierwsza zmiana przepisuje szablon do bazy (jedna transakcja).
+pub async fn revoke(db: &Pool<Postgres>, gid: u64, role_id: u64, perm: Permission) -> Result<bool> {
+    let mut tx = db.begin().await?;
+    let template = materialize(&mut tx, gid, perm).await?;
+    let res = sqlx::query(
+        "DELETE FROM tss.role_capabilities WHERE guild_id = $1 AND role_id = $2 AND capability = $3",
+    )
+    .bind(gid as i64)
+    .bind(role_id as i64)
+    .bind(perm.as_str())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let mut m = GRANTS.entry(gid).or_default();
+    let set = m.entry(perm).or_default();
+    set.extend(template);
+    set.remove(&role_id);
+    Ok(res.rows_affected() > 0)
+}
+
+/// Przepisz szablon capability do bazy (w transakcji wołającego). Zwraca zapisane role
+/// (z markerem) do wstawienia w cache po commicie; pusto, gdy już skonfigurowana.
+async fn materialize(tx: &mut Transaction<'_, Postgres>, gid: u64, perm: Permission) -> Result<Vec<u64>> {
+    if is_configured(gid, perm) {
+        return Ok(Vec::new());
+    }
+    let mut roles = default_roles(gid, perm);
+    roles.push(CONFIGURED_MARKER);
+    for rid in &roles {
+        insert_row(tx, gid, *rid, perm).await?;
+    }
+    Ok(roles)
+}
+
+async fn insert_row(tx: &mut Transaction<'_, Postgres>, gid: u64, role_id: u64, perm: Permission) -> Result<bool> {
+    let res = sqlx::query(
+        r#"
+        INSERT INTO tss.role_capabilities (guild_id, role_id, capability)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id, role_id, capability) DO NOTHING
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(role_id as i64)
+    .bind(perm.as_str())
+    .execute(&mut **tx)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/* ==========================================
+   authorize
+   ========================================== */
+
+/// Członek z interakcji (Discord dołącza rozwiązane uprawnienia, więc Administrator
+/// i właściciel serwera są widoczni bez dodatkowych zapytań). Brak członka (DM) = odmowa.
+pub fn authorize(guild_id: GuildId, member: Option<&Member>, perm: Permission) -> bool {
+    let Some(member) = member else { return false; };
+    if member.permissions.is_some_and(|p| p.administrator()) {
+        return true;
+    }
+    has_role_for(guild_id.get(), &member.roles, perm)
+}
+
+/// Wariant dla samego ID (np. moderator zapisany w sesji panelu) – pobiera członka i gildię.
+pub async fn authorize_user(ctx: &Context, guild_id: GuildId, user_id: UserId, perm: Permission) -> bool {
+    let Ok(member) = guild_id.member(&ctx.http, user_id).await else { return false; };
+    if has_role_for(guild_id.get(), &member.roles, perm) {
+        return true;
+    }
+    let Ok(guild) = guild_id.to_partial_guild(&ctx.http).await else { return false; };
+    guild.owner_id == user_id
+        || member
+            .roles
+            .iter()
+            .any(|rid| guild.roles.get(rid).is_some_and(|r| r.permissions.administrator()))
+}
+
+fn has_role_for(gid: u64, member_roles: &[serenity::all::RoleId], perm: Permission) -> bool {
+    let allowed = roles_for(gid, perm);
+    member_roles.iter().any(|r| allowed.contains(&r.get()))
+}
@@ -0,0 +1,242 @@
+// src/perms_admin.rs
+//
+// /perms – przypisywanie capabilities rolom (tss.role_capabilities).
+// Pierwszy grant/revoke danej capability przepisuje szablon z permissions.rs do bazy,
+// więc zmiana jest zawsze przyrostowa względem tego, co pokazuje `show`.
+
+use anyhow::Result;
+use serenity::all::*;
+
+use crate::discord::router::Routes;
+use crate::{
+    command_acl,
+    permissions::{self, Permission},
+    AppContext,
+};
+
+const SYSTEM_NAME: &str = "Tigris Permissions";
+
+pub struct Perms;
+
+impl Perms {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["perms"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        let cap_opt = |required: bool| {
+            CreateCommandOption::new(CommandOptionType::String, "capability", "Uprawnienie (np. ban, warn-remove)")
+                .required(required)
+                .set_autocomplete(true)
+        };
+        let role_opt = |required: bool| {
+            CreateCommandOption::new(CommandOptionType::Role, "role", "Rola").required(required)
+        };
+
+        vec![
+            CreateCommand::new("perms")
+                .description("Uprawnienia ról do komend bota")
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "grant", "Nadaj roli uprawnienie")
+                        .add_sub_option(role_opt(true))
+                        .add_sub_option(cap_opt(true)),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "revoke", "Odbierz roli uprawnienie")
+                        .add_sub_option(role_opt(true))
+                        .add_sub_option(cap_opt(true)),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Pokaż uprawnienia")
+                        .add_sub_option(cap_opt(false))
+                        .add_sub_option(role_opt(false)),
+                )
+                .default_member_permissions(Permissions::ADMINISTRATOR),
+        ]
+    }
+
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        if let Some(ac) = interaction.clone().autocomplete() {
+            if ac.data.name == "perms"
+                && let Err(e) = handle_capability_autocomplete(ctx, &ac).await
+            {
+                tracing::warn!(?e, "perms autocomplete failed");
+            }
+            return;
+        }
+
+        let Some(cmd) = interaction.command() else { return; };
+        if cmd.data.name.as_str() != "perms" { return; }
+        if let Err(e) = handle_perms(ctx, app, &cmd).await {
+            tracing::warn!(?e, "perms command failed");
+        }
+    }
+}
+
+async fn handle_perms(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Perms) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień do zarządzania uprawnieniami.").await;
+    }
+    let Some(sc) = cmd.data.options.first() else {
+        return edit_ephemeral(ctx, cmd, "Wybierz podkomendę.").await;
+    };
+
+    let mut role: Option<RoleId> = None;
+    let mut capability: Option<String> = None;
+    if let CommandDataOptionValue::SubCommand(params) = &sc.value {
+        for p in params {
+            match (p.name.as_str(), &p.value) {
+                ("role", CommandDataOptionValue::Role(r)) => role = Some(*r),
+                ("capability", CommandDataOptionValue::String(s)) => capability = Some(s.clone()),
+                _ => {}
+            }
+        }
+    }
+    let perm = match capability.as_deref() {
+        Some(raw) => match Permission::parse(raw) {
+            Some(p) => Some(p),
+            None => return edit_ephemeral(ctx, cmd, "Nieznane uprawnienie – wybierz z listy podpowiedzi.").await,
+        },
+        None => None,
+    };
+
+    match sc.name.as_str() {
+        "grant" | "revoke" => {
+            let (Some(role), Some(perm)) = (role, perm) else {
+                return edit_ephemeral(ctx, cmd, "Podaj rolę i uprawnienie.").await;
+            };
+            let granting = sc.name == "grant";
+            let changed = if granting {
+                permissions::grant(&app.db, gid.get(), role.get(), perm).await?
+            } else {
+                permissions::revoke(&app.db, gid.get(), role.get(), perm).await?
+            };
+            tracing::info!(
+                gid = gid.get(),
+                role = role.get(),
+                capability = perm.as_str(),
+                grant = granting,
+                changed,
+                by = cmd.user.id.get(),
+                "role capability changed"
+            );
+
+            // ACL komend Discorda wynika z tych samych danych
+            if changed
+                && perm.command().is_some()
+                && let Err(e) = command_acl::apply_permissions(ctx, gid).await
+            {
+                tracing::warn!(error=?e, gid = gid.get(), "apply_permissions after /perms failed");
+            }
+
+            let msg = match (granting, changed) {
+                (true, true) => format!("✅ <@&{}> ma teraz `{}` ({}).", role.get(), perm.as_str(), perm.label()),
+                (true, false) => format!("<@&{}> już ma `{}`.", role.get(), perm.as_str()),
+                (false, true) => format!("🗑️ <@&{}> nie ma już `{}` ({}).", role.get(), perm.as_str(), perm.label()),
+                (false, false) => format!("<@&{}> nie miała `{}`.", role.get(), perm.as_str()),
+            };
+            edit_ephemeral(ctx, cmd, &msg).await
+        }
+        "show" => {
+            let e = match (perm, role) {
+                (Some(perm), _) => show_capability(gid, perm),
+                (None, Some(role)) => show_role(gid, role),
+                (None, None) => show_all(gid),
+            };
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(e))
+                .await?;
+            Ok(())
+        }
+        _ => edit_ephemeral(ctx, cmd, "Nieznana podkomenda.").await,
+    }
+}
+
+fn show_capability(gid: GuildId, perm: Permission) -> CreateEmbed {
+    let roles = permissions::roles_for(gid.get(), perm);
+    base_embed(format!("🔐 {}", perm.as_str()))
+        .field("Dotyczy", perm.label(), true)
+        .field("Źródło", source_label(gid, perm), true)
+        .field("Role", role_list(&roles), false)
+}
+
+fn show_role(gid: GuildId, role: RoleId) -> CreateEmbed {
+    let caps: Vec<String> = Permission::ALL
+        .into_iter()
+        .filter(|p| permissions::roles_for(gid.get(), *p).contains(&role.get()))
+        .map(|p| format!("`{}`", p.as_str()))
+        .collect();
+    let desc = if caps.is_empty() { "— (brak)".to_string() } else { caps.join(", ") };
+    base_embed("🔐 Uprawnienia roli".to_string()).description(format!("<@&{}>\n\n{desc}", role.get()))
+}
+
+fn show_all(gid: GuildId) -> CreateEmbed {
+    let mut desc = String::new();
+    for p in Permission::ALL {
+        let roles = permissions::roles_for(gid.get(), p);
+        let src = if permissions::is_configured(gid.get(), p) { "" } else { " · szablon" };
+        desc.push_str(&format!("`{}` → {}{}\n", p.as_str(), role_list(&roles), src));
+    }
+    if desc.len() > 4000 {
+        desc.truncate(3990);
+        desc.push('…');
+    }
+    base_embed("🔐 Uprawnienia komend".to_string())
+        .description(desc)
+        .field("Zawsze", "Właściciel serwera i role z uprawnieniem Administrator", false)
+}
+
+async fn handle_capability_autocomplete(ctx: &Context, ac: &CommandInteraction) -> Result<()> {
+    let needle = ac
+        .data
+        .autocomplete()
+        .map(|o| o.value.trim().to_lowercase())
+        .unwrap_or_default();
+
+    let choices = Permission::ALL
+        .into_iter()
+        .filter(|p| p.as_str().contains(&needle))
+        .take(25)
+        .map(|p| AutocompleteChoice::new(format!("{} ({})", p.as_str(), p.label()), p.as_str()))
+        .collect();
+
+    ac.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Autocomplete(CreateAutocompleteResponse::new().set_choices(choices)),
+    )
+    .await?;
+    Ok(())
+}
+
+/* ========================= Helpers ========================= */
+
+fn base_embed(title: String) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .colour(Colour::new(0x5865F2))
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+}
+
+fn source_label(gid: GuildId, perm: Permission) -> &'static str {
+    if permissions::is_configured(gid.get(), perm) { "baza (/perms)" } else { "szablon" }
+}
+
+fn role_list(roles: &[u64]) -> String {
+    if roles.is_empty() {
+        return "— (brak)".into();
+    }
+    roles.iter().map(|r| format!("<@&{r}>")).collect::<Vec<_>>().join(" ")
+}
+
+async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,738 @@
+// src/raid.rs
+//
+// Tryb raidu: gdy AltGuard widzi falę wejść (≥ `raidaware_join_per_60s` w 60 s), zamykamy serwer –
+// wyższy poziom weryfikacji gildii, wstrzymany panel weryfikacji, slowmode na czatach publicznych
+// i kwarantanna każdego nowego członka. Po ciszy (`raid.quiet_minutes` bez wejść) albo `/raid end`
+// przywracamy poprzedni stan. Każdy krok trafia na kanał logów AltGuard.
+// Co przywrócić trzymamy w tss.raid_state – restart bota nie zostawia serwera zamkniętego.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use dashmap::{mapref::entry::Entry, DashMap};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serenity::all::*;
+use sqlx::{Pool, Postgres, Row};
+
+use crate::altguard::AltGuard;
+use crate::discord::router::Routes;
+use crate::{
+    permissions::{self, Permission},
+    registry::{env_channels, env_roles},
+    AppContext,
+};
+
+const BRAND_FOOTER: &str = "Tigris Security System™ • AltGuard";
+/// Poziom weryfikacji na czas raidu (zweryfikowany telefon).
+const RAID_LEVEL: VerificationLevel = VerificationLevel::Higher;
+/// Co ile watcher sprawdza, czy już jest cisza.
+const WATCH_TICK: Duration = Duration::from_secs(30);
+
+/// Co zmieniliśmy na serwerze – do przywrócenia (tss.raid_state).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Lockdown {
+    prev_verification: Option<VerificationLevel>,
+    slowmode: Vec<(u64, u16)>, // (kanał, poprzedni slowmode w s)
+    verify_paused: bool,
+}
+
+impl Lockdown {
+    /// Nic już nie trzeba przywracać.
+    fn is_restored(&self) -> bool {
+        self.prev_verification.is_none() && self.slowmode.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// `start` jeszcze zamyka serwer – `/raid end` czeka na koniec.
+    Starting,
+    Active,
+    /// `end` przywraca serwer.
+    Ending,
+}
+
+struct RaidState {
+    started_unix: i64,
+    last_join: Instant,
+    quarantined: u32,
+    lockdown: Lockdown,
+    phase: Phase,
+}
+
+static ACTIVE: Lazy<DashMap<u64, RaidState>> = Lazy::new(DashMap::new);
+
+pub fn is_active(gid: u64) -> bool {
+    ACTIVE.contains_key(&gid)
+}
+
+/// Panel weryfikacji odrzuca kliknięcia do końca raidu.
+pub fn verify_paused(gid: u64) -> bool {
+    ACTIVE.get(&gid).is_some_and(|s| s.lockdown.verify_paused)
+}
+
+/// Wynik `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOutcome {
+    Ended,
+    /// Część ustawień nie wróciła – raid trwa z tym, co zostało do przywrócenia.
+    Incomplete,
+    NotActive,
+    /// Raid właśnie się włącza albo już kończy.
+    Busy,
+}
+
+/// Co `end` ma przywrócić (wpis zostaje w ACTIVE w fazie `Ending`).
+struct Claimed {
+    started_unix: i64,
+    quarantined: u32,
+    lockdown: Lockdown,
+}
+
+pub struct Raid;
+
+impl Raid {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["raid"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new("raid")
+                .description("Tryb raidu – lockdown serwera")
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "status", "Czy trwa raid"))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "start", "Włącz tryb raidu ręcznie"))
+                .add_option(CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "end",
+                    "Zakończ raid i przywróć ustawienia serwera",
+                )),
+        ]
+    }
+
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        let Some(cmd) = interaction.command() else { return; };
+        if cmd.data.name.as_str() != "raid" { return; }
+        if let Err(e) = handle_raid(ctx, app, &cmd).await {
+            tracing::warn!(?e, "raid command failed");
+        }
+    }
+}
+
+async fn handle_raid(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Raid) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień do /raid.").await;
+    }
+
+    let gid = gid.get();
+    let by = cmd.user.id.get();
+    let msg = match cmd.data.options.first().map(|o| o.name.as_str()) {
+        Some("status") => status_text(gid, app.altguard().config(gid).raid.quiet_minutes),
+        Some("start") => {
+            if start(ctx, app, gid, &format!("ręcznie: <@{by}>")).await {
+                "🚨 Tryb raidu włączony – kolejne kroki na kanale logów AltGuard.".to_string()
+            } else {
+                "Tryb raidu już trwa.".to_string()
+            }
+        }
+        Some("end") => match end(ctx, &app.db, gid, &format!("ręcznie: <@{by}>")).await {
+            EndOutcome::Ended => "✅ Tryb raidu zakończony – ustawienia serwera przywrócone.".to_string(),
+            EndOutcome::Incomplete => {
+                "⚠️ Nie wszystko udało się przywrócić – szczegóły na kanale logów, raid trwa dalej.".to_string()
+            }
+            EndOutcome::NotActive => "Tryb raidu nie jest aktywny.".to_string(),
+            EndOutcome::Busy => "Tryb raidu właśnie się włącza albo kończy – spróbuj za chwilę.".to_string(),
+        },
+        _ => "Nieznana podkomenda.".to_string(),
+    };
+    edit_ephemeral(ctx, cmd, &msg).await
+}
+
+fn status_text(gid: u64, quiet_minutes: u32) -> String {
+    let Some(s) = ACTIVE.get(&gid) else {
+        return "Brak aktywnego raidu.".to_string();
+    };
+    format!(
+        "🚨 Raid trwa od <t:{}:R>.\nKwarantanna: **{}** nowych członków • ostatnie wejście {} s temu.\n\
+         Koniec po {} min bez wejść albo przez `/raid end`.",
+        s.started_unix,
+        s.quarantined,
+        s.last_join.elapsed().as_secs(),
+        quiet_minutes
+    )
+}
+
+/* ========================= Wejścia ========================= */
+
+/// Wejście członka (po `AltGuard::record_join`): start raidu po przekroczeniu progu,
+/// a w trakcie raidu – kwarantanna i odświeżenie licznika ciszy.
+pub async fn on_join(ctx: &Context, app: &AppContext, member: &Member) {
+    let gid = member.guild_id.get();
+    if !is_active(gid) {
+        let ag = app.altguard();
+        let cfg = ag.config(gid);
+        if !cfg.enabled || !cfg.raid.lockdown {
+            return;
+        }
+        let joins = ag.joins_last_60s(gid).await;
+        if joins < cfg.raidaware_join_per_60s {
+            return;
+        }
+        start(ctx, app, gid, &format!("automatycznie: {joins} wejść w 60 s")).await;
+    }
+
+    match ACTIVE.get_mut(&gid) {
+        Some(mut s) => s.last_join = Instant::now(),
+        None => return,
+    }
+    let role = env_roles::quarantine_id(gid);
+    if member.user.bot || role == 0 {
+        return; // brak roli zgłaszamy raz, przy starcie
+    }
+    let uid = member.user.id;
+    match ctx
+        .http
+        .add_member_role(member.guild_id, uid, RoleId::new(role), Some("AltGuard: tryb raidu – kwarantanna"))
+        .await
+    {
+        Ok(()) => {
+            if let Some(mut s) = ACTIVE.get_mut(&gid) {
+                s.quarantined += 1;
+            }
+        }
+        Err(e) => tracing::warn!(error=?e, gid, uid = uid.get(), "raid quarantine failed"),
+    }
+}
+
+/* ========================= Start / koniec ========================= */
+
+/// Włącza tryb raidu i po kolei zamyka serwer; `false`, gdy raid już trwa.
+/// Stan trafia do tss.raid_state przed pierwszą zmianą i po każdym kroku.
+pub async fn start(ctx: &Context, app: &AppContext, gid: u64, cause: &str) -> bool {
+    let policy = app.altguard().config(gid).raid;
+    // wpis od razu: równoległe wejścia nie odpalą drugiego startu, a panel staje natychmiast
+    if !begin(gid, chrono::Utc::now().timestamp(), policy.pause_verify) {
+        return false;
+    }
+    persist(&app.db, gid).await;
+    tracing::warn!(gid, cause, "raid mode started");
+    report(ctx, gid, "🚨 Tryb raidu włączony", cause, Colour::RED, true).await;
+
+    let guild = GuildId::new(gid);
+    let reason = "AltGuard: tryb raidu";
+
+    // 1) poziom weryfikacji gildii
+    if policy.verification {
+        let step = match raise_verification(ctx, guild, reason).await {
+            Ok(Some(prev)) => {
+                if let Some(mut s) = ACTIVE.get_mut(&gid) {
+                    s.lockdown.prev_verification = Some(prev);
+                }
+                persist(&app.db, gid).await;
+                format!("{} → **{}**", level_label(prev), level_label(RAID_LEVEL))
+            }
+            Ok(None) => "Poziom już jest co najmniej taki jak w trybie raidu – bez zmian.".to_string(),
+            Err(e) => {
+                tracing::warn!(error=?e, gid, "raid: verification level change failed");
+                format!("Nie udało się podnieść poziomu: {e}")
+            }
+        };
+        report(ctx, gid, "🔒 Poziom weryfikacji", &step, Colour::ORANGE, false).await;
+    }
+
+    // 2) panel weryfikacji (flaga ustawiona już przy wpisie)
+    if policy.pause_verify {
+        report(
+            ctx,
+            gid,
+            "⏸️ Panel weryfikacji wstrzymany",
+            "Kliknięcia „Akceptuję regulamin” są odrzucane do końca raidu.",
+            Colour::ORANGE,
+            false,
+        )
+        .await;
+    }
+
+    // 3) slowmode na czatach publicznych
+    if policy.slowmode_secs > 0 {
+        let (changed, failed) = apply_slowmode(ctx, gid, policy.slowmode_secs, reason).await;
+        let mut step = if changed.is_empty() {
+            "Żaden kanał nie wymagał zmiany.".to_string()
+        } else {
+            format!(
+                "{} s na: {}",
+                policy.slowmode_secs,
+                changed.iter().map(|(id, _)| format!("<#{id}>")).collect::<Vec<_>>().join(", ")
+            )
+        };
+        if !failed.is_empty() {
+            step.push_str(&format!(
+                "\nNie udało się: {}",
+                failed.iter().map(|id| format!("<#{id}>")).collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if let Some(mut s) = ACTIVE.get_mut(&gid) {
+            s.lockdown.slowmode = changed;
+        }
+        persist(&app.db, gid).await;
+        report(ctx, gid, "🐢 Slowmode", &step, Colour::ORANGE, false).await;
+    }
+
+    // 4) kwarantanna nowych członków (on_join)
+    let role = env_roles::quarantine_id(gid);
+    let step = if role != 0 {
+        format!("Każdy nowy członek dostaje <@&{role}> do końca raidu i czeka na decyzję staffu.")
+    } else {
+        "⚠️ Brak roli `role.quarantine` w /registry – nowi członkowie **nie** są izolowani.".to_string()
+    };
+    report(ctx, gid, "🚧 Kwarantanna nowych członków", &step, Colour::ORANGE, false).await;
+
+    if let Some(mut s) = ACTIVE.get_mut(&gid) {
+        s.phase = Phase::Active;
+    }
+    spawn_watcher(ctx.clone(), app.db.clone(), app.altguard(), gid);
+    true
+}
+
+/// Kończy raid i przywraca serwer. Wpis (i wiersz w tss.raid_state) znika dopiero, gdy
+/// wszystko wróciło; nieudane kroki zostają do kolejnej próby po następnej ciszy.
+pub async fn end(ctx: &Context, db: &Pool<Postgres>, gid: u64, cause: &str) -> EndOutcome {
+    let state = match claim_end(gid) {
+        Ok(s) => s,
+        Err(outcome) => return outcome,
+    };
+    tracing::info!(gid, cause, quarantined = state.quarantined, "raid mode ending");
+    let guild = GuildId::new(gid);
+    let reason = "AltGuard: koniec trybu raidu";
+    let mut remaining = Lockdown::default();
+
+    if let Some(prev) = state.lockdown.prev_verification {
+        let step = match guild
+            .edit(&ctx.http, EditGuild::new().verification_level(prev).audit_log_reason(reason))
+            .await
+        {
+            Ok(_) => format!("Przywrócono: **{}**", level_label(prev)),
+            Err(e) => {
+                tracing::warn!(error=?e, gid, "raid: verification level restore failed");
+                remaining.prev_verification = Some(prev);
+                format!("Nie udało się przywrócić ({}): {e}", level_label(prev))
+            }
+        };
+        report(ctx, gid, "🔓 Poziom weryfikacji", &step, Colour::DARK_GREEN, false).await;
+    }
+
+    if state.lockdown.verify_paused {
+        report(ctx, gid, "▶️ Panel weryfikacji wznowiony", "Weryfikacja działa normalnie.", Colour::DARK_GREEN, false)
+            .await;
+    }
+
+    if !state.lockdown.slowmode.is_empty() {
+        let mut lines = Vec::with_capacity(state.lockdown.slowmode.len());
+        for &(id, prev) in &state.lockdown.slowmode {
+            let res = ChannelId::new(id)
+                .edit(&ctx.http, EditChannel::new().rate_limit_per_user(prev).audit_log_reason(reason))
+                .await;
+            match res {
+                Ok(_) => lines.push(format!("<#{id}> → {prev} s")),
+                Err(e) => {
+                    tracing::warn!(error=?e, gid, channel = id, "raid: slowmode restore failed");
+                    remaining.slowmode.push((id, prev));
+                    lines.push(format!("<#{id}> – nie udało się: {e}"));
+                }
+            }
+        }
+        report(ctx, gid, "🐇 Slowmode przywrócony", &lines.join("\n"), Colour::DARK_GREEN, false).await;
+    }
+
+    if !settle_end(gid, remaining) {
+        persist(db, gid).await;
+        report(
+            ctx,
+            gid,
+            "⚠️ Raid nie zakończony",
+            "Nie wszystko udało się przywrócić – raid trwa. Kolejna próba po następnej ciszy albo przez `/raid end`.",
+            Colour::ORANGE,
+            false,
+        )
+        .await;
+        return EndOutcome::Incomplete;
+    }
+
+    if let Err(e) = delete_state(db, gid).await {
+        tracing::warn!(error=?e, gid, "raid state delete failed");
+    }
+    tracing::info!(gid, cause, "raid mode ended");
+
+    let minutes = (chrono::Utc::now().timestamp() - state.started_unix).max(0) / 60;
+    let summary = format!(
+        "{cause}\nCzas trwania: {minutes} min • w kwarantannie: **{}**.\n\
+         Osoby w kwarantannie czekają na ręczną decyzję staffu.",
+        state.quarantined
+    );
+    report(ctx, gid, "✅ Tryb raidu zakończony", &summary, Colour::DARK_GREEN, false).await;
+    EndOutcome::Ended
+}
+
+/// guild_create: raid przerwany restartem wraca do pamięci; ciszę liczymy od teraz.
+pub async fn resume(ctx: &Context, app: &AppContext, gid: u64) -> Result<bool> {
+    if is_active(gid) {
+        return Ok(false);
+    }
+    let Some((lockdown, started_unix)) = load_state(&app.db, gid).await? else {
+        return Ok(false);
+    };
+    if !restore_entry(gid, lockdown, started_unix) {
+        return Ok(false);
+    }
+    spawn_watcher(ctx.clone(), app.db.clone(), app.altguard(), gid);
+    report(
+        ctx,
+        gid,
+        "🚨 Tryb raidu wznowiony",
+        "Bot został zrestartowany w trakcie raidu – lockdown trwa dalej.",
+        Colour::ORANGE,
+        false,
+    )
+    .await;
+    Ok(true)
+}
+
+/// Kończy raid po `quiet_minutes` bez wejść. Znika, gdy raid zakończono ręcznie.
+fn spawn_watcher(ctx: Context, db: Pool<Postgres>, ag: Arc<AltGuard>, gid: u64) {
+    let Some(started) = ACTIVE.get(&gid).map(|s| s.started_unix) else { return; };
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(WATCH_TICK).await;
+            let Some(idle) = watched_idle(gid, started) else { return; };
+            let quiet_minutes = ag.config(gid).raid.quiet_minutes.max(1);
+            if idle >= Duration::from_secs(quiet_minutes as u64 * 60) {
+                let cause = format!("automatycznie: {quiet_minutes} min bez nowych wejść");
+                // Incomplete/Busy: wpis został, próbujemy po kolejnej ciszy
+                if end(&ctx, &db, gid, &cause).await == EndOutcome::Ended {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/* ========================= Przejścia stanu (ACTIVE) ========================= */
+
+/// Nowy raid w fazie `Starting`; `false`, gdy gildia ma już wpis.
+fn begin(gid: u64, started_unix: i64, verify_paused: bool) -> bool {
+    match ACTIVE.entry(gid) {
+        Entry::Occupied(_) => false,
+        Entry::Vacant(v) => {
+            v.insert(RaidState {
+                started_unix,
+                last_join: Instant::now(),
+                quarantined: 0,
+                lockdown: Lockdown { verify_paused, ..Default::default() },
+                phase: Phase::Starting,
+            });
+            true
+        }
+    }
+}
+
+/// Przejmuje aktywny raid do zakończenia (faza `Ending`) – tylko jeden `end` naraz
+/// i nigdy w trakcie `start`.
+fn claim_end(gid: u64) -> std::result::Result<Claimed, EndOutcome> {
+    let Some(mut s) = ACTIVE.get_mut(&gid) else {
+        return Err(EndOutcome::NotActive);
+    };
+    if s.phase != Phase::Active {
+        return Err(EndOutcome::Busy);
+    }
+    s.phase = Phase::Ending;
+    Ok(Claimed { started_unix: s.started_unix, quarantined: s.quarantined, lockdown: s.lockdown.clone() })
+}
+
+/// Po próbie przywrócenia: wszystko wróciło → usuń wpis (`true`); inaczej zostaw tylko
+/// nieudane kroki, wróć do `Active` i licz ciszę od nowa (`false`).
+fn settle_end(gid: u64, remaining: Lockdown) -> bool {
+    if remaining.is_restored() {
+        ACTIVE.remove(&gid);
+        return true;
+    }
+    if let Some(mut s) = ACTIVE.get_mut(&gid) {
+        s.lockdown = remaining;
+        s.phase = Phase::Active;
+        s.last_join = Instant::now();
+    }
+    false
+}
+
+/// Raid z tss.raid_state; `false`, gdy gildia ma już wpis.
+fn restore_entry(gid: u64, lockdown: Lockdown, started_unix: i64) -> bool {
+    match ACTIVE.entry(gid) {
+        Entry::Occupied(_) => false,
+        Entry::Vacant(v) => {
+            v.insert(RaidState { started_unix, last_join: Instant::now(), quarantined: 0, lockdown, phase: Phase::Active });
+            true
+        }
+    }
+}
+
+/// Czas od ostatniego wejścia dla watchera raidu `started`; `None` = ten raid się skończył
+/// (inny `started_unix` to kolejny raid z własnym watcherem).
+fn watched_idle(gid: u64, started: i64) -> Option<Duration> {
+    ACTIVE.get(&gid).filter(|s| s.started_unix == started).map(|s| s.last_join.elapsed())
+}
+
+/* ========================= Kroki lockdownu ========================= */
+
+/// Podnosi poziom weryfikacji do `RAID_LEVEL`; zwraca poprzedni (None = nic nie zmieniono).
+async fn raise_verification(ctx: &Context, guild: GuildId, reason: &str) -> Result<Option<VerificationLevel>> {
+    let prev = guild.to_partial_guild(&ctx.http).await?.verification_level;
+    if prev >= RAID_LEVEL {
+        return Ok(None);
+    }
+    guild
+        .edit(&ctx.http, EditGuild::new().verification_level(RAID_LEVEL).audit_log_reason(reason))
+        .await?;
+    Ok(Some(prev))
+}
+
+/// Czaty publiczne z /registry (`channel.chat.*`).
+fn public_chats(gid: u64) -> Vec<u64> {
+    use env_channels::chats;
+    [
+        chats::general_id(gid),
+        chats::looking_for_players_id(gid),
+        chats::grind_id(gid),
+        chats::commands_public_id(gid),
+        chats::suggestions_id(gid),
+    ]
+    .into_iter()
+    .filter(|id| *id != 0)
+    .collect()
+}
+
+/// Ustawia slowmode tam, gdzie jest niższy; zwraca (zmienione z poprzednią wartością, nieudane).
+async fn apply_slowmode(ctx: &Context, gid: u64, secs: u16, reason: &str) -> (Vec<(u64, u16)>, Vec<u64>) {
+    let mut changed = Vec::new();
+    let mut failed = Vec::new();
+    for id in public_chats(gid) {
+        let ch = ChannelId::new(id);
+        let prev = match ch.to_channel(&ctx.http).await {
+            Ok(c) => c.guild().and_then(|g| g.rate_limit_per_user).unwrap_or(0),
+            Err(e) => {
+                tracing::warn!(error=?e, gid, channel = id, "raid: channel fetch failed");
+                failed.push(id);
+                continue;
+            }
+        };
+        if prev >= secs {
+            continue;
+        }
+        match ch.edit(&ctx.http, EditChannel::new().rate_limit_per_user(secs).audit_log_reason(reason)).await {
+            Ok(_) => changed.push((id, prev)),
+            Err(e) => {
+                tracing::warn!(error=?e, gid, channel = id, "raid: slowmode failed");
+                failed.push(id);
+            }
+        }
+    }
+    (changed, failed)
+}
+
+fn level_label(level: VerificationLevel) -> &'static str {
+    match level {
+        VerificationLevel::None => "brak",
+        VerificationLevel::Low => "e-mail",
+        VerificationLevel::Medium => "konto > 5 min",
+        VerificationLevel::High => "na serwerze > 10 min",
+        VerificationLevel::Higher => "telefon",
+        _ => "nieznany",
+    }
+}
+
+/* ========================= Log + tss.raid_state ========================= */
+
+async fn report(ctx: &Context, gid: u64, title: &str, body: &str, colour: Colour, ping_staff: bool) {
+    let log_id = env_channels::logs::altguard_id(gid);
+    if log_id == 0 {
+        return;
+    }
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(body)
+        .colour(colour)
+        .footer(CreateEmbedFooter::new(BRAND_FOOTER))
+        .timestamp(Timestamp::now());
+    let mut msg = CreateMessage::new().embed(embed);
+    let ping = env_roles::moderator_id(gid);
+    if ping_staff && ping != 0 {
+        msg = msg
+            .content(format!("<@&{ping}>"))
+            .allowed_mentions(CreateAllowedMentions::new().roles(vec![RoleId::new(ping)]));
+    }
+    if let Err(e) = ChannelId::new(log_id).send_message(&ctx.http, msg).await {
+        tracing::warn!(error=?e, gid, title, "raid log failed");
+    }
+}
+
+/// Zapisz bieżący stan raidu z ACTIVE (błąd tylko logujemy – raid trwa dalej).
+async fn persist(db: &Pool<Postgres>, gid: u64) {
+    let Some((lockdown, started_unix)) = ACTIVE.get(&gid).map(|s| (s.lockdown.clone(), s.started_unix)) else {
+        return;
+    };
+    if let Err(e) = save_state(db, gid, &lockdown, started_unix).await {
+        tracing::warn!(error=?e, gid, "raid state save failed");
+    }
+}
+
+async fn save_state(db: &Pool<Postgres>, gid: u64, lockdown: &Lockdown, started_unix: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tss.raid_state (guild_id, state, started_at)
+        VALUES ($1, $2, to_timestamp($3))
+        ON CONFLICT (guild_id) DO UPDATE SET state = EXCLUDED.state
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(serde_json::to_value(lockdown)?)
+    .bind(started_unix as f64)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+async fn load_state(db: &Pool<Postgres>, gid: u64) -> Result<Option<(Lockdown, i64)>> {
+    let row = sqlx::query(
+        r#"
+        SELECT state, EXTRACT(EPOCH FROM started_at)::BIGINT AS started_unix
+          FROM tss.raid_state
+         WHERE guild_id = $1
+        "#,
+    )
+    .bind(gid as i64)
+    .fetch_optional(db)
+    .await?;
+    let Some(r) = row else { return Ok(None); };
+    let lockdown: Lockdown = serde_json::from_value(r.try_get("state")?)?;
+    Ok(Some((lockdown, r.try_get("started_unix")?)))
+}
+
+async fn delete_state(db: &Pool<Postgres>, gid: u64) -> Result<()> {
+    sqlx::query("DELETE FROM tss.raid_state WHERE guild_id = $1")
+        .bind(gid as i64)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activate(gid: u64) {
+        ACTIVE.get_mut(&gid).unwrap().phase = Phase::Active;
+    }
+
+    #[test]
+    fn lockdown_state_round_trips() {
+        let lockdown = Lockdown {
+            prev_verification: Some(VerificationLevel::Low),
+            slowmode: vec![(111, 0), (222, 5)],
+            verify_paused: true,
+        };
+        // tak jak save_state/load_state: JSONB przez serde_json::Value
+        let back: Lockdown = serde_json::from_value(serde_json::to_value(&lockdown).unwrap()).unwrap();
+        assert_eq!(back.prev_verification, Some(VerificationLevel::Low));
+        assert_eq!(back.slowmode, vec![(111, 0), (222, 5)]);
+        assert!(back.verify_paused);
+        assert!(!back.is_restored());
+        assert!(Lockdown { verify_paused: true, ..Default::default() }.is_restored());
+    }
+
+    #[test]
+    fn second_start_is_rejected_and_end_waits_for_start() {
+        let gid = 9_001;
+        assert!(begin(gid, 100, true));
+        assert!(!begin(gid, 200, false));
+        assert!(verify_paused(gid));
+
+        // /raid end w trakcie startu
+        assert_eq!(claim_end(gid).err(), Some(EndOutcome::Busy));
+
+        activate(gid);
+        let claimed = claim_end(gid).ok().unwrap();
+        assert_eq!(claimed.started_unix, 100);
+        // drugi end w trakcie przywracania
+        assert_eq!(claim_end(gid).err(), Some(EndOutcome::Busy));
+
+        assert!(settle_end(gid, Lockdown::default()));
+        assert!(!is_active(gid));
+        assert_eq!(claim_end(gid).err(), Some(EndOutcome::NotActive));
+    }
+
+    #[test]
+    fn failed_restore_keeps_remaining_steps() {
+        let gid = 9_002;
+        assert!(begin(gid, 100, true));
+        activate(gid);
+        ACTIVE.get_mut(&gid).unwrap().lockdown.slowmode = vec![(1, 0), (2, 0)];
+        claim_end(gid).ok().unwrap();
+
+        let remaining = Lockdown { slowmode: vec![(2, 0)], ..Default::default() };
+        assert!(!settle_end(gid, remaining));
+        assert!(is_active(gid));
+        assert!(!verify_paused(gid));
+        let s = ACTIVE.get(&gid).unwrap();
+        assert_eq!(s.phase, Phase::Active);
+        assert_eq!(s.lockdown.slowmode, vec![(2, 0)]);
+        drop(s);
+
+        // kolejna próba działa jak zwykły end
+        assert!(claim_end(gid).is_ok());
+        assert!(settle_end(gid, Lockdown::default()));
+        assert!(!is_active(gid));
+    }
+
+    #[test]
+    fn resume_only_fills_an_empty_slot() {
+        let gid = 9_003;
+        let lockdown = Lockdown { prev_verification: Some(VerificationLevel::Medium), ..Default::default() };
+        assert!(restore_entry(gid, lockdown.clone(), 100));
+        assert!(!restore_entry(gid, lockdown, 200));
+        // wznowiony raid od razu da się zakończyć
+        assert_eq!(claim_end(gid).ok().map(|c| c.started_unix), Some(100));
+        assert!(settle_end(gid, Lockdown::default()));
+    }
+
+    #[test]
+    fn watcher_stops_when_raid_is_replaced() {
+        let gid = 9_004;
+        assert!(begin(gid, 100, false));
+        assert!(watched_idle(gid, 100).is_some());
+
+        activate(gid);
+        claim_end(gid).ok().unwrap();
+        settle_end(gid, Lockdown::default());
+        assert!(watched_idle(gid, 100).is_none());
+
+        // kolejny raid ma własny watcher – stary kończy pracę
+        assert!(begin(gid, 300, false));
+        assert!(watched_idle(gid, 100).is_none());
+        assert!(watched_idle(gid, 300).is_some());
+        ACTIVE.remove(&gid);
+    }
+}
@@ -1,9 +1,12 @@
-//! Centralny rejestr identyfikatorów (role itd.) z obsługą profili PROD/DEV.
+//! Centralny rejestr identyfikatorów (role itd.) z obsługą profili PROD/DEV (per gildia, `guilds.rs`).
 //! PROD = stałe z realnego serwera (poniżej w `roles::*`).
 //! DEV  = stałe z serwera testowego (poniżej w `dev::*` – UZUPEŁNIONE).
 //!
 //! `env_roles::*` fallbackuje w DEV na PROD (dla ról).
 //! `env_channels::*` w DEV **nie** fallbackuje (zwraca 0, jeśli brak ID).
+//!
+//! Najpierw liczy się wpis gildii z `tss.resource_registry` (ustawiany przez /registry),
+//! a stałe poniżej są tylko seedem/fallbackiem – patrz `CATALOG` i `resolve`.
 
 #![allow(non_upper_case_globals)]
 #![allow(dead_code)]
@@ -368,256 +371,453 @@ pub mod channels {
 }
 
 /* ==========================================
-   ENV switch: role
+   Rejestr per gildia (tss.resource_registry)
    ========================================== */
-pub mod env_roles {
-    use super::{dev, roles};
+// Każdy zasób ma klucz (np. `role.admin`, `channel.logs.ban_kick_mute`). Wartość dla gildii
+// bierzemy z tss.resource_registry (cache w pamięci, ładowany w guild_create), a gdy jej
+// brak – ze stałych powyżej (PROD/DEV wg profilu gildii, patrz guilds.rs). /registry pozwala ustawić ID bez rekompilacji.
+
+use std::collections::HashMap;
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use sqlx::{Pool, Postgres, Row};
+
+use crate::guilds::{self, GuildProfile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Role,
+    Channel,
+    Category,
+}
 
-    #[inline] fn is_prod(env: &str) -> bool {
-        env.eq_ignore_ascii_case("production") || env.eq_ignore_ascii_case("prod")
+impl ResourceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResourceKind::Role => "ROLE",
+            ResourceKind::Channel => "CHANNEL",
+            ResourceKind::Category => "CATEGORY",
+        }
     }
-    #[inline] fn pick(env: &str, dev_id: u64, prod_id: u64) -> u64 {
-        if is_prod(env) { prod_id } else { if dev_id != 0 { dev_id } else { prod_id } }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ResourceKind::Role => "rola",
+            ResourceKind::Channel => "kanał",
+            ResourceKind::Category => "kategoria",
+        }
     }
+}
 
-    pub fn owner_id(env: &str) -> u64 { pick(env, dev::core::WLASCICIEL, roles::core::WLASCICIEL) }
-    pub fn co_owner_id(env: &str) -> u64 { pick(env, dev::core::WSPOL_WLASCICIEL, roles::core::WSPOL_WLASCICIEL) }
-    pub fn admin_id(env: &str) -> u64 { pick(env, dev::core::ADMIN, roles::core::ADMIN) }
-    pub fn moderator_id(env: &str) -> u64 { pick(env, dev::core::MODERATOR, roles::core::MODERATOR) }
-    pub fn test_moderator_id(env: &str) -> u64 { pick(env, dev::core::TEST_MODERATOR, roles::core::TEST_MODERATOR) }
-    pub fn opiekun_id(env: &str) -> u64 { pick(env, dev::core::OPIEKUN, roles::core::OPIEKUN) }
-    pub fn technik_zarzad_id(env: &str) -> u64 { pick(env, dev::core::TECHNIK_ZARZAD, roles::core::TECHNIK_ZARZAD) }
-    pub fn gumis_od_botow_id(env: &str) -> u64 { pick(env, dev::core::GUMIS_OD_BOTOW, roles::core::GUMIS_OD_BOTOW) }
-    pub fn verified_id(env: &str) -> u64 { pick(env, dev::special::ZWERYFIKOWANY, roles::special::ZWERYFIKOWANY) }
-    pub fn member_id(env: &str) -> u64 { pick(env, dev::special::MEMBER, roles::special::MEMBER) }
+/// Wpis katalogu: klucz + stałe wkompilowane (seed/fallback).
+#[derive(Debug, Clone, Copy)]
+pub struct Resource {
+    pub key: &'static str,
+    pub kind: ResourceKind,
+    pub dev: u64,
+    pub prod: u64,
+}
 
-    pub fn staff_set(env: &str) -> Vec<u64> {
-        vec![
-            owner_id(env), co_owner_id(env), technik_zarzad_id(env), gumis_od_botow_id(env),
-            opiekun_id(env), admin_id(env), moderator_id(env), test_moderator_id(env),
-        ]
-    }
-    pub fn moderator_set(env: &str) -> Vec<u64> {
-        vec![
-            admin_id(env), moderator_id(env), test_moderator_id(env), opiekun_id(env),
-            technik_zarzad_id(env), gumis_od_botow_id(env), co_owner_id(env), owner_id(env),
-        ]
+impl Resource {
+    /// Wartość ze stałych profilu: role w DEV fallbackują na PROD, kanały nie; custom = brak.
+    pub fn compiled(&self, profile: GuildProfile) -> u64 {
+        match profile {
+            GuildProfile::Production => self.prod,
+            GuildProfile::Development if self.kind == ResourceKind::Role && self.dev == 0 => self.prod,
+            GuildProfile::Development => self.dev,
+            GuildProfile::Custom => 0,
+        }
     }
-    pub fn color_roles(env: &str) -> Vec<u64> {
-        use super::{dev::colors as D, roles::colors as P};
-        vec![pick(env, D::SZARY, P::SZARY), pick(env, D::ZIELONY, P::ZIELONY), pick(env, D::CZERWONY, P::CZERWONY),
-             pick(env, D::POMARANCZOWY, P::POMARANCZOWY), pick(env, D::ROZOWY, P::ROZOWY), pick(env, D::ZOLTY, P::ZOLTY),
-             pick(env, D::NIEBIESKI, P::NIEBIESKI), pick(env, D::FIOLETOWY, P::FIOLETOWY)]
+}
+
+const fn role(key: &'static str, dev: u64, prod: u64) -> Resource {
+    Resource { key, kind: ResourceKind::Role, dev, prod }
+}
+const fn channel(key: &'static str, dev: u64, prod: u64) -> Resource {
+    Resource { key, kind: ResourceKind::Channel, dev, prod }
+}
+const fn category(key: &'static str, dev: u64, prod: u64) -> Resource {
+    Resource { key, kind: ResourceKind::Category, dev, prod }
+}
+
+/// Wszystkie zasoby, które da się ustawić przez /registry.
+pub static CATALOG: &[Resource] = &[
+    role("role.owner",                       dev::core::WLASCICIEL, roles::core::WLASCICIEL),
+    role("role.co_owner",                    dev::core::WSPOL_WLASCICIEL, roles::core::WSPOL_WLASCICIEL),
+    role("role.technik_zarzad",              dev::core::TECHNIK_ZARZAD, roles::core::TECHNIK_ZARZAD),
+    role("role.gumis_od_botow",              dev::core::GUMIS_OD_BOTOW, roles::core::GUMIS_OD_BOTOW),
+    role("role.opiekun",                     dev::core::OPIEKUN, roles::core::OPIEKUN),
+    role("role.head_admin",                  dev::core::HEAD_ADMIN, roles::core::HEAD_ADMIN),
+    role("role.admin",                       dev::core::ADMIN, roles::core::ADMIN),
+    role("role.head_moderator",              dev::core::HEAD_MODERATOR, roles::core::HEAD_MODERATOR),
+    role("role.moderator",                   dev::core::MODERATOR, roles::core::MODERATOR),
+    role("role.test_moderator",              dev::core::TEST_MODERATOR, roles::core::TEST_MODERATOR),
+    role("role.verified",                    dev::special::ZWERYFIKOWANY, roles::special::ZWERYFIKOWANY),
+    role("role.member",                      dev::special::MEMBER, roles::special::MEMBER),
+    role("role.quarantine",                  0, 0),
+    role("role.color.szary",                 dev::colors::SZARY, roles::colors::SZARY),
+    role("role.color.zielony",               dev::colors::ZIELONY, roles::colors::ZIELONY),
+    role("role.color.czerwony",              dev::colors::CZERWONY, roles::colors::CZERWONY),
+    role("role.color.pomaranczowy",          dev::colors::POMARANCZOWY, roles::colors::POMARANCZOWY),
+    role("role.color.rozowy",                dev::colors::ROZOWY, roles::colors::ROZOWY),
+    role("role.color.zolty",                 dev::colors::ZOLTY, roles::colors::ZOLTY),
+    role("role.color.niebieski",             dev::colors::NIEBIESKI, roles::colors::NIEBIESKI),
+    role("role.color.fioletowy",             dev::colors::FIOLETOWY, roles::colors::FIOLETOWY),
+    role("role.age.13_plus",                 dev::age::AGE_13_PLUS, roles::age::AGE_13_PLUS),
+    role("role.age.16_plus",                 dev::age::AGE_16_PLUS, roles::age::AGE_16_PLUS),
+    role("role.age.18_plus",                 dev::age::AGE_18_PLUS, roles::age::AGE_18_PLUS),
+    role("role.gender.dziewczyna",           dev::gender::DZIEWCZYNA, roles::gender::DZIEWCZYNA),
+    role("role.gender.mezczyzna",            dev::gender::MEZCZYZNA, roles::gender::MEZCZYZNA),
+    role("role.gender.plec_inna",            dev::gender::PLEC_INNA, roles::gender::PLEC_INNA),
+    role("role.region.dolnoslaskie",         dev::region::DOLNOSLASKIE, roles::region::DOLNOSLASKIE),
+    role("role.region.kujawsko_pomorskie",   dev::region::KUJAWSKO_POMORSKIE, roles::region::KUJAWSKO_POMORSKIE),
+    role("role.region.lubelskie",            dev::region::LUBELSKIE, roles::region::LUBELSKIE),
+    role("role.region.lubuskie",             dev::region::LUBUSKIE, roles::region::LUBUSKIE),
+    role("role.region.lodzkie",              dev::region::LODZKIE, roles::region::LODZKIE),
+    role("role.region.malopolskie",          dev::region::MALOPOLSKIE, roles::region::MALOPOLSKIE),
+    role("role.region.mazowieckie",          dev::region::MAZOWIECKIE, roles::region::MAZOWIECKIE),
+    role("role.region.opolskie",             dev::region::OPOLSKIE, roles::region::OPOLSKIE),
+    role("role.region.podkarpackie",         dev::region::PODKARPACKIE, roles::region::PODKARPACKIE),
+    role("role.region.pomorskie",            dev::region::POMORSKIE, roles::region::POMORSKIE),
+    role("role.region.slaskie",              dev::region::SLASKIE, roles::region::SLASKIE),
+    role("role.region.swietokrzyskie",       dev::region::SWIETOKRZYSKIE, roles::region::SWIETOKRZYSKIE),
+    role("role.region.podlaskie",            dev::region::PODLASKIE, roles::region::PODLASKIE),
+    role("role.region.warminssko_mazurskie", dev::region::WARMINSSKO_MAZURSKIE, roles::region::WARMINSSKO_MAZURSKIE),
+    role("role.region.wielkopolskie",        dev::region::WIELKOPOLSKIE, roles::region::WIELKOPOLSKIE),
+    role("role.region.zachodniopomorskie",   dev::region::ZACHODNIOPOMORSKIE, roles::region::ZACHODNIOPOMORSKIE),
+    role("role.region.zagranica",            dev::region::ZAGRANICA, roles::region::ZAGRANICA),
+    role("role.interest.wedkarstwo",         dev::interests::WEDKARSTWO, roles::interests::WEDKARSTWO),
+    role("role.interest.gaming",             dev::interests::GAMING, roles::interests::GAMING),
+    role("role.interest.arty",               dev::interests::ARTY, roles::interests::ARTY),
+    role("role.interest.koty",               dev::interests::KOTY, roles::interests::KOTY),
+    role("role.interest.pieski",             dev::interests::PIESKI, roles::interests::PIESKI),
+    role("role.level.5_plus_text",           dev::levels::LVL_5_PLUS_TEXT, roles::levels::LVL_5_PLUS_TEXT),
+    role("role.level.5_plus_voice",          dev::levels::LVL_5_PLUS_VOICE, roles::levels::LVL_5_PLUS_VOICE),
+    role("role.level.10_plus_text",          dev::levels::LVL_10_PLUS_TEXT, roles::levels::LVL_10_PLUS_TEXT),
+    role("role.level.10_plus_voice",         dev::levels::LVL_10_PLUS_VOICE, roles::levels::LVL_10_PLUS_VOICE),
+    role("role.level.15_plus_text",          dev::levels::LVL_15_PLUS_TEXT, roles::levels::LVL_15_PLUS_TEXT),
+    role("role.level.15_plus_voice",         dev::levels::LVL_15_PLUS_VOICE, roles::levels::LVL_15_PLUS_VOICE),
+    role("role.level.20_plus_text",          dev::levels::LVL_20_PLUS_TEXT, roles::levels::LVL_20_PLUS_TEXT),
+    role("role.level.20_plus_voice",         dev::levels::LVL_20_PLUS_VOICE, roles::levels::LVL_20_PLUS_VOICE),
+    role("role.level.25_plus_text",          dev::levels::LVL_25_PLUS_TEXT, roles::levels::LVL_25_PLUS_TEXT),
+    role("role.level.25_plus_voice",         dev::levels::LVL_25_PLUS_VOICE, roles::levels::LVL_25_PLUS_VOICE),
+    role("role.level.30_plus_text",          dev::levels::LVL_30_PLUS_TEXT, roles::levels::LVL_30_PLUS_TEXT),
+    role("role.level.30_plus_voice",         dev::levels::LVL_30_PLUS_VOICE, roles::levels::LVL_30_PLUS_VOICE),
+    role("role.level.40_plus_text",          dev::levels::LVL_40_PLUS_TEXT, roles::levels::LVL_40_PLUS_TEXT),
+    role("role.level.40_plus_voice",         dev::levels::LVL_40_PLUS_VOICE, roles::levels::LVL_40_PLUS_VOICE),
+    role("role.level.50_plus_text",          dev::levels::LVL_50_PLUS_TEXT, roles::levels::LVL_50_PLUS_TEXT),
+    role("role.level.50_plus_voice",         dev::levels::LVL_50_PLUS_VOICE, roles::levels::LVL_50_PLUS_VOICE),
+    role("role.level.75_plus_text",          dev::levels::LVL_75_PLUS_TEXT, roles::levels::LVL_75_PLUS_TEXT),
+    role("role.level.75_plus_voice",         dev::levels::LVL_75_PLUS_VOICE, roles::levels::LVL_75_PLUS_VOICE),
+    role("role.level.100_plus_text",         dev::levels::LVL_100_PLUS_TEXT, roles::levels::LVL_100_PLUS_TEXT),
+    role("role.level.100_plus_voice",        dev::levels::LVL_100_PLUS_VOICE, roles::levels::LVL_100_PLUS_VOICE),
+    channel("channel.stats.date",               channels::dev::STATS_DATE, channels::prod::STATS_DATE),
+    channel("channel.stats.population",         channels::dev::STATS_POPULATION, channels::prod::STATS_POPULATION),
+    channel("channel.stats.online",             channels::dev::STATS_ONLINE, channels::prod::STATS_ONLINE),
+    channel("channel.stats.last_joined",        channels::dev::STATS_LAST_JOINED, channels::prod::STATS_LAST_JOINED),
+    channel("channel.logs.ban_kick_mute",       channels::dev::LOGS_BAN_KICK_MUTE, channels::prod::LOGS_BAN_KICK_MUTE),
+    channel("channel.logs.commands",            channels::dev::LOGS_COMMANDS, channels::prod::LOGS_COMMANDS),
+    channel("channel.logs.channel_edits",       channels::dev::LOGS_CHANNEL_EDITS, channels::prod::LOGS_CHANNEL_EDITS),
+    channel("channel.logs.voice",               channels::dev::LOGS_VOICE, channels::prod::LOGS_VOICE),
+    channel("channel.logs.timeouts",            channels::dev::LOGS_TIMEOUTS, channels::prod::LOGS_TIMEOUTS),
+    channel("channel.logs.message_delete",      channels::dev::LOGS_MESSAGE_DELETE, channels::prod::LOGS_MESSAGE_DELETE),
+    channel("channel.logs.joins_leaves",        channels::dev::LOGS_JOINS_LEAVES, channels::prod::LOGS_JOINS_LEAVES),
+    channel("channel.logs.roles",               channels::dev::LOGS_ROLES, channels::prod::LOGS_ROLES),
+    channel("channel.logs.tickets",             channels::dev::LOGS_TICKETS, channels::prod::LOGS_TICKETS),
+    channel("channel.logs.altguard",            channels::dev::LOGS_ALTGUARD, channels::prod::LOGS_ALTGUARD),
+    channel("channel.logs.admin_points",        channels::dev::ADMINS_POINTS, channels::prod::ADMINS_POINTS),
+    channel("channel.logs.new_channels",        channels::dev::LOGS_NEW_CHANNELS, channels::prod::LOGS_NEW_CHANNELS),
+    channel("channel.verify",                   channels::dev::VERIFY, channels::prod::VERIFY),
+    channel("channel.verify.photos",            channels::dev::VERIFY_PHOTOS, channels::prod::VERIFY_PHOTOS),
+    channel("channel.global.welcome",           channels::dev::GLOBAL_WELCOME, channels::prod::GLOBAL_WELCOME),
+    channel("channel.global.goodbye",           channels::dev::GLOBAL_GOODBYE, channels::prod::GLOBAL_GOODBYE),
+    channel("channel.contact.create_ticket",    channels::dev::CONTACT_CREATE_TICKET, channels::prod::CONTACT_CREATE_TICKET),
+    channel("channel.contact.appeals",          channels::dev::CONTACT_APPEALS, channels::prod::CONTACT_APPEALS),
+    channel("channel.official.events",          channels::dev::OFFICIAL_EVENTS, channels::prod::OFFICIAL_EVENTS),
+    channel("channel.official.calendar",        channels::dev::OFFICIAL_CALENDAR, channels::prod::OFFICIAL_CALENDAR),
+    channel("channel.chat.general",             channels::dev::CHAT_GENERAL, channels::prod::CHAT_GENERAL),
+    channel("channel.chat.looking_for_players", channels::dev::CHAT_LFP, channels::prod::CHAT_LFP),
+    channel("channel.chat.grind",               channels::dev::CHAT_GRIND, channels::prod::CHAT_GRIND),
+    channel("channel.chat.commands_public",     channels::dev::CHAT_COMMANDS_PUBLIC, channels::prod::CHAT_COMMANDS_PUBLIC),
+    channel("channel.chat.suggestions",         channels::dev::CHAT_SUGGESTIONS, channels::prod::CHAT_SUGGESTIONS),
+    channel("channel.fun.clips",                channels::dev::FUN_CLIPS, channels::prod::FUN_CLIPS),
+    channel("channel.fun.photos",               channels::dev::FUN_PHOTOS, channels::prod::FUN_PHOTOS),
+    channel("channel.fun.memes",                channels::dev::FUN_MEMES, channels::prod::FUN_MEMES),
+    channel("channel.fun.show_off",             channels::dev::FUN_SHOW_OFF, channels::prod::FUN_SHOW_OFF),
+    channel("channel.fun.selfie",               channels::dev::FUN_SELFIE, channels::prod::FUN_SELFIE),
+    channel("channel.fun.last_letter",          channels::dev::FUN_LAST_LETTER, channels::prod::FUN_LAST_LETTER),
+    channel("channel.fun.nsfw",                 channels::dev::FUN_NSFW, channels::prod::FUN_NSFW),
+    channel("channel.topics.games",             channels::dev::TOPICS_GAMES, channels::prod::TOPICS_GAMES),
+    channel("channel.topics.tv_series",         channels::dev::TOPICS_TV_SERIES, channels::prod::TOPICS_TV_SERIES),
+    channel("channel.topics.drawing",           channels::dev::TOPICS_DRAWING, channels::prod::TOPICS_DRAWING),
+    channel("channel.topics.politics",          channels::dev::TOPICS_POLITICS, channels::prod::TOPICS_POLITICS),
+    channel("channel.topics.music",             channels::dev::TOPICS_MUSIC, channels::prod::TOPICS_MUSIC),
+    category("category.new_channels_parent",     channels::dev::LOGS_NEW_CHANNELS_PARENT, channels::prod::LOGS_NEW_CHANNELS_PARENT),
+    category("category.watch.1",                 channels::dev::WATCH_CAT_1, channels::prod::WATCH_CAT_1),
+    category("category.watch.2",                 channels::dev::WATCH_CAT_2, channels::prod::WATCH_CAT_2),
+    category("category.watch.3",                 channels::dev::WATCH_CAT_3, channels::prod::WATCH_CAT_3),
+    category("category.watch.4",                 channels::dev::WATCH_CAT_4, channels::prod::WATCH_CAT_4),
+    category("category.watch.5",                 channels::dev::WATCH_CAT_5, channels::prod::WATCH_CAT_5),
+    category("category.watch.6",                 channels::dev::WATCH_CAT_6, channels::prod::WATCH_CAT_6),
+    category("category.watch.7",                 channels::dev::WATCH_CAT_7, channels::prod::WATCH_CAT_7),
+    category("category.watch.8",                 channels::dev::WATCH_CAT_8, channels::prod::WATCH_CAT_8),
+    category("category.watch.9",                 channels::dev::WATCH_CAT_9, channels::prod::WATCH_CAT_9),
+];
+
+pub fn find(key: &str) -> Option<&'static Resource> {
+    CATALOG.iter().find(|r| r.key == key)
+}
+
+/// guild_id → (klucz → discord_id) z tss.resource_registry
+static OVERRIDES: Lazy<DashMap<u64, HashMap<String, u64>>> = Lazy::new(DashMap::new);
+
+/// Wpis z bazy dla gildii (jeśli ustawiony).
+pub fn lookup(gid: u64, key: &str) -> Option<u64> {
+    OVERRIDES.get(&gid).and_then(|m| m.get(key).copied())
+}
+
+/// Baza (per gildia) → stałe profilu gildii. 0 = brak.
+pub fn resolve(gid: u64, key: &str) -> u64 {
+    if let Some(id) = lookup(gid, key) {
+        return id;
     }
-    pub fn age_roles(env: &str) -> Vec<u64> {
-        use super::{dev::age as D, roles::age as P};
-        vec![pick(env, D::AGE_13_PLUS, P::AGE_13_PLUS), pick(env, D::AGE_16_PLUS, P::AGE_16_PLUS), pick(env, D::AGE_18_PLUS, P::AGE_18_PLUS)]
+    find(key).map(|r| r.compiled(guilds::profile(gid))).unwrap_or(0)
+}
+
+/// Załaduj wpisy gildii do cache (guild_create).
+pub async fn warmup(db: &Pool<Postgres>, gid: u64) -> anyhow::Result<()> {
+    let rows = sqlx::query(
+        "SELECT key, discord_id FROM tss.resource_registry WHERE guild_id = $1",
+    )
+    .bind(gid as i64)
+    .fetch_all(db)
+    .await?;
+
+    let map = rows
+        .into_iter()
+        .map(|r| (r.get::<String, _>("key"), r.get::<i64, _>("discord_id") as u64))
+        .collect();
+    OVERRIDES.insert(gid, map);
+    Ok(())
+}
+
+/// Ustaw (upsert) ID zasobu dla gildii.
+pub async fn set_entry(db: &Pool<Postgres>, gid: u64, res: &Resource, id: u64) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tss.resource_registry (guild_id, key, kind, discord_id, updated_at)
+        VALUES ($1, $2, $3, $4, now())
+        ON CONFLICT (guild_id, key)
+        DO UPDATE SET kind = EXCLUDED.kind, discord_id = EXCLUDED.discord_id, updated_at = now()
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(res.key)
+    .bind(res.kind.as_str())
+    .bind(id as i64)
+    .execute(db)
+    .await?;
+
+    OVERRIDES.entry(gid).or_default().insert(res.key.to_string(), id);
+    Ok(())
+}
+
+/// Usuń wpis gildii (wraca fallback ze stałych).
+pub async fn remove_entry(db: &Pool<Postgres>, gid: u64, key: &str) -> anyhow::Result<bool> {
+    let res = sqlx::query("DELETE FROM tss.resource_registry WHERE guild_id = $1 AND key = $2")
+        .bind(gid as i64)
+        .bind(key)
+        .execute(db)
+        .await?;
+
+    if let Some(mut m) = OVERRIDES.get_mut(&gid) {
+        m.remove(key);
     }
-    pub fn gender_roles(env: &str) -> Vec<u64> {
-        use super::{dev::gender as D, roles::gender as P};
-        vec![pick(env, D::DZIEWCZYNA, P::DZIEWCZYNA), pick(env, D::MEZCZYZNA, P::MEZCZYZNA), pick(env, D::PLEC_INNA, P::PLEC_INNA)]
+    Ok(res.rows_affected() > 0)
+}
+
+/// Przepisz stałe profilu (PROD/DEV) do bazy gildii. Zwraca liczbę zapisanych wpisów.
+pub async fn import_compiled(
+    db: &Pool<Postgres>,
+    gid: u64,
+    profile: GuildProfile,
+    overwrite: bool,
+) -> anyhow::Result<u64> {
+    let mut written = 0u64;
+    for res in CATALOG {
+        let id = res.compiled(profile);
+        if id == 0 || (!overwrite && lookup(gid, res.key).is_some()) {
+            continue;
+        }
+        set_entry(db, gid, res, id).await?;
+        written += 1;
     }
-    pub fn region_roles(env: &str) -> Vec<u64> {
-        use super::{dev::region as D, roles::region as P};
+    Ok(written)
+}
+
+/* ==========================================
+   ENV switch: role
+   ========================================== */
+pub mod env_roles {
+    use super::resolve;
+
+    pub fn owner_id(gid: u64) -> u64 { resolve(gid, "role.owner") }
+    pub fn co_owner_id(gid: u64) -> u64 { resolve(gid, "role.co_owner") }
+    pub fn head_admin_id(gid: u64) -> u64 { resolve(gid, "role.head_admin") }
+    pub fn admin_id(gid: u64) -> u64 { resolve(gid, "role.admin") }
+    pub fn head_moderator_id(gid: u64) -> u64 { resolve(gid, "role.head_moderator") }
+    pub fn moderator_id(gid: u64) -> u64 { resolve(gid, "role.moderator") }
+    pub fn test_moderator_id(gid: u64) -> u64 { resolve(gid, "role.test_moderator") }
+    pub fn opiekun_id(gid: u64) -> u64 { resolve(gid, "role.opiekun") }
+    pub fn technik_zarzad_id(gid: u64) -> u64 { resolve(gid, "role.technik_zarzad") }
+    pub fn gumis_od_botow_id(gid: u64) -> u64 { resolve(gid, "role.gumis_od_botow") }
+    pub fn verified_id(gid: u64) -> u64 { resolve(gid, "role.verified") }
+    pub fn member_id(gid: u64) -> u64 { resolve(gid, "role.member") }
+    pub fn quarantine_id(gid: u64) -> u64 { resolve(gid, "role.quarantine") }
+
+    pub fn staff_set(gid: u64) -> Vec<u64> {
         vec![
-            pick(env, D::DOLNOSLASKIE, P::DOLNOSLASKIE), pick(env, D::KUJAWSKO_POMORSKIE, P::KUJAWSKO_POMORSKIE),
-            pick(env, D::LUBELSKIE, P::LUBELSKIE), pick(env, D::LUBUSKIE, P::LUBUSKIE), pick(env, D::LODZKIE, P::LODZKIE),
-            pick(env, D::MALOPOLSKIE, P::MALOPOLSKIE), pick(env, D::MAZOWIECKIE, P::MAZOWIECKIE),
-            pick(env, D::OPOLSKIE, P::OPOLSKIE), pick(env, D::PODKARPACKIE, P::PODKARPACKIE),
-            pick(env, D::POMORSKIE, P::POMORSKIE), pick(env, D::SLASKIE, P::SLASKIE),
-            pick(env, D::SWIETOKRZYSKIE, P::SWIETOKRZYSKIE), pick(env, D::PODLASKIE, P::PODLASKIE),
-            pick(env, D::WARMINSSKO_MAZURSKIE, P::WARMINSSKO_MAZURSKIE), pick(env, D::WIELKOPOLSKIE, P::WIELKOPOLSKIE),
-            pick(env, D::ZACHODNIOPOMORSKIE, P::ZACHODNIOPOMORSKIE), pick(env, D::ZAGRANICA, P::ZAGRANICA),
+            owner_id(gid), co_owner_id(gid), technik_zarzad_id(gid), gumis_od_botow_id(gid),
+            opiekun_id(gid), admin_id(gid), moderator_id(gid), test_moderator_id(gid),
         ]
     }
-    pub fn interest_roles(env: &str) -> Vec<u64> {
-        use super::{dev::interests as D, roles::interests as P};
-        vec![pick(env, D::WEDKARSTWO, P::WEDKARSTWO), pick(env, D::GAMING, P::GAMING),
-             pick(env, D::ARTY, P::ARTY), pick(env, D::KOTY, P::KOTY), pick(env, D::PIESKI, P::PIESKI)]
-    }
-    pub fn level_roles(env: &str) -> Vec<u64> {
-        use super::{dev::levels as D, roles::levels as P};
+    pub fn moderator_set(gid: u64) -> Vec<u64> {
         vec![
-            pick(env, D::LVL_5_PLUS_TEXT, P::LVL_5_PLUS_TEXT), pick(env, D::LVL_5_PLUS_VOICE, P::LVL_5_PLUS_VOICE),
-            pick(env, D::LVL_10_PLUS_TEXT, P::LVL_10_PLUS_TEXT), pick(env, D::LVL_10_PLUS_VOICE, P::LVL_10_PLUS_VOICE),
-            pick(env, D::LVL_15_PLUS_TEXT, P::LVL_15_PLUS_TEXT), pick(env, D::LVL_15_PLUS_VOICE, P::LVL_15_PLUS_VOICE),
-            pick(env, D::LVL_20_PLUS_TEXT, P::LVL_20_PLUS_TEXT), pick(env, D::LVL_20_PLUS_VOICE, P::LVL_20_PLUS_VOICE),
-            pick(env, D::LVL_25_PLUS_TEXT, P::LVL_25_PLUS_TEXT), pick(env, D::LVL_25_PLUS_VOICE, P::LVL_25_PLUS_VOICE),
-            pick(env, D::LVL_30_PLUS_TEXT, P::LVL_30_PLUS_TEXT), pick(env, D::LVL_30_PLUS_VOICE, P::LVL_30_PLUS_VOICE),
-            pick(env, D::LVL_40_PLUS_TEXT, P::LVL_40_PLUS_TEXT), pick(env, D::LVL_40_PLUS_VOICE, P::LVL_40_PLUS_VOICE),
-            pick(env, D::LVL_50_PLUS_TEXT, P::LVL_50_PLUS_TEXT), pick(env, D::LVL_50_PLUS_VOICE, P::LVL_50_PLUS_VOICE),
-            pick(env, D::LVL_75_PLUS_TEXT, P::LVL_75_PLUS_TEXT), pick(env, D::LVL_75_PLUS_VOICE, P::LVL_75_PLUS_VOICE),
-            pick(env, D::LVL_100_PLUS_TEXT, P::LVL_100_PLUS_TEXT), pick(env, D::LVL_100_PLUS_VOICE, P::LVL_100_PLUS_VOICE),
+            admin_id(gid), moderator_id(gid), test_moderator_id(gid), opiekun_id(gid),
+            technik_zarzad_id(gid), gumis_od_botow_id(gid), co_owner_id(gid), owner_id(gid),
         ]
     }
+
+    /// Wszystkie role grupy (prefiks klucza, np. `role.color.`) w kolejności katalogu.
+    fn group(gid: u64, prefix: &str) -> Vec<u64> {
+        super::CATALOG
+            .iter()
+            .filter(|r| r.key.starts_with(prefix))
+            .map(|r| resolve(gid, r.key))
+            .collect()
+    }
+
+    pub fn color_roles(gid: u64) -> Vec<u64> { group(gid, "role.color.") }
+    pub fn age_roles(gid: u64) -> Vec<u64> { group(gid, "role.age.") }
+    pub fn gender_roles(gid: u64) -> Vec<u64> { group(gid, "role.gender.") }
+    pub fn region_roles(gid: u64) -> Vec<u64> { group(gid, "role.region.") }
+    pub fn interest_roles(gid: u64) -> Vec<u64> { group(gid, "role.interest.") }
+    pub fn level_roles(gid: u64) -> Vec<u64> { group(gid, "role.level.") }
 }
 
 /* =========================
    ENV PICKER DLA KANAŁÓW
    ========================= */
 pub mod env_channels {
-    pub fn verify_photos_id(env: &str) -> u64 {
-        pick_channel(env, super::channels::dev::VERIFY_PHOTOS, super::channels::prod::VERIFY_PHOTOS)
-    }
-
-    #[inline]
-    fn is_prod(env: &str) -> bool {
-        env.eq_ignore_ascii_case("production") || env.eq_ignore_ascii_case("prod")
-    }
+    use super::resolve;
 
-    #[inline]
-    fn pick_channel(env: &str, dev_id: u64, prod_id: u64) -> u64 {
-        if is_prod(env) { prod_id } else { dev_id }
-    }
+    pub fn verify_photos_id(gid: u64) -> u64 { resolve(gid, "channel.verify.photos") }
 
     // pomocnik (root-level): log altguard
-    pub fn altguard_id(env: &str) -> u64 {
-        pick_channel(env, super::channels::dev::LOGS_ALTGUARD, super::channels::prod::LOGS_ALTGUARD)
-    }
+    pub fn altguard_id(gid: u64) -> u64 { resolve(gid, "channel.logs.altguard") }
 
     // Statystyki
-    pub fn stats_date_id(env: &str)        -> u64 { pick_channel(env, super::channels::dev::STATS_DATE,        super::channels::prod::STATS_DATE) }
-    pub fn stats_population_id(env: &str)  -> u64 { pick_channel(env, super::channels::dev::STATS_POPULATION,  super::channels::prod::STATS_POPULATION) }
-    pub fn stats_online_id(env: &str)      -> u64 { pick_channel(env, super::channels::dev::STATS_ONLINE,      super::channels::prod::STATS_ONLINE) }
-    pub fn stats_last_joined_id(env: &str) -> u64 { pick_channel(env, super::channels::dev::STATS_LAST_JOINED, super::channels::prod::STATS_LAST_JOINED) }
+    pub fn stats_date_id(gid: u64) -> u64 { resolve(gid, "channel.stats.date") }
+    pub fn stats_population_id(gid: u64) -> u64 { resolve(gid, "channel.stats.population") }
+    pub fn stats_online_id(gid: u64) -> u64 { resolve(gid, "channel.stats.online") }
+    pub fn stats_last_joined_id(gid: u64) -> u64 { resolve(gid, "channel.stats.last_joined") }
 
     // Logi
     pub mod logs {
-        use super::pick_channel;
-        use crate::registry::channels;
-
-        pub fn ban_kick_mute_id(env: &str)   -> u64 { pick_channel(env, channels::dev::LOGS_BAN_KICK_MUTE,  channels::prod::LOGS_BAN_KICK_MUTE) }
-        pub fn commands_id(env: &str)        -> u64 { pick_channel(env, channels::dev::LOGS_COMMANDS,       channels::prod::LOGS_COMMANDS) }
-        pub fn channel_edits_id(env: &str)   -> u64 { pick_channel(env, channels::dev::LOGS_CHANNEL_EDITS,  channels::prod::LOGS_CHANNEL_EDITS) }
-        pub fn voice_id(env: &str)           -> u64 { pick_channel(env, channels::dev::LOGS_VOICE,          channels::prod::LOGS_VOICE) }
-        pub fn timeouts_id(env: &str)        -> u64 { pick_channel(env, channels::dev::LOGS_TIMEOUTS,       channels::prod::LOGS_TIMEOUTS) }
-        pub fn message_delete_id(env: &str)  -> u64 { pick_channel(env, channels::dev::LOGS_MESSAGE_DELETE, channels::prod::LOGS_MESSAGE_DELETE) }
-        pub fn joins_leaves_id(env: &str)    -> u64 { pick_channel(env, channels::dev::LOGS_JOINS_LEAVES,   channels::prod::LOGS_JOINS_LEAVES) }
-        pub fn roles_id(env: &str)           -> u64 { pick_channel(env, channels::dev::LOGS_ROLES,          channels::prod::LOGS_ROLES) }
-        pub fn tickets_id(env: &str)         -> u64 { pick_channel(env, channels::dev::LOGS_TICKETS,        channels::prod::LOGS_TICKETS) }
-        pub fn altguard_id(env: &str)        -> u64 { pick_channel(env, channels::dev::LOGS_ALTGUARD,       channels::prod::LOGS_ALTGUARD) }
+        use super::resolve;
+        pub fn ban_kick_mute_id(gid: u64) -> u64 { resolve(gid, "channel.logs.ban_kick_mute") }
+        pub fn commands_id(gid: u64) -> u64 { resolve(gid, "channel.logs.commands") }
+        pub fn channel_edits_id(gid: u64) -> u64 { resolve(gid, "channel.logs.channel_edits") }
+        pub fn voice_id(gid: u64) -> u64 { resolve(gid, "channel.logs.voice") }
+        pub fn timeouts_id(gid: u64) -> u64 { resolve(gid, "channel.logs.timeouts") }
+        pub fn message_delete_id(gid: u64) -> u64 { resolve(gid, "channel.logs.message_delete") }
+        pub fn joins_leaves_id(gid: u64) -> u64 { resolve(gid, "channel.logs.joins_leaves") }
+        pub fn roles_id(gid: u64) -> u64 { resolve(gid, "channel.logs.roles") }
+        pub fn tickets_id(gid: u64) -> u64 { resolve(gid, "channel.logs.tickets") }
+        pub fn altguard_id(gid: u64) -> u64 { resolve(gid, "channel.logs.altguard") }
+        pub fn admin_points_id(gid: u64) -> u64 { resolve(gid, "channel.logs.admin_points") }
     }
 
     // Weryfikacja
     pub mod verify {
-        use super::pick_channel;
-        use crate::registry::channels;
+        use super::resolve;
         /// ID kanału #weryfikacje (DEV/PROD; w DEV bez fallbacku)
-        pub fn id(env: &str) -> u64 {
-            pick_channel(env, channels::dev::VERIFY, channels::prod::VERIFY)
-        }
+        pub fn id(gid: u64) -> u64 { resolve(gid, "channel.verify") }
     }
 
     // Początek (Global)
     pub mod global {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn welcome_id(env: &str) -> u64 { pick_channel(env, channels::dev::GLOBAL_WELCOME, channels::prod::GLOBAL_WELCOME) }
-        pub fn goodbye_id(env: &str) -> u64 { pick_channel(env, channels::dev::GLOBAL_GOODBYE, channels::prod::GLOBAL_GOODBYE) }
+        use super::resolve;
+        pub fn welcome_id(gid: u64) -> u64 { resolve(gid, "channel.global.welcome") }
+        pub fn goodbye_id(gid: u64) -> u64 { resolve(gid, "channel.global.goodbye") }
     }
 
     // Kontakt (Global)
     pub mod contact {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn create_ticket_id(env: &str) -> u64 { pick_channel(env, channels::dev::CONTACT_CREATE_TICKET, channels::prod::CONTACT_CREATE_TICKET) }
-        pub fn appeals_id(env: &str)       -> u64 { pick_channel(env, channels::dev::CONTACT_APPEALS,      channels::prod::CONTACT_APPEALS) }
+        use super::resolve;
+        pub fn create_ticket_id(gid: u64) -> u64 { resolve(gid, "channel.contact.create_ticket") }
+        pub fn appeals_id(gid: u64) -> u64 { resolve(gid, "channel.contact.appeals") }
     }
 
     // Oficjalne (Global)
     pub mod official {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn events_id(env: &str)   -> u64 { pick_channel(env, channels::dev::OFFICIAL_EVENTS,   channels::prod::OFFICIAL_EVENTS) }
-        pub fn calendar_id(env: &str) -> u64 { pick_channel(env, channels::dev::OFFICIAL_CALENDAR, channels::prod::OFFICIAL_CALENDAR) }
+        use super::resolve;
+        pub fn events_id(gid: u64) -> u64 { resolve(gid, "channel.official.events") }
+        pub fn calendar_id(gid: u64) -> u64 { resolve(gid, "channel.official.calendar") }
     }
 
     // Chaty
     pub mod chats {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn general_id(env: &str)             -> u64 { pick_channel(env, channels::dev::CHAT_GENERAL,         channels::prod::CHAT_GENERAL) }
-        pub fn looking_for_players_id(env: &str) -> u64 { pick_channel(env, channels::dev::CHAT_LFP,             channels::prod::CHAT_LFP) }
-        pub fn grind_id(env: &str)               -> u64 { pick_channel(env, channels::dev::CHAT_GRIND,           channels::prod::CHAT_GRIND) }
-        pub fn commands_public_id(env: &str)     -> u64 { pick_channel(env, channels::dev::CHAT_COMMANDS_PUBLIC, channels::prod::CHAT_COMMANDS_PUBLIC) }
-        pub fn suggestions_id(env: &str)         -> u64 { pick_channel(env, channels::dev::CHAT_SUGGESTIONS,     channels::prod::CHAT_SUGGESTIONS) }
+        use super::resolve;
+        pub fn general_id(gid: u64) -> u64 { resolve(gid, "channel.chat.general") }
+        pub fn looking_for_players_id(gid: u64) -> u64 { resolve(gid, "channel.chat.looking_for_players") }
+        pub fn grind_id(gid: u64) -> u64 { resolve(gid, "channel.chat.grind") }
+        pub fn commands_public_id(gid: u64) -> u64 { resolve(gid, "channel.chat.commands_public") }
+        pub fn suggestions_id(gid: u64) -> u64 { resolve(gid, "channel.chat.suggestions") }
     }
 
     // Rozrywka
     pub mod fun {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn clips_id(env: &str)       -> u64 { pick_channel(env, channels::dev::FUN_CLIPS,        channels::prod::FUN_CLIPS) }
-        pub fn photos_id(env: &str)      -> u64 { pick_channel(env, channels::dev::FUN_PHOTOS,       channels::prod::FUN_PHOTOS) }
-        pub fn memes_id(env: &str)       -> u64 { pick_channel(env, channels::dev::FUN_MEMES,        channels::prod::FUN_MEMES) }
-        pub fn show_off_id(env: &str)    -> u64 { pick_channel(env, channels::dev::FUN_SHOW_OFF,     channels::prod::FUN_SHOW_OFF) }
-        pub fn selfie_id(env: &str)      -> u64 { pick_channel(env, channels::dev::FUN_SELFIE,       channels::prod::FUN_SELFIE) }
-        pub fn last_letter_id(env: &str) -> u64 { pick_channel(env, channels::dev::FUN_LAST_LETTER,  channels::prod::FUN_LAST_LETTER) }
-        pub fn nsfw_id(env: &str)        -> u64 { pick_channel(env, channels::dev::FUN_NSFW,         channels::prod::FUN_NSFW) }
+        use super::resolve;
+        pub fn clips_id(gid: u64) -> u64 { resolve(gid, "channel.fun.clips") }
+        pub fn photos_id(gid: u64) -> u64 { resolve(gid, "channel.fun.photos") }
+        pub fn memes_id(gid: u64) -> u64 { resolve(gid, "channel.fun.memes") }
+        pub fn show_off_id(gid: u64) -> u64 { resolve(gid, "channel.fun.show_off") }
+        pub fn selfie_id(gid: u64) -> u64 { resolve(gid, "channel.fun.selfie") }
+        pub fn last_letter_id(gid: u64) -> u64 { resolve(gid, "channel.fun.last_letter") }
+        pub fn nsfw_id(gid: u64) -> u64 { resolve(gid, "channel.fun.nsfw") }
     }
 
     // Tematy
     pub mod topics {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn games_id(env: &str)     -> u64 { pick_channel(env, channels::dev::TOPICS_GAMES,     channels::prod::TOPICS_GAMES) }
-        pub fn tv_series_id(env: &str) -> u64 { pick_channel(env, channels::dev::TOPICS_TV_SERIES, channels::prod::TOPICS_TV_SERIES) }
-        pub fn drawing_id(env: &str)   -> u64 { pick_channel(env, channels::dev::TOPICS_DRAWING,   channels::prod::TOPICS_DRAWING) }
-        pub fn politics_id(env: &str)  -> u64 { pick_channel(env, channels::dev::TOPICS_POLITICS,  channels::prod::TOPICS_POLITICS) }
-        pub fn music_id(env: &str)     -> u64 { pick_channel(env, channels::dev::TOPICS_MUSIC,     channels::prod::TOPICS_MUSIC) }
+        use super::resolve;
+        pub fn games_id(gid: u64) -> u64 { resolve(gid, "channel.topics.games") }
+        pub fn tv_series_id(gid: u64) -> u64 { resolve(gid, "channel.topics.tv_series") }
+        pub fn drawing_id(gid: u64) -> u64 { resolve(gid, "channel.topics.drawing") }
+        pub fn politics_id(gid: u64) -> u64 { resolve(gid, "channel.topics.politics") }
+        pub fn music_id(gid: u64) -> u64 { resolve(gid, "channel.topics.music") }
     }
 
     // Lista obserwowanych kategorii
     pub mod watch {
-        use super::pick_channel;
-        use crate::registry::channels;
-        pub fn categories(env: &str) -> Vec<u64> {
-            let all = [
-                pick_channel(env, channels::dev::WATCH_CAT_1, channels::prod::WATCH_CAT_1),
-                pick_channel(env, channels::dev::WATCH_CAT_2, channels::prod::WATCH_CAT_2),
-                pick_channel(env, channels::dev::WATCH_CAT_3, channels::prod::WATCH_CAT_3),
-                pick_channel(env, channels::dev::WATCH_CAT_4, channels::prod::WATCH_CAT_4),
-                pick_channel(env, channels::dev::WATCH_CAT_5, channels::prod::WATCH_CAT_5),
-                pick_channel(env, channels::dev::WATCH_CAT_6, channels::prod::WATCH_CAT_6),
-                pick_channel(env, channels::dev::WATCH_CAT_7, channels::prod::WATCH_CAT_7),
-                pick_channel(env, channels::dev::WATCH_CAT_8, channels::prod::WATCH_CAT_8),
-                pick_channel(env, channels::dev::WATCH_CAT_9, channels::prod::WATCH_CAT_9),
-            ];
-            all.into_iter().filter(|id| *id != 0).collect()
+        pub fn categories(gid: u64) -> Vec<u64> {
+            super::watch_categories(gid)
         }
     }
 
     // --- Watcher nowych kanałów (root-level) ---
-    pub fn new_channels_id(env: &str) -> u64 {
-        pick_channel(
-            env,
-            super::channels::dev::LOGS_NEW_CHANNELS,
-            super::channels::prod::LOGS_NEW_CHANNELS,
-        )
-    }
+    pub fn new_channels_id(gid: u64) -> u64 { resolve(gid, "channel.logs.new_channels") }
 
-    pub fn new_channels_parent_id(env: &str) -> u64 {
-        pick_channel(
-            env,
-            super::channels::dev::LOGS_NEW_CHANNELS_PARENT,
-            super::channels::prod::LOGS_NEW_CHANNELS_PARENT,
-        )
-    }
+    pub fn new_channels_parent_id(gid: u64) -> u64 { resolve(gid, "category.new_channels_parent") }
 
     /// Lista kategorii obserwowanych pod kątem nowych kanałów (root helper).
-    pub fn watch_categories(env: &str) -> Vec<u64> {
-        use crate::registry::channels;
-        let all = [
-            pick_channel(env, channels::dev::WATCH_CAT_1, channels::prod::WATCH_CAT_1),
-            pick_channel(env, channels::dev::WATCH_CAT_2, channels::prod::WATCH_CAT_2),
-            pick_channel(env, channels::dev::WATCH_CAT_3, channels::prod::WATCH_CAT_3),
-            pick_channel(env, channels::dev::WATCH_CAT_4, channels::prod::WATCH_CAT_4),
-            pick_channel(env, channels::dev::WATCH_CAT_5, channels::prod::WATCH_CAT_5),
-            pick_channel(env, channels::dev::WATCH_CAT_6, channels::prod::WATCH_CAT_6),
-            pick_channel(env, channels::dev::WATCH_CAT_7, channels::prod::WATCH_CAT_7),
-            pick_channel(env, channels::dev::WATCH_CAT_8, channels::prod::WATCH_CAT_8),
-            pick_channel(env, channels::dev::WATCH_CAT_9, channels::prod::WATCH_CAT_9),
-        ];
-        all.into_iter().filter(|id| *id != 0).collect()
+    pub fn watch_categories(gid: u64) -> Vec<u64> {
+        super::CATALOG
+            .iter()
+            .filter(|r| r.key.starts_with("category.watch."))
+            .map(|r| resolve(gid, r.key))
+            .filter(|id| *id != 0)
+            .collect()
     }
 }
@@ -0,0 +1,350 @@
+// src/registry_admin.rs
+//
+// /registry – ustawianie ID ról/kanałów per gildia (tss.resource_registry) bez rekompilacji.
+// Stałe z registry.rs zostają jako fallback (wg profilu gildii); `import` przepisuje je do bazy
+// jako punkt startowy, `profile` przełącza profil gildii (tss.guilds).
+
+use anyhow::Result;
+use serenity::all::*;
+
+use crate::discord::router::Routes;
+use crate::{
+    guilds::{self, GuildProfile},
+    permissions::{self, Permission},
+    registry::{self, Resource, ResourceKind, CATALOG},
+    AppContext,
+};
+
+const SYSTEM_NAME: &str = "Tigris Registry";
+
+pub struct Registry;
+
+impl Registry {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["registry"]);
+
+    pub fn commands() -> Vec<CreateCommand> {
+        let key_opt = || {
+            CreateCommandOption::new(CommandOptionType::String, "key", "Klucz zasobu (np. role.admin)")
+                .required(true)
+                .set_autocomplete(true)
+        };
+
+        vec![
+            CreateCommand::new("registry")
+                .description("Rejestr ról i kanałów tej gildii")
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Ustaw ID zasobu")
+                        .add_sub_option(key_opt())
+                        .add_sub_option(
+                            CreateCommandOption::new(
+                                CommandOptionType::String,
+                                "value",
+                                "ID lub wzmianka (0 = usuń wpis i wróć do stałej)",
+                            )
+                            .required(true),
+                        ),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "get", "Pokaż zasób")
+                        .add_sub_option(key_opt()),
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Lista zasobów")
+                        .add_sub_option(CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "prefix",
+                            "Filtr klucza (np. channel.logs)",
+                        )),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "import",
+                        "Zapisz wkompilowane stałe do bazy gildii",
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "profile", "Profil stałych (domyślnie profil gildii)")
+                            .add_string_choice("Serwer główny", "production")
+                            .add_string_choice("Serwer testowy", "development"),
+                    )
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::Boolean,
+                        "overwrite",
+                        "Nadpisz istniejące wpisy",
+                    )),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "profile",
+                        "Pokaż lub zmień profil gildii (fallback stałych)",
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "value", "Nowy profil")
+                            .add_string_choice("Serwer główny", "production")
+                            .add_string_choice("Serwer testowy", "development")
+                            .add_string_choice("Własny (tylko /registry)", "custom"),
+                    ),
+                )
+                .default_member_permissions(Permissions::ADMINISTRATOR),
+        ]
+    }
+
+    pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        if let Some(ac) = interaction.clone().autocomplete() {
+            if ac.data.name == "registry"
+                && let Err(e) = handle_key_autocomplete(ctx, &ac).await
+            {
+                tracing::warn!(?e, "registry autocomplete failed");
+            }
+            return;
+        }
+
+        let Some(cmd) = interaction.command() else { return; };
+        if cmd.data.name.as_str() != "registry" { return; }
+        if let Err(e) = handle_registry(ctx, app, &cmd).await {
+            tracing::warn!(?e, "registry command failed");
+        }
+    }
+}
+
+async fn handle_registry(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Registry) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień do rejestru.").await;
+    }
+    let Some(sc) = cmd.data.options.first() else {
+        return edit_ephemeral(ctx, cmd, "Wybierz podkomendę.").await;
+    };
+
+    let mut key: Option<String> = None;
+    let mut value: Option<String> = None;
+    let mut prefix: Option<String> = None;
+    let mut profile: Option<String> = None;
+    let mut overwrite = false;
+    if let CommandDataOptionValue::SubCommand(params) = &sc.value {
+        for p in params {
+            match (p.name.as_str(), &p.value) {
+                ("key", CommandDataOptionValue::String(s)) => key = Some(s.trim().to_lowercase()),
+                ("value", CommandDataOptionValue::String(s)) => value = Some(s.trim().to_string()),
+                ("prefix", CommandDataOptionValue::String(s)) => prefix = Some(s.trim().to_lowercase()),
+                ("profile", CommandDataOptionValue::String(s)) => profile = Some(s.clone()),
+                ("overwrite", CommandDataOptionValue::Boolean(b)) => overwrite = *b,
+                _ => {}
+            }
+        }
+    }
+
+    let current = guilds::profile(gid.get());
+    match sc.name.as_str() {
+        "set" => {
+            let Some(res) = key.as_deref().and_then(registry::find) else {
+                return edit_ephemeral(ctx, cmd, "Nieznany klucz – wybierz z listy podpowiedzi.").await;
+            };
+            let Some(id) = value.as_deref().and_then(parse_snowflake) else {
+                return edit_ephemeral(ctx, cmd, "Podaj ID, wzmiankę albo 0.").await;
+            };
+
+            if id == 0 {
+                let removed = registry::remove_entry(&app.db, gid.get(), res.key).await?;
+                tracing::info!(gid = gid.get(), key = res.key, by = cmd.user.id.get(), "registry entry removed");
+                let msg = if removed {
+                    format!("🗑️ Usunięto wpis `{}` – obowiązuje stała: {}", res.key, mention(res.kind, res.compiled(current)))
+                } else {
+                    format!("Wpis `{}` nie był ustawiony.", res.key)
+                };
+                return edit_ephemeral(ctx, cmd, &msg).await;
+            }
+
+            if let Err(why) = validate(ctx, gid, res, id).await {
+                return edit_ephemeral(ctx, cmd, &format!("⛔ {why}")).await;
+            }
+            registry::set_entry(&app.db, gid.get(), res, id).await?;
+            tracing::info!(gid = gid.get(), key = res.key, id, by = cmd.user.id.get(), "registry entry set");
+            edit_ephemeral(ctx, cmd, &format!("✅ `{}` → {}", res.key, mention(res.kind, id))).await
+        }
+        "get" => {
+            let Some(res) = key.as_deref().and_then(registry::find) else {
+                return edit_ephemeral(ctx, cmd, "Nieznany klucz – wybierz z listy podpowiedzi.").await;
+            };
+            let stored = registry::lookup(gid.get(), res.key);
+            let e = CreateEmbed::new()
+                .title(format!("🗂️ {}", res.key))
+                .colour(Colour::new(0x5865F2))
+                .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+                .field("Typ", res.kind.label(), true)
+                .field("Obowiązuje", mention(res.kind, registry::resolve(gid.get(), res.key)), true)
+                .field("Źródło", if stored.is_some() { "baza" } else { "stała" }, true)
+                .field("Wpis w bazie", stored.map(|id| mention(res.kind, id)).unwrap_or_else(|| "—".into()), true)
+                .field("Stała PROD", mention(res.kind, res.prod), true)
+                .field("Stała DEV", mention(res.kind, res.dev), true)
+                .field("Profil gildii", current.label(), true);
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(e))
+                .await?;
+            Ok(())
+        }
+        "list" => {
+            let prefix = prefix.unwrap_or_default();
+            let entries: Vec<&Resource> = CATALOG.iter().filter(|r| r.key.starts_with(&prefix)).collect();
+            if entries.is_empty() {
+                return edit_ephemeral(ctx, cmd, "Brak kluczy o tym prefiksie.").await;
+            }
+
+            let stored = entries
+                .iter()
+                .filter(|r| registry::lookup(gid.get(), r.key).is_some())
+                .count();
+            let mut desc = String::new();
+            let mut shown = 0usize;
+            for r in &entries {
+                let src = if registry::lookup(gid.get(), r.key).is_some() { "" } else { " · stała" };
+                let line = format!(
+                    "`{}` → {}{}\n",
+                    r.key,
+                    mention(r.kind, registry::resolve(gid.get(), r.key)),
+                    src
+                );
+                if desc.len() + line.len() > 3900 {
+                    break;
+                }
+                desc.push_str(&line);
+                shown += 1;
+            }
+            if shown < entries.len() {
+                desc.push_str(&format!("… i {} więcej (zawęź `prefix`)", entries.len() - shown));
+            }
+
+            let e = CreateEmbed::new()
+                .title("🗂️ Rejestr zasobów")
+                .colour(Colour::new(0x5865F2))
+                .footer(CreateEmbedFooter::new(format!(
+                    "{SYSTEM_NAME} • w bazie: {stored}/{}",
+                    entries.len()
+                )))
+                .description(desc);
+            cmd.edit_response(&ctx.http, EditInteractionResponse::new().embed(e))
+                .await?;
+            Ok(())
+        }
+        "import" => {
+            let from = profile.as_deref().and_then(GuildProfile::parse).unwrap_or(current);
+            if from == GuildProfile::Custom {
+                return edit_ephemeral(ctx, cmd, "Profil własny nie ma stałych – wybierz `profile` do importu.").await;
+            }
+            let written = registry::import_compiled(&app.db, gid.get(), from, overwrite).await?;
+            tracing::info!(gid = gid.get(), profile = from.as_str(), written, overwrite, by = cmd.user.id.get(), "registry import");
+            edit_ephemeral(
+                ctx,
+                cmd,
+                &format!(
+                    "✅ Zaimportowano {written} wpisów (profil: {}{}).",
+                    from.label(),
+                    if overwrite { ", z nadpisaniem" } else { "" }
+                ),
+            )
+            .await
+        }
+        "profile" => {
+            let Some(raw) = value else {
+                return edit_ephemeral(ctx, cmd, &format!("Profil gildii: **{}** (`{}`).", current.label(), current.as_str())).await;
+            };
+            let Some(next) = GuildProfile::parse(&raw) else {
+                return edit_ephemeral(ctx, cmd, "Nieznany profil.").await;
+            };
+            let name = gid.name(&ctx.cache).unwrap_or_default();
+            guilds::set_profile(&app.db, gid.get(), &name, next).await?;
+            tracing::info!(gid = gid.get(), from = current.as_str(), to = next.as_str(), by = cmd.user.id.get(), "guild profile changed");
+            edit_ephemeral(
+                ctx,
+                cmd,
+                &format!("✅ Profil gildii: **{}** → **{}**.", current.label(), next.label()),
+            )
+            .await
+        }
+        _ => edit_ephemeral(ctx, cmd, "Nieznana podkomenda.").await,
+    }
+}
+
+async fn handle_key_autocomplete(ctx: &Context, ac: &CommandInteraction) -> Result<()> {
+    let needle = ac
+        .data
+        .autocomplete()
+        .map(|o| o.value.trim().to_lowercase())
+        .unwrap_or_default();
+
+    let choices = CATALOG
+        .iter()
+        .filter(|r| r.key.contains(&needle))
+        .take(25)
+        .map(|r| AutocompleteChoice::new(format!("{} ({})", r.key, r.kind.label()), r.key))
+        .collect();
+
+    ac.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Autocomplete(CreateAutocompleteResponse::new().set_choices(choices)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Sprawdź, czy ID istnieje w gildii i pasuje do typu zasobu.
+async fn validate(ctx: &Context, gid: GuildId, res: &Resource, id: u64) -> std::result::Result<(), String> {
+    match res.kind {
+        ResourceKind::Role => {
+            let roles = gid.roles(&ctx.http).await.map_err(|e| format!("Nie udało się pobrać ról: {e}"))?;
+            if !roles.contains_key(&RoleId::new(id)) {
+                return Err(format!("Rola `{id}` nie istnieje na tym serwerze."));
+            }
+        }
+        ResourceKind::Channel | ResourceKind::Category => {
+            let channels = gid
+                .channels(&ctx.http)
+                .await
+                .map_err(|e| format!("Nie udało się pobrać kanałów: {e}"))?;
+            let Some(ch) = channels.get(&ChannelId::new(id)) else {
+                return Err(format!("Kanał `{id}` nie istnieje na tym serwerze."));
+            };
+            let is_category = ch.kind == ChannelType::Category;
+            if res.kind == ResourceKind::Category && !is_category {
+                return Err(format!("`{}` wymaga kategorii, a <#{id}> nią nie jest.", res.key));
+            }
+            if res.kind == ResourceKind::Channel && is_category {
+                return Err(format!("`{}` wymaga kanału, a podano kategorię.", res.key));
+            }
+        }
+    }
+    Ok(())
+}
+
+/* ========================= Helpers ========================= */
+
+/// "123", "<@&123>", "<#123>" → 123
+fn parse_snowflake(s: &str) -> Option<u64> {
+    let digits: String = s.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+fn mention(kind: ResourceKind, id: u64) -> String {
+    if id == 0 {
+        return "— (brak)".into();
+    }
+    match kind {
+        ResourceKind::Role => format!("<@&{id}> (`{id}`)"),
+        ResourceKind::Channel | ResourceKind::Category => format!("<#{id}> (`{id}`)"),
+    }
+}
+
+async fn edit_ephemeral(ctx: &Context, cmd: &CommandInteraction, msg: &str) -> Result<()> {
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().content(msg))
+        .await?;
+    Ok(())
+}
@@ -25,7 +25,7 @@ impl StatsChannels {
 
     /// Wywołaj przy starcie (READY/GUILD_CREATE) – ustawi datę i liczniki.
     pub async fn sync_on_ready(ctx: &Context, app: &AppContext, guild_id: u64) {
-        let _ = Self::update_date(ctx, app).await;
+        let _ = Self::update_date(ctx, app, guild_id).await;
         let _ = Self::update_counts(ctx, app, guild_id).await;
     }
 
@@ -80,7 +80,7 @@ impl StatsChannels {
         let app1 = app.clone();
         tokio::spawn(async move {
             loop {
-                if let Err(e) = Self::update_date(&ctx1, &app1).await {
+                if let Err(e) = Self::update_date(&ctx1, &app1, guild_id).await {
                     tracing::warn!(guild_id, error = ?e, "stats: failed to update date");
                 }
                 if let Err(e) = Self::update_counts(&ctx1, &app1, guild_id).await {
@@ -114,9 +114,8 @@ impl StatsChannels {
     /* -------------------- Konkretne aktualizacje kanałów ------------------- */
 
     /// Ustaw „⇒ Data:  DD.MM.YYYY”.
-    pub async fn update_date(ctx: &Context, app: &AppContext) -> serenity::Result<()> {
-        let env = app.env();
-        let ch_id = env_channels::stats_date_id(&env);
+    pub async fn update_date(ctx: &Context, _app: &AppContext, guild_id: u64) -> serenity::Result<()> {
+        let ch_id = env_channels::stats_date_id(guild_id);
         if ch_id == 0 {
             return Ok(());
         }
@@ -133,12 +132,11 @@ impl StatsChannels {
     /// o PartialGuild i bierzemy pola approximate_* (jeśli są dostępne).
     pub async fn update_counts(
         ctx: &Context,
-        app: &AppContext,
+        _app: &AppContext,
         guild_id: u64,
     ) -> serenity::Result<()> {
-        let env = app.env();
-        let ch_pop = env_channels::stats_population_id(&env);
-        let ch_onl = env_channels::stats_online_id(&env);
+        let ch_pop = env_channels::stats_population_id(guild_id);
+        let ch_onl = env_channels::stats_online_id(guild_id);
         if ch_pop == 0 && ch_onl == 0 {
             return Ok(());
         }
@@ -221,14 +219,13 @@ impl StatsChannels {
 /// Znajdź kanał "ostatnio dołączył": najpierw ID z rejestru, potem fallback po nazwie i typie.
 async fn find_last_joined_channel(
     ctx: &Context,
-    app: &AppContext,
+    _app: &AppContext,
     guild_id: u64,
 ) -> Option<ChannelId> {
     // A) Pobierz mapę kanałów gildii (jedno zapytanie)
     if let Ok(map) = GuildId::new(guild_id).channels(&ctx.http).await {
         // Spróbuj ID z rejestru
-        let env = app.env();
-        let wanted = env_channels::stats_last_joined_id(&env);
+        let wanted = env_channels::stats_last_joined_id(guild_id);
         if wanted != 0 {
             if let Some(gc) = map.get(&ChannelId::new(wanted)) {
                 if matches!(gc.kind, ChannelType::Voice | ChannelType::Stage) {
@@ -5,18 +5,23 @@ use serenity::all::*;
 use sqlx::{Pool, Postgres, Row};
 use serde_json::Value as Json;
 
-use crate::AppContext;
+use crate::discord::router::Routes;
+use crate::permissions::{self, Permission};
+use crate::{invites, warn, AppContext};
 
 const SYSTEM_NAME: &str = "Tigris User Inspector";
 
 pub struct UserInfo;
 
 impl UserInfo {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["user"]);
+
     /* ===================== Komendy ===================== */
 
-    pub async fn register_commands(ctx: &Context, gid: GuildId) -> Result<()> {
-        // /user – wymagane „user”, opcjonalne „public”
-        gid.create_command(&ctx.http,
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            // /user – wymagane „user”, opcjonalne „public”
             CreateCommand::new("user")
                 .description("Pokaż szczegółowe informacje o użytkowniku (profil, serwer, moderacja).")
                 .add_option(
@@ -26,9 +31,8 @@ impl UserInfo {
                 .add_option(
                     CreateCommandOption::new(CommandOptionType::Boolean, "public", "Odpowiedź publiczna (domyślnie prywatna)")
                 )
-                .default_member_permissions(Permissions::MODERATE_MEMBERS)
-        ).await?;
-        Ok(())
+                .default_member_permissions(Permissions::MODERATE_MEMBERS),
+        ]
     }
 
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
@@ -55,6 +59,16 @@ async fn handle_user(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         return Ok(());
     };
 
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::User) {
+        cmd.create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().content("⛔ Brak uprawnień.").ephemeral(true)
+            )
+        ).await?;
+        return Ok(());
+    }
+
     // Zbierz opcje przed defer, żeby ustawić ephemeral zależnie od "public"
     let mut target: Option<UserId> = None;
     let mut want_public = false;
@@ -176,6 +190,20 @@ async fn handle_user(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         e_guild = e_guild.description("_Użytkownik nie jest członkiem tej gildii._");
     }
 
+    // Zaproszenie z ostatniego wejścia (tss.member_joins)
+    match invites::last_join(&app.db, gid.get(), uid.get()).await {
+        Ok(Some(j)) => {
+            let txt = format!(
+                "{} • <t:{}:R>",
+                invites::label(j.invite_code.as_deref(), j.inviter_id),
+                j.joined_unix
+            );
+            e_guild = e_guild.field("Zaproszenie", txt, true);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error=?e, "member_joins lookup failed"),
+    }
+
     /* ====== Moderacja (warn/mute) ====== */
     let mut e_mod = CreateEmbed::new()
         .title("🛡️ Moderacja")
@@ -183,7 +211,7 @@ async fn handle_user(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
     // WARN – konfiguracja wygasania
-    let decay_days: i64 = get_warn_decay_days(&app.db, gid.get()).await.unwrap_or(30) as i64;
+    let decay_days: i64 = warn::load_warn_config(&app.db, gid.get()).await.decay_days as i64;
 
     // WARN – total & active points + ostatnie 5
     let total_warns: i64 = sqlx::query_scalar(
@@ -211,7 +239,7 @@ async fn handle_user(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
 
     let recent_warns = sqlx::query(
         r#"
-        SELECT id, points, reason, EXTRACT(EPOCH FROM created_at)::BIGINT AS ts
+        SELECT id, points, preset, reason, EXTRACT(EPOCH FROM created_at)::BIGINT AS ts
         FROM tss.warn_cases
         WHERE guild_id=$1 AND user_id=$2 AND deleted_at IS NULL
         ORDER BY created_at DESC
@@ -228,9 +256,11 @@ async fn handle_user(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     for r in recent_warns {
         let id: i64 = r.try_get("id").unwrap_or(0);
         let pts: i32 = r.try_get("points").unwrap_or(0);
+        let preset: Option<String> = r.try_get("preset").unwrap_or(None);
         let rsn: String = r.try_get("reason").unwrap_or_default();
         let ts: i64 = r.try_get("ts").unwrap_or(now_unix());
-        warn_lines.push(format!("`#{}` • **{}p** • <t:{ts}:R>\n{}", id, pts, truncate(&rsn, 140)));
+        let preset = preset.map(|p| format!(" ({p})")).unwrap_or_default();
+        warn_lines.push(format!("`#{}` • **{}p**{} • <t:{ts}:R>\n{}", id, pts, preset, truncate(&rsn, 140)));
     }
     if warn_lines.is_empty() {
         warn_lines.push("_brak aktywnych wpisów_".into());
@@ -312,20 +342,6 @@ async fn handle_user(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
 
 /* ========================= Helpers ========================= */
 
-async fn get_warn_decay_days(db: &Pool<Postgres>, gid: u64) -> Result<i32> {
-    let row = sqlx::query("SELECT cfg FROM tss.warn_config WHERE guild_id=$1")
-        .bind(gid as i64)
-        .fetch_optional(db)
-        .await?;
-    if let Some(r) = row {
-        let v: Json = r.try_get("cfg")?;
-        if let Some(n) = v.get("decay_days").and_then(|x| x.as_i64()) {
-            return Ok(n as i32);
-        }
-    }
-    Ok(30)
-}
-
 async fn get_mute_role(db: &Pool<Postgres>, gid: u64) -> Result<Option<u64>> {
     let row = sqlx::query("SELECT cfg FROM tss.mute_config WHERE guild_id=$1")
         .bind(gid as i64)
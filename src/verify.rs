@@ -10,29 +10,35 @@ use serenity::builder::{
     EditInteractionResponse,
 };
 
+use crate::discord::router::Routes;
 use crate::altguard::{AltVerdict, ScoreInput};
+use crate::idguard::{IdgInput, IdgVerdict};
+use crate::permissions::{self, Permission};
 use crate::registry::{env_channels, env_roles};
-use crate::{welcome::Welcome, AppContext};
+use crate::{invites, raid, welcome::Welcome, AppContext};
 
 const BRAND_FOOTER: &str = "Tigris Security System™ • Unfaithful";
 
 pub struct Verify;
 
 impl Verify {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes {
+        commands: &["verify-panel"],
+        components: &["verify_accept"],
+        modals: &[],
+    };
+
     /* ======================
        REJESTR KOMEND
        ====================== */
 
     /// Rejestruje /verify-panel na danej gildii.
-    pub async fn register_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
-        guild_id
-            .create_command(
-                &ctx.http,
-                CreateCommand::new("verify-panel")
-                    .description("Publikuje panel weryfikacji w #weryfikacje"),
-            )
-            .await?;
-        Ok(())
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
+            CreateCommand::new("verify-panel")
+                .description("Publikuje panel weryfikacji w #weryfikacje"),
+        ]
     }
 
     /* ======================
@@ -40,7 +46,7 @@ impl Verify {
        ====================== */
 
     /// Jedna brama do obsługi interakcji związanych z weryfikacją.
-    /// Wołaj w `interaction_create` (mod.rs).
+    /// Wołane przez router (discord/router.rs).
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
         // Najpierw slash-komenda (klonujemy, bo metody konsumują enum)
         if let Some(cmd) = interaction.clone().command() {
@@ -65,14 +71,9 @@ impl Verify {
        ====================== */
 
     async fn on_command(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
-        // ACL: tylko staff
-        let env = app.env();
-        let staff = env_roles::staff_set(&env);
         let allowed = cmd
-            .member
-            .as_ref()
-            .map(|m| m.roles.iter().any(|r| staff.contains(&r.get())))
-            .unwrap_or(false);
+            .guild_id
+            .is_some_and(|g| permissions::authorize(g, cmd.member.as_deref(), Permission::VerifyPanel));
 
         if !allowed {
             let _ = cmd
@@ -150,18 +151,17 @@ impl Verify {
     /// Znajduje lub tworzy kanał `#weryfikacje` i nakłada poprawne nadpisania uprawnień.
     async fn ensure_verify_channel(
         ctx: &Context,
-        app: &AppContext,
+        _app: &AppContext,
         guild_id: GuildId,
     ) -> Result<ChannelId> {
-        let env = app.env();
 
         // 0) Jeśli w rejestrze mamy jawne ID kanału, użyj i napraw uprawnienia.
-        let preferred = env_channels::verify::id(&env);
+        let preferred = env_channels::verify::id(guild_id.get());
         if preferred != 0 {
             let preferred_id = ChannelId::new(preferred);
             if let Ok(map) = guild_id.channels(&ctx.http).await {
                 if let Some((_id, ch)) = map.get_key_value(&preferred_id) {
-                    let overwrites = Self::build_overwrites(app, guild_id);
+                    let overwrites = Self::build_overwrites(guild_id);
                     let _ = ch
                         .id
                         .edit(&ctx.http, EditChannel::new().permissions(overwrites))
@@ -179,7 +179,7 @@ impl Verify {
                 .find(|(_, ch)| ch.kind == serenity::all::ChannelType::Text
                     && ch.name.eq_ignore_ascii_case("weryfikacje"))
             {
-                let overwrites = Self::build_overwrites(app, guild_id);
+                let overwrites = Self::build_overwrites(guild_id);
                 let _ = ch
                     .id
                     .edit(&ctx.http, EditChannel::new().permissions(overwrites))
@@ -189,7 +189,7 @@ impl Verify {
         }
 
         // 2) Brak? Tworzymy kanał z nadpisaniami
-        let overwrites = Self::build_overwrites(app, guild_id);
+        let overwrites = Self::build_overwrites(guild_id);
         let created: GuildChannel = guild_id
             .create_channel(
                 &ctx.http,
@@ -204,10 +204,9 @@ impl Verify {
     }
 
     /// Nadpisania uprawnień: @everyone (VIEW+READ; bez SEND), Member (DENY VIEW), staff (ALLOW VIEW+SEND+READ).
-    fn build_overwrites(app: &AppContext, guild_id: GuildId) -> Vec<PermissionOverwrite> {
-        let env = app.env();
-        let member_role = env_roles::member_id(&env);
-        let staff_roles = env_roles::staff_set(&env);
+    fn build_overwrites(guild_id: GuildId) -> Vec<PermissionOverwrite> {
+        let member_role = env_roles::member_id(guild_id.get());
+        let staff_roles = env_roles::staff_set(guild_id.get());
 
         let mut ov = Vec::new();
 
@@ -278,7 +277,7 @@ impl Verify {
        Klik przycisku
        ====================== */
 
-    /// Obsługa kliknięcia przycisku – szybkie ACK, detekcja klona 1:1, role, AltGuard log, publiczny embed powitalny.
+    /// Obsługa kliknięcia przycisku – szybkie ACK, detekcja klona 1:1, IdGuard, role, AltGuard log, publiczny embed powitalny.
     pub async fn on_component(ctx: &Context, app: &AppContext, i: &ComponentInteraction) {
         // Szybki ACK — zawsze najpierw
         let _ = i
@@ -302,9 +301,21 @@ impl Verify {
             return;
         };
 
-        let env = app.env();
-        let member_role_u64 = env_roles::member_id(&env);
-        let verified_role_u64 = env_roles::verified_id(&env);
+        // Tryb raidu: panel wstrzymany do końca lockdownu
+        if raid::verify_paused(guild_id.get()) {
+            let _ = i
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(
+                        "⏸️ Weryfikacja jest chwilowo wstrzymana (ochrona przed raidem). Spróbuj za kilka minut.",
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        let member_role_u64 = env_roles::member_id(guild_id.get());
+        let verified_role_u64 = env_roles::verified_id(guild_id.get());
 
         // Member
         let Ok(member) = guild_id.member(&ctx.http, i.user.id).await else {
@@ -345,7 +356,7 @@ impl Verify {
             .await
         {
             // Log do kanału AltGuard
-            let log_id = env_channels::logs::altguard_id(&env);
+            let log_id = env_channels::logs::altguard_id(guild_id.get());
             if log_id != 0 {
                 let details = format!(
                     "Avatar hamming: {}\nTa sama nazwa: {}\nTa sama global name: {}",
@@ -385,6 +396,26 @@ impl Verify {
             return;
         }
 
+        /* === IDGUARD: nick/avatar (przed nadaniem ról) === */
+        let idguard = app.idguard();
+        let report = idguard.screen(ctx, &IdgInput::from_member(&member)).await;
+        if report.verdict == IdgVerdict::Block
+            && !idguard.is_exempt(guild_id.get(), member.user.id.get()).await
+        {
+            // Embed z przyciskami poszedł już do logu AltGuard (IdGuard::screen);
+            // cofnięcie akcji / zatwierdzenie przez staff odblokowuje weryfikację
+            let _ = i
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(
+                        "❌ Twój nick lub avatar narusza zasady serwera. \
+                         Zmień go albo poczekaj na ręczną weryfikację przez administrację.",
+                    ),
+                )
+                .await;
+            return;
+        }
+
         // Role
         if member_role_u64 != 0 {
             if let Err(e) = member
@@ -424,24 +455,31 @@ impl Verify {
            ALTGUARD: log po weryfikacji
            ====================== */
         let ag = app.altguard();
-        let staff_set = env_roles::staff_set(&env);
+        let staff_set = env_roles::staff_set(guild_id.get());
         let has_trusted = member.roles.iter().any(|r| staff_set.contains(&r.get()));
 
+        // zaproszenie ustalone przy wejściu (tss.member_joins)
+        let join = invites::last_join(&app.db, guild_id.get(), i.user.id.get())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error=?e, "member_joins lookup failed");
+                None
+            });
         let input = ScoreInput {
             guild_id: guild_id.get(),
             user_id: i.user.id.get(),
             username: Some(i.user.name.clone()),
             display_name: member.nick.clone(),
             global_name: i.user.global_name.clone(),
-            invite_code: None,
-            inviter_id: None,
+            invite_code: join.as_ref().and_then(|j| j.invite_code.clone()),
+            inviter_id: join.as_ref().and_then(|j| j.inviter_id),
             has_trusted_role: has_trusted,
             avatar_url: i.user.avatar_url(),
         };
 
         match ag.score_user(&input).await {
             Ok(score) => {
-                let log_id = env_channels::logs::altguard_id(&env);
+                let log_id = env_channels::logs::altguard_id(guild_id.get());
                 if log_id != 0 {
                     let (title, colour) = match score.verdict {
                         AltVerdict::Low => ("AltGuard: Niskie ryzyko", Colour::DARK_GREEN),
@@ -466,6 +504,7 @@ impl Verify {
                         ))
                         .field("User ID", format!("`{}`", i.user.id.get()), true)
                         .field("Score", format!("**{}** / 100", score.score), true)
+                        .field("Zaproszenie", invites::label(input.invite_code.as_deref(), input.inviter_id), true)
                         .field("Sygnały", if top.is_empty() { "–".into() } else { top }, false)
                         .footer(CreateEmbedFooter::new(BRAND_FOOTER))
                         .colour(colour);
@@ -2,14 +2,24 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use chrono::NaiveDateTime;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serenity::all::*;
 use sqlx::{Pool, Postgres, Row};
 
-use crate::{registry::env_channels, AppContext};
+use crate::discord::router::Routes;
+use crate::{
+    ban::apply_auto_ban,
+    cases::{self, CaseAction, CaseRef, NewCase},
+    kick::apply_kick,
+    mute::{apply_mute, MuteRequest},
+    permissions::{self, Permission},
+    registry::env_channels,
+    AppContext,
+};
 
 const SYSTEM_NAME: &str = "Tigris Warn System";
-const DECAY_DAYS: i64 = 30; // warn wygasa po tylu dniach
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WarnCase {
@@ -17,14 +27,71 @@ pub struct WarnCase {
     pub guild_id: u64,
     pub user_id: u64,
     pub moderator_id: u64,
+    pub points: i32,
+    pub preset: Option<String>,
     pub reason: String,
     pub evidence: Option<String>,
     pub created_at: i64,
 }
 
+/// Nazwany preset wagi warna (np. "spam" = 1 pkt), per gildia.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarnPreset {
+    pub name: String,
+    pub points: i32,
+}
+
+/// Konfiguracja warnów per gildia (`tss.warn_config`).
+/// Progi `<= 0` wyłączają daną eskalację.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarnConfig {
+    pub decay_days: i32,
+    pub timeout_pts: i32,
+    pub timeout_hours: i32,
+    pub kick_pts: i32,
+    pub ban_pts: i32,
+}
+impl Default for WarnConfig {
+    fn default() -> Self {
+        Self {
+            decay_days: 30,
+            timeout_pts: 3,
+            timeout_hours: 12,
+            kick_pts: 6,
+            ban_pts: 9,
+        }
+    }
+}
+
+static CFG: Lazy<DashMap<u64, WarnConfig>> = Lazy::new(DashMap::new);
+static PRESETS: Lazy<DashMap<u64, Vec<WarnPreset>>> = Lazy::new(DashMap::new);
+
+/// Maksymalna waga pojedynczego warna.
+const MAX_WARN_POINTS: i64 = 20;
+
+/// Kara wynikająca z przekroczenia progu punktów.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escalation {
+    Timeout,
+    Kick,
+    Ban,
+}
+impl Escalation {
+    fn as_str(self) -> &'static str {
+        match self {
+            Escalation::Timeout => "timeout",
+            Escalation::Kick => "kick",
+            Escalation::Ban => "ban",
+        }
+    }
+}
+
 pub struct Warns;
 
 impl Warns {
+    /// Komendy i custom_id obsługiwane przez ten moduł (router w discord/router.rs).
+    pub const ROUTES: Routes = Routes::commands(&["warn", "warns", "warn-remove", "warn-config"]);
+
     pub async fn ensure_tables(db: &Pool<Postgres>) -> Result<()> {
         sqlx::query(r#"CREATE SCHEMA IF NOT EXISTS tss;"#)
             .execute(db)
@@ -52,7 +119,8 @@ impl Warns {
             ALTER TABLE tss.warn_cases
                 ADD COLUMN IF NOT EXISTS deleted_at    TIMESTAMPTZ NULL,
                 ADD COLUMN IF NOT EXISTS deleted_by    BIGINT      NULL,
-                ADD COLUMN IF NOT EXISTS delete_reason TEXT        NULL;
+                ADD COLUMN IF NOT EXISTS delete_reason TEXT        NULL,
+                ADD COLUMN IF NOT EXISTS preset        TEXT        NULL;
             "#,
         )
         .execute(db)
@@ -73,9 +141,8 @@ impl Warns {
         Ok(())
     }
 
-    pub async fn register_commands(ctx: &Context, gid: GuildId) -> Result<()> {
-        gid.create_command(
-            &ctx.http,
+    pub fn commands() -> Vec<CreateCommand> {
+        vec![
             CreateCommand::new("warn")
                 .description("Nadaj ostrzeżenie")
                 .add_option(
@@ -86,28 +153,35 @@ impl Warns {
                     CreateCommandOption::new(CommandOptionType::String, "reason", "Powód")
                         .required(true),
                 )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "preset",
+                        "Rodzaj przewinienia (waga z presetu)",
+                    )
+                    .set_autocomplete(true),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "points",
+                        "Waga warna w punktach (nadpisuje preset, domyślnie 1)",
+                    )
+                    .min_int_value(1)
+                    .max_int_value(MAX_WARN_POINTS as u64),
+                )
                 .add_option(CreateCommandOption::new(
                     CommandOptionType::String,
                     "evidence",
                     "Dowód/URL (opcjonalnie)",
                 ))
                 .default_member_permissions(Permissions::MODERATE_MEMBERS),
-        )
-        .await?;
-
-        gid.create_command(
-            &ctx.http,
             CreateCommand::new("warns")
                 .description("Pokaż ostrzeżenia użytkownika")
                 .add_option(
                     CreateCommandOption::new(CommandOptionType::User, "user", "Użytkownik")
                         .required(true),
                 ),
-        )
-        .await?;
-
-        gid.create_command(
-            &ctx.http,
             CreateCommand::new("warn-remove")
                 .description("Usuń (un-warn) po ID sprawy")
                 .add_option(
@@ -127,13 +201,111 @@ impl Warns {
                     .required(true),
                 )
                 .default_member_permissions(Permissions::MODERATE_MEMBERS),
-        )
-        .await?;
-
-        Ok(())
+            CreateCommand::new("warn-config")
+                .description("Konfiguracja systemu warnów (wygasanie + progi eskalacji)")
+                .add_option(CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "show",
+                    "Pokaż aktualną konfigurację",
+                ))
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Ustaw parametry")
+                        .add_sub_option(
+                            CreateCommandOption::new(
+                                CommandOptionType::Integer,
+                                "decay_days",
+                                "Po ilu dniach warn wygasa",
+                            )
+                            .min_int_value(1)
+                            .max_int_value(365),
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(
+                                CommandOptionType::Integer,
+                                "timeout_pts",
+                                "Próg wyciszenia (0 = wyłączony)",
+                            )
+                            .min_int_value(0),
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(
+                                CommandOptionType::Integer,
+                                "timeout_hours",
+                                "Długość wyciszenia po przekroczeniu progu (h)",
+                            )
+                            .min_int_value(1)
+                            .max_int_value(672),
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(
+                                CommandOptionType::Integer,
+                                "kick_pts",
+                                "Próg kicka (0 = wyłączony)",
+                            )
+                            .min_int_value(0),
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(
+                                CommandOptionType::Integer,
+                                "ban_pts",
+                                "Próg bana (0 = wyłączony)",
+                            )
+                            .min_int_value(0),
+                        ),
+                )
+                .add_option(CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "reset",
+                    "Przywróć wartości domyślne",
+                ))
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "preset-set",
+                        "Dodaj/zmień preset wagi warna",
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "name",
+                            "Nazwa presetu (np. spam)",
+                        )
+                        .required(true)
+                        .max_length(32),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::Integer, "points", "Waga (pkt)")
+                            .required(true)
+                            .min_int_value(1)
+                            .max_int_value(MAX_WARN_POINTS as u64),
+                    ),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "preset-remove",
+                        "Usuń preset wagi warna",
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "name", "Nazwa presetu")
+                            .required(true)
+                            .set_autocomplete(true),
+                    ),
+                )
+                .default_member_permissions(Permissions::ADMINISTRATOR),
+        ]
     }
 
     pub async fn on_interaction(ctx: &Context, app: &AppContext, interaction: Interaction) {
+        if let Some(ac) = interaction.clone().autocomplete() {
+            if matches!(ac.data.name.as_str(), "warn" | "warn-config")
+                && let Err(e) = handle_preset_autocomplete(ctx, app, &ac).await
+            {
+                tracing::warn!(?e, "warn preset autocomplete failed");
+            }
+            return;
+        }
+
         if let Some(cmd) = interaction.clone().command() {
             match cmd.data.name.as_str() {
                 "warn" => {
@@ -151,6 +323,11 @@ impl Warns {
                         tracing::warn!(?e, "warn-remove failed");
                     }
                 }
+                "warn-config" => {
+                    if let Err(e) = handle_warn_config(ctx, app, &cmd).await {
+                        tracing::warn!(?e, "warn-config failed");
+                    }
+                }
                 _ => {}
             }
         }
@@ -169,23 +346,43 @@ async fn handle_warn(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
     };
 
-    if !moderate_permission(ctx, gid, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Warn) {
         return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
     let mut user: Option<UserId> = None;
     let mut reason: Option<String> = None;
     let mut evidence: Option<String> = None;
+    let mut preset_name: Option<String> = None;
+    let mut points_opt: Option<i64> = None;
 
     for o in &cmd.data.options {
         match (&o.name[..], &o.value) {
             ("user", CommandDataOptionValue::User(u)) => user = Some(*u),
             ("reason", CommandDataOptionValue::String(s)) => reason = Some(s.clone()),
             ("evidence", CommandDataOptionValue::String(s)) => evidence = Some(s.clone()),
+            ("preset", CommandDataOptionValue::String(s)) => preset_name = Some(s.clone()),
+            ("points", CommandDataOptionValue::Integer(n)) => points_opt = Some(*n),
             _ => {}
         }
     }
 
+    // Waga: jawne „points” > preset > 1
+    let preset = match preset_name.as_deref() {
+        Some(name) => match find_preset(&app.db, gid.get(), name).await {
+            Some(p) => Some(p),
+            None => {
+                return edit_ephemeral(ctx, cmd, &format!("Nieznany preset `{}`.", truncate(name, 32)))
+                    .await;
+            }
+        },
+        None => None,
+    };
+    let points: i32 = points_opt
+        .map(|n| n.clamp(1, MAX_WARN_POINTS) as i32)
+        .or(preset.as_ref().map(|p| p.points))
+        .unwrap_or(1);
+
     let Some(uid) = user else {
         return edit_ephemeral(ctx, cmd, "Musisz wskazać użytkownika.").await;
     };
@@ -196,25 +393,70 @@ async fn handle_warn(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
 
     let case_id = insert_warn(
         &app.db,
-        gid.get(),
-        uid.get(),
-        cmd.user.id.get(),
-        &reason_text,
-        evidence.as_deref(),
+        &NewWarn {
+            gid: gid.get(),
+            uid: uid.get(),
+            mod_id: cmd.user.id.get(),
+            points,
+            preset: preset.as_ref().map(|p| p.name.as_str()),
+            reason: &reason_text,
+            evidence: evidence.as_deref(),
+        },
     )
     .await?;
 
-    let _ = dm_warn(ctx, uid, &reason_text, evidence.as_deref()).await;
+    let case_no = cases::record_case_logged(
+        &app.db,
+        &NewCase {
+            guild_id: gid.get(),
+            user_id: uid.get(),
+            moderator_id: cmd.user.id.get(),
+            action: CaseAction::Warn,
+            reason: &reason_text,
+            evidence: evidence.as_deref(),
+            source: None,
+            reference: Some(CaseRef::Warn(case_id)),
+        },
+    )
+    .await;
 
-    if let Some(log_ch) = log_channel(&app) {
-        let embed =
-            log_embed_warn(ctx, gid, cmd.user.id, uid, &reason_text, evidence.as_deref()).await;
+    let _ = dm_warn(ctx, uid, points, &reason_text, evidence.as_deref()).await;
+
+    if let Some(log_ch) = log_channel(gid.get()) {
+        let mut embed = log_embed_warn(
+            ctx,
+            cmd.user.id,
+            uid,
+            points,
+            preset.as_ref().map(|p| p.name.as_str()),
+            &reason_text,
+            evidence.as_deref(),
+        )
+        .await;
+        if let Some(no) = case_no {
+            embed = embed.field("Sprawa", format!("#{no}"), true);
+        }
         let _ = ChannelId::new(log_ch)
             .send_message(&ctx.http, CreateMessage::new().embed(embed))
             .await;
     }
 
-    let conf = confirm_embed_warn(ctx, uid, case_id, &reason_text, evidence.as_deref()).await;
+    // Progi z warn_config → ewentualny timeout/kick/ban
+    let issued = IssuedWarn { id: case_id, case_no, points: points as i64, reason: &reason_text };
+    let escalation = match escalate_after_warn(ctx, app, gid, uid, &issued).await {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::warn!(?e, case_id, "warn escalation failed");
+            Some("⚠️ nie udało się wykonać (szczegóły w logach)".to_string())
+        }
+    };
+
+    let decay_days = load_warn_config(&app.db, gid.get()).await.decay_days as i64;
+    let mut conf =
+        confirm_embed_warn(ctx, uid, case_no, decay_days, points, &reason_text, evidence.as_deref()).await;
+    if let Some(esc) = escalation {
+        conf = conf.field("Auto-eskalacja", esc, false);
+    }
     cmd.edit_response(&ctx.http, EditInteractionResponse::new().embeds(vec![conf]))
         .await?;
     Ok(())
@@ -230,6 +472,9 @@ async fn handle_warns(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     let Some(gid) = cmd.guild_id else {
         return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
     };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::Warns) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
+    }
     let mut user: Option<UserId> = None;
     for o in &cmd.data.options {
         if o.name == "user" {
@@ -242,12 +487,15 @@ async fn handle_warns(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
         return edit_ephemeral(ctx, cmd, "Wskaż użytkownika.").await;
     };
 
-    let list = list_active_warns(&app.db, gid.get(), uid.get(), DECAY_DAYS, 10).await?;
+    let decay_days = load_warn_config(&app.db, gid.get()).await.decay_days as i64;
+    let list = list_active_warns(&app.db, gid.get(), uid.get(), decay_days, 10).await?;
+    let active = active_points(&app.db, gid.get(), uid.get(), decay_days).await?;
 
     let mut e = CreateEmbed::new()
         .title("📒 Ostrzeżenia użytkownika")
         .colour(Colour::new(0x3498DB))
         .field("Użytkownik", format!("<@{}>", uid.get()), true)
+        .field("Aktywne punkty", format!("**{active}** _(okres: {decay_days} dni)_"), true)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
     if list.is_empty() {
@@ -255,14 +503,17 @@ async fn handle_warns(ctx: &Context, app: &AppContext, cmd: &CommandInteraction)
     } else {
         let mut lines = Vec::new();
         for c in list {
-            let expires_ts = c.created_at + (DECAY_DAYS * 86_400);
+            let expires_ts = c.created_at + (decay_days * 86_400);
             let expires = NaiveDateTime::from_timestamp_opt(expires_ts, 0)
                 .unwrap()
                 .format("%d/%m/%Y")
                 .to_string();
+            let preset = c.preset.map(|p| format!(" ({p})")).unwrap_or_default();
             lines.push(format!(
-                "`#{}` • wygasa: {}\n{}",
+                "`#{}` • **{} pkt**{} • wygasa: {}\n{}",
                 c.id,
+                c.points,
+                preset,
                 expires,
                 truncate(&c.reason, 180)
             ));
@@ -285,7 +536,7 @@ async fn handle_warn_remove(ctx: &Context, app: &AppContext, cmd: &CommandIntera
     let Some(gid) = cmd.guild_id else {
         return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
     };
-    if !moderate_permission(ctx, gid, cmd.user.id).await {
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::WarnRemove) {
         return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
     }
 
@@ -303,8 +554,13 @@ async fn handle_warn_remove(ctx: &Context, app: &AppContext, cmd: &CommandIntera
     };
     let del_rs = reason.unwrap_or_else(|| "moderator remove".into());
 
-    if let Some(uid) = soft_delete_warn(&app.db, gid.get(), cid, cmd.user.id.get(), &del_rs).await? {
+    if let Some(uid) = revoke_warn(&app.db, gid.get(), cid, cmd.user.id.get(), &del_rs).await? {
         let user_id = UserId::new(uid);
+        if let Err(e) =
+            cases::mark_deleted_by_ref(&app.db, gid.get(), CaseRef::Warn(cid), cmd.user.id.get(), &del_rs).await
+        {
+            tracing::warn!(?e, case_id = cid, "case delete mark failed");
+        }
         let _ = dm_unwarn(ctx, user_id, cmd.user.id, &del_rs).await;
 
         let e = CreateEmbed::new()
@@ -321,7 +577,7 @@ async fn handle_warn_remove(ctx: &Context, app: &AppContext, cmd: &CommandIntera
         cmd.edit_response(&ctx.http, EditInteractionResponse::new().embeds(vec![e]))
             .await?;
 
-        if let Some(log_ch) = log_channel(&app) {
+        if let Some(log_ch) = log_channel(gid.get()) {
             let embed = log_embed_unwarn(ctx, gid, cmd.user.id, user_id, cid, &del_rs).await;
             let _ = ChannelId::new(log_ch)
                 .send_message(&ctx.http, CreateMessage::new().embed(embed))
@@ -333,11 +589,164 @@ async fn handle_warn_remove(ctx: &Context, app: &AppContext, cmd: &CommandIntera
     Ok(())
 }
 
+async fn handle_warn_config(ctx: &Context, app: &AppContext, cmd: &CommandInteraction) -> Result<()> {
+    cmd.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true)),
+    )
+    .await?;
+
+    let Some(gid) = cmd.guild_id else {
+        return edit_ephemeral(ctx, cmd, "Użyj na serwerze.").await;
+    };
+    if !permissions::authorize(gid, cmd.member.as_deref(), Permission::WarnConfig) {
+        return edit_ephemeral(ctx, cmd, "⛔ Brak uprawnień.").await;
+    }
+
+    let Some(sc) = cmd.data.options.first() else {
+        return edit_ephemeral(ctx, cmd, "Wybierz podkomendę.").await;
+    };
+
+    let (cfg, changed) = match sc.name.as_str() {
+        "show" => (load_warn_config(&app.db, gid.get()).await, false),
+        "set" => {
+            let mut cfg = load_warn_config(&app.db, gid.get()).await;
+            if let CommandDataOptionValue::SubCommand(params) = &sc.value {
+                for p in params {
+                    let CommandDataOptionValue::Integer(n) = p.value else { continue };
+                    let n = n as i32;
+                    match p.name.as_str() {
+                        "decay_days" => cfg.decay_days = n.max(1),
+                        "timeout_pts" => cfg.timeout_pts = n.max(0),
+                        "timeout_hours" => cfg.timeout_hours = n.max(1),
+                        "kick_pts" => cfg.kick_pts = n.max(0),
+                        "ban_pts" => cfg.ban_pts = n.max(0),
+                        _ => {}
+                    }
+                }
+            }
+            save_warn_config(&app.db, gid.get(), &cfg).await?;
+            (cfg, true)
+        }
+        "reset" => {
+            let cfg = WarnConfig::default();
+            save_warn_config(&app.db, gid.get(), &cfg).await?;
+            (cfg, true)
+        }
+        "preset-set" | "preset-remove" => {
+            let mut name: Option<String> = None;
+            let mut points: Option<i64> = None;
+            if let CommandDataOptionValue::SubCommand(params) = &sc.value {
+                for p in params {
+                    match (p.name.as_str(), &p.value) {
+                        ("name", CommandDataOptionValue::String(v)) => {
+                            name = Some(normalize_preset_name(v))
+                        }
+                        ("points", CommandDataOptionValue::Integer(n)) => points = Some(*n),
+                        _ => {}
+                    }
+                }
+            }
+            let Some(name) = name.filter(|n| !n.is_empty()) else {
+                return edit_ephemeral(ctx, cmd, "Podaj nazwę presetu.").await;
+            };
+            if sc.name == "preset-set" {
+                let pts = points.unwrap_or(1).clamp(1, MAX_WARN_POINTS) as i32;
+                save_preset(&app.db, gid.get(), &name, pts).await?;
+            } else if !delete_preset(&app.db, gid.get(), &name).await? {
+                return edit_ephemeral(ctx, cmd, &format!("Nie ma presetu `{name}`.")).await;
+            }
+            (load_warn_config(&app.db, gid.get()).await, true)
+        }
+        _ => return edit_ephemeral(ctx, cmd, "Nieznana podkomenda.").await,
+    };
+
+    let presets = load_presets(&app.db, gid.get()).await;
+    let e = warn_config_embed(&cfg, &presets)
+        .title(if changed { "🛠️ Zapisano konfigurację warnów" } else { "⚙️ Konfiguracja warnów" });
+
+    if changed && let Some(log_ch) = log_channel(gid.get()) {
+        let log = warn_config_embed(&cfg, &presets)
+            .title("🛠️ Zmieniono konfigurację warnów")
+            .field("Administrator", format!("<@{}>", cmd.user.id.get()), true);
+        let _ = ChannelId::new(log_ch)
+            .send_message(&ctx.http, CreateMessage::new().embed(log))
+            .await;
+    }
+
+    cmd.edit_response(&ctx.http, EditInteractionResponse::new().embeds(vec![e]))
+        .await?;
+    Ok(())
+}
+
 // Embeds / DM / Logs
 
+fn warn_config_embed(cfg: &WarnConfig, presets: &[WarnPreset]) -> CreateEmbed {
+    let threshold = |pts: i32| {
+        if pts > 0 {
+            format!("**{pts}** pkt")
+        } else {
+            "_wyłączony_".to_string()
+        }
+    };
+    CreateEmbed::new()
+        .colour(Colour::new(0x95A5A6))
+        .field("Wygasanie", format!("**{}** dni", cfg.decay_days), true)
+        .field(
+            "Wyciszenie",
+            format!("{} • {}h", threshold(cfg.timeout_pts), cfg.timeout_hours),
+            true,
+        )
+        .field("Kick", threshold(cfg.kick_pts), true)
+        .field("Ban", threshold(cfg.ban_pts), true)
+        .field(
+            "Presety",
+            if presets.is_empty() {
+                "_brak – każdy warn = 1 pkt (lub `points`)_".to_string()
+            } else {
+                presets
+                    .iter()
+                    .map(|p| format!("`{}` = **{}** pkt", p.name, p.points))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+            false,
+        )
+        .footer(CreateEmbedFooter::new(SYSTEM_NAME))
+}
+
+async fn handle_preset_autocomplete(
+    ctx: &Context,
+    app: &AppContext,
+    ac: &CommandInteraction,
+) -> Result<()> {
+    let Some(gid) = ac.guild_id else { return Ok(()) };
+    let Some(focused) = ac.data.autocomplete() else { return Ok(()) };
+    if !matches!(focused.name, "preset" | "name") {
+        return Ok(());
+    }
+
+    let needle = focused.value.to_lowercase();
+    let choices = load_presets(&app.db, gid.get())
+        .await
+        .into_iter()
+        .filter(|p| p.name.contains(&needle))
+        .take(25)
+        .map(|p| AutocompleteChoice::new(format!("{} ({} pkt)", p.name, p.points), p.name))
+        .collect();
+
+    ac.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Autocomplete(CreateAutocompleteResponse::new().set_choices(choices)),
+    )
+    .await?;
+    Ok(())
+}
+
 async fn dm_warn(
     ctx: &Context,
     uid: UserId,
+    points: i32,
     reason: &str,
     evidence: Option<&str>,
 ) -> Result<()> {
@@ -346,6 +755,7 @@ async fn dm_warn(
         .title("Ostrzeżenie")
         .colour(Colour::new(0xE67E22))
         .description("Otrzymujesz ostrzeżenie od zespołu moderacji.")
+        .field("Punkty", format!("**{points}**"), true)
         .field("Powód", format!("```{}```", truncate(reason, 900)), false)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
     if let Some(ev) = evidence {
@@ -391,9 +801,10 @@ async fn dm_unwarn(ctx: &Context, uid: UserId, moderator: UserId, reason: &str)
 
 async fn log_embed_warn(
     ctx: &Context,
-    _gid: GuildId,
     mod_id: UserId,
     user_id: UserId,
+    points: i32,
+    preset: Option<&str>,
     reason: &str,
     evidence: Option<&str>,
 ) -> CreateEmbed {
@@ -412,6 +823,14 @@ async fn log_embed_warn(
             true,
         )
         .field("Kiedy", format!("<t:{now}:F> • <t:{now}:R>"), true)
+        .field(
+            "Punkty",
+            match preset {
+                Some(p) => format!("**{points}** ({p})"),
+                None => format!("**{points}**"),
+            },
+            true,
+        )
         .field("Powód", format!("```{}```", truncate(reason, 1400)), false)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
@@ -430,21 +849,27 @@ async fn log_embed_warn(
 async fn confirm_embed_warn(
     ctx: &Context,
     uid: UserId,
-    case_id: i64,
+    case_no: Option<i64>,
+    decay_days: i64,
+    points: i32,
     reason: &str,
     evidence: Option<&str>,
 ) -> CreateEmbed {
-    let expires_ts = now_unix() + (DECAY_DAYS * 86_400);
+    let expires_ts = now_unix() + (decay_days * 86_400);
     let expires = NaiveDateTime::from_timestamp_opt(expires_ts, 0)
         .unwrap()
         .format("%d/%m/%Y")
         .to_string();
     let mut e = CreateEmbed::new()
         .title("✅ Ostrzeżenie nadane")
-        .colour(Colour::new(0x2ECC71))
-        .field("Case", format!("#{}", case_id), true)
+        .colour(Colour::new(0x2ECC71));
+    if let Some(no) = case_no {
+        e = e.field("Sprawa", format!("#{no}"), true);
+    }
+    e = e
         .field("Użytkownik", format!("<@{}>", uid.get()), true)
         .field("Wygasa", expires, true)
+        .field("Punkty", format!("**{points}**"), true)
         .field("Powód", format!("```{}```", truncate(reason, 900)), false)
         .footer(CreateEmbedFooter::new(SYSTEM_NAME));
 
@@ -500,29 +925,306 @@ async fn log_embed_unwarn(
     e
 }
 
+// Escalation
+
+/// Najwyższy próg przekroczony przez ostatni warn (`before` < próg <= `after`).
+/// Próg już wcześniej przekroczony nie odpala się ponownie.
+fn crossed_threshold(cfg: &WarnConfig, before: i64, after: i64) -> Option<(Escalation, i32)> {
+    let crossed = |t: i32| t > 0 && before < t as i64 && after >= t as i64;
+    if crossed(cfg.ban_pts) {
+        Some((Escalation::Ban, cfg.ban_pts))
+    } else if crossed(cfg.kick_pts) {
+        Some((Escalation::Kick, cfg.kick_pts))
+    } else if crossed(cfg.timeout_pts) {
+        Some((Escalation::Timeout, cfg.timeout_pts))
+    } else {
+        None
+    }
+}
+
+/// Świeżo nadany warn, od którego liczymy eskalację.
+struct IssuedWarn<'a> {
+    id: i64,              // tss.warn_cases.id
+    case_no: Option<i64>, // numer sprawy gildii (tss.cases)
+    points: i64,
+    reason: &'a str,
+}
+
+/// Po nadaniu warna: przelicz aktywne punkty (okno wygaszania) i – jeśli przekroczono próg –
+/// wykonaj karę istniejącą ścieżką Mute/Kick/Ban. Eskalacja trafia do `tss.warn_escalations`
+/// jako osobna sprawa powiązana z warnem. Zwraca opis do potwierdzenia dla moderatora.
+async fn escalate_after_warn(
+    ctx: &Context,
+    app: &AppContext,
+    gid: GuildId,
+    uid: UserId,
+    warn: &IssuedWarn<'_>,
+) -> Result<Option<String>> {
+    let cfg = load_warn_config(&app.db, gid.get()).await;
+    let after = refresh_warn_points(&app.db, gid.get(), uid.get(), cfg.decay_days as i64).await?;
+    let before = after - warn.points;
+
+    let Some((action, threshold)) = crossed_threshold(&cfg, before, after) else {
+        return Ok(None);
+    };
+
+    let bot = ctx.cache.current_user().id;
+    let warn_ref = warn.case_no.map(|no| format!(" (sprawa #{no})")).unwrap_or_default();
+    let source = format!("Auto-eskalacja z warna{warn_ref}");
+    let reason = format!(
+        "Przekroczono próg ostrzeżeń: {after} pkt (próg {threshold}). Warn{warn_ref}: {}",
+        truncate(warn.reason, 300)
+    );
+
+    let (mute_case, ban_case, summary) = match action {
+        Escalation::Timeout => {
+            let req = MuteRequest {
+                guild_id: gid,
+                moderator_id: bot,
+                user_id: uid,
+                reason: &reason,
+                evidence: None,
+                minutes: cfg.timeout_hours as i64 * 60,
+                source: Some(&source),
+                force_timeout: true,
+            };
+            let Some(applied) = apply_mute(ctx, app, &req).await? else {
+                anyhow::bail!("timeout escalation: timeout_hours = 0");
+            };
+            (Some(applied.case_id), None, format!("timeout na {}h", cfg.timeout_hours))
+        }
+        Escalation::Kick => {
+            apply_kick(ctx, app, gid, bot, uid, &reason, Some(&source)).await?;
+            (None, None, "kick".to_string())
+        }
+        Escalation::Ban => {
+            let id = apply_auto_ban(ctx, app, gid, bot, uid, &reason, Some(&source)).await?;
+            (None, id, "ban permanentny".to_string())
+        }
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO tss.warn_escalations
+            (guild_id, user_id, warn_case_id, action, threshold_pts, active_points, mute_case_id, ban_case_id)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+        "#,
+    )
+    .bind(gid.get() as i64)
+    .bind(uid.get() as i64)
+    .bind(warn.id)
+    .bind(action.as_str())
+    .bind(threshold)
+    .bind(after as i32)
+    .bind(mute_case)
+    .bind(ban_case)
+    .execute(&app.db)
+    .await?;
+
+    Ok(Some(format!(
+        "{summary} (próg {threshold} pkt, aktywne: {after} pkt)"
+    )))
+}
+
 // DB + Utils
 
-async fn insert_warn(
-    db: &Pool<Postgres>,
+/// Konfiguracja warnów gildii (cache → `tss.warn_config` → domyślna).
+/// Jedyne źródło okresu wygasania i progów – używane też przez /user i /admcheck.
+pub async fn load_warn_config(db: &Pool<Postgres>, gid: u64) -> WarnConfig {
+    if let Some(c) = CFG.get(&gid) {
+        return c.clone();
+    }
+    let row = sqlx::query(
+        r#"
+        SELECT decay_days, timeout_pts, timeout_hours, kick_pts, ban_pts
+        FROM tss.warn_config
+        WHERE guild_id = $1
+        "#,
+    )
+    .bind(gid as i64)
+    .fetch_optional(db)
+    .await;
+
+    let cfg = match row {
+        Ok(Some(r)) => WarnConfig {
+            decay_days: r.get("decay_days"),
+            timeout_pts: r.get("timeout_pts"),
+            timeout_hours: r.get("timeout_hours"),
+            kick_pts: r.get("kick_pts"),
+            ban_pts: r.get("ban_pts"),
+        },
+        Ok(None) => WarnConfig::default(),
+        // błąd DB – nie zapisuj domyślnej do cache, spróbujemy ponownie
+        Err(e) => {
+            tracing::warn!(?e, gid, "warn_config load failed");
+            return WarnConfig::default();
+        }
+    };
+    CFG.insert(gid, cfg.clone());
+    cfg
+}
+
+async fn save_warn_config(db: &Pool<Postgres>, gid: u64, cfg: &WarnConfig) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tss.warn_config
+            (guild_id, decay_days, timeout_pts, timeout_hours, kick_pts, ban_pts, updated_at)
+        VALUES ($1,$2,$3,$4,$5,$6, now())
+        ON CONFLICT (guild_id) DO UPDATE SET
+            decay_days    = EXCLUDED.decay_days,
+            timeout_pts   = EXCLUDED.timeout_pts,
+            timeout_hours = EXCLUDED.timeout_hours,
+            kick_pts      = EXCLUDED.kick_pts,
+            ban_pts       = EXCLUDED.ban_pts,
+            updated_at    = now()
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(cfg.decay_days)
+    .bind(cfg.timeout_pts)
+    .bind(cfg.timeout_hours)
+    .bind(cfg.kick_pts)
+    .bind(cfg.ban_pts)
+    .execute(db)
+    .await?;
+    CFG.insert(gid, cfg.clone());
+    Ok(())
+}
+
+/// Presety gildii (cache → `tss.warn_presets`), posortowane po wadze.
+async fn load_presets(db: &Pool<Postgres>, gid: u64) -> Vec<WarnPreset> {
+    if let Some(p) = PRESETS.get(&gid) {
+        return p.clone();
+    }
+    let rows = sqlx::query(
+        "SELECT name, points FROM tss.warn_presets WHERE guild_id = $1 ORDER BY points, name",
+    )
+    .bind(gid as i64)
+    .fetch_all(db)
+    .await;
+
+    match rows {
+        Ok(rows) => {
+            let list: Vec<WarnPreset> = rows
+                .into_iter()
+                .map(|r| WarnPreset {
+                    name: r.get("name"),
+                    points: r.get("points"),
+                })
+                .collect();
+            PRESETS.insert(gid, list.clone());
+            list
+        }
+        Err(e) => {
+            tracing::warn!(?e, gid, "warn_presets load failed");
+            Vec::new()
+        }
+    }
+}
+
+async fn find_preset(db: &Pool<Postgres>, gid: u64, name: &str) -> Option<WarnPreset> {
+    let name = normalize_preset_name(name);
+    load_presets(db, gid).await.into_iter().find(|p| p.name == name)
+}
+
+async fn save_preset(db: &Pool<Postgres>, gid: u64, name: &str, points: i32) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tss.warn_presets (guild_id, name, points, updated_at)
+        VALUES ($1,$2,$3, now())
+        ON CONFLICT (guild_id, name) DO UPDATE SET points = EXCLUDED.points, updated_at = now()
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(name)
+    .bind(points)
+    .execute(db)
+    .await?;
+    PRESETS.remove(&gid);
+    Ok(())
+}
+
+async fn delete_preset(db: &Pool<Postgres>, gid: u64, name: &str) -> Result<bool> {
+    let res = sqlx::query("DELETE FROM tss.warn_presets WHERE guild_id = $1 AND name = $2")
+        .bind(gid as i64)
+        .bind(name)
+        .execute(db)
+        .await?;
+    PRESETS.remove(&gid);
+    Ok(res.rows_affected() > 0)
+}
+
+fn normalize_preset_name(s: &str) -> String {
+    s.trim().to_lowercase().chars().take(32).collect()
+}
+
+/// Przelicz aktywne punkty (okno wygaszania) i zapisz je w `tss.warn_points`.
+async fn refresh_warn_points(db: &Pool<Postgres>, gid: u64, uid: u64, decay_days: i64) -> Result<i64> {
+    let pts = active_points(db, gid, uid, decay_days).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO tss.warn_points (guild_id, user_id, total_points, last_decay_at, updated_at)
+        VALUES ($1,$2,$3, now(), now())
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET
+            total_points  = EXCLUDED.total_points,
+            last_decay_at = now(),
+            updated_at    = now()
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .bind(pts as i32)
+    .execute(db)
+    .await?;
+    Ok(pts)
+}
+
+/// Suma punktów z nieusuniętych warnów w oknie wygaszania.
+async fn active_points(db: &Pool<Postgres>, gid: u64, uid: u64, decay_days: i64) -> Result<i64> {
+    let pts: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(points), 0)::BIGINT
+        FROM tss.warn_cases
+        WHERE guild_id = $1
+          AND user_id = $2
+          AND deleted_at IS NULL
+          AND created_at >= now() - make_interval(days => $3::INT)
+        "#,
+    )
+    .bind(gid as i64)
+    .bind(uid as i64)
+    .bind(decay_days as i32)
+    .fetch_one(db)
+    .await?;
+    Ok(pts)
+}
+
+struct NewWarn<'a> {
     gid: u64,
     uid: u64,
     mod_id: u64,
-    reason: &str,
-    evidence: Option<&str>,
-) -> Result<i64> {
+    points: i32,
+    preset: Option<&'a str>,
+    reason: &'a str,
+    evidence: Option<&'a str>,
+}
+
+async fn insert_warn(db: &Pool<Postgres>, w: &NewWarn<'_>) -> Result<i64> {
     let id: i64 = sqlx::query_scalar(
         r#"
         INSERT INTO tss.warn_cases
-            (guild_id, user_id, moderator_id, points, reason, evidence)
-        VALUES ($1,$2,$3,1,$4,$5)
+            (guild_id, user_id, moderator_id, points, preset, reason, evidence)
+        VALUES ($1,$2,$3,$4,$5,$6,$7)
         RETURNING id
         "#,
     )
-    .bind(gid as i64)
-    .bind(uid as i64)
-    .bind(mod_id as i64)
-    .bind(reason)
-    .bind(evidence)
+    .bind(w.gid as i64)
+    .bind(w.uid as i64)
+    .bind(w.mod_id as i64)
+    .bind(w.points)
+    .bind(w.preset)
+    .bind(w.reason)
+    .bind(w.evidence)
     .fetch_one(db)
     .await?;
     Ok(id)
@@ -543,6 +1245,8 @@ async fn list_active_warns(
             guild_id,
             user_id,
             moderator_id,
+            points,
+            preset,
             reason,
             evidence,
             EXTRACT(EPOCH FROM created_at)::BIGINT AS created_at_unix
@@ -569,6 +1273,8 @@ async fn list_active_warns(
             guild_id: r.try_get::<i64, _>("guild_id").unwrap() as u64,
             user_id: r.try_get::<i64, _>("user_id").unwrap() as u64,
             moderator_id: r.try_get::<i64, _>("moderator_id").unwrap() as u64,
+            points: r.try_get::<i32, _>("points").unwrap_or(1),
+            preset: r.try_get::<Option<String>, _>("preset").unwrap_or(None),
             reason: r.try_get::<String, _>("reason").unwrap(),
             evidence: r.try_get::<Option<String>, _>("evidence").unwrap(),
             created_at: r.try_get::<i64, _>("created_at_unix").unwrap(),
@@ -577,6 +1283,24 @@ async fn list_active_warns(
     Ok(list)
 }
 
+/// Usuń warna (soft delete) i przelicz aktywne punkty – wspólne dla /warn-remove i /case delete.
+pub(crate) async fn revoke_warn(
+    db: &Pool<Postgres>,
+    gid: u64,
+    warn_id: i64,
+    by: u64,
+    why: &str,
+) -> Result<Option<u64>> {
+    let Some(uid) = soft_delete_warn(db, gid, warn_id, by, why).await? else {
+        return Ok(None);
+    };
+    let decay_days = load_warn_config(db, gid).await.decay_days as i64;
+    if let Err(e) = refresh_warn_points(db, gid, uid, decay_days).await {
+        tracing::warn!(?e, warn_id, "warn_points refresh failed");
+    }
+    Ok(Some(uid))
+}
+
 async fn soft_delete_warn(
     db: &Pool<Postgres>,
     gid: u64,
@@ -634,9 +1358,8 @@ fn truncate(s: &str, max: usize) -> String {
     }
 }
 
-fn log_channel(app: &AppContext) -> Option<u64> {
-    let env = app.env();
-    let id = env_channels::logs::ban_kick_mute_id(&env);
+fn log_channel(gid: u64) -> Option<u64> {
+    let id = env_channels::logs::ban_kick_mute_id(gid);
     if id == 0 {
         None
     } else {
@@ -644,11 +1367,25 @@ fn log_channel(app: &AppContext) -> Option<u64> {
     }
 }
 
-async fn moderate_permission(ctx: &Context, gid: GuildId, uid: UserId) -> bool {
-    if let Ok(m) = gid.member(&ctx.http, uid).await {
-        if let Ok(p) = m.permissions(&ctx.cache) {
-            return p.moderate_members() || p.kick_members() || p.administrator();
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_only_when_crossing_threshold() {
+        let cfg = WarnConfig::default(); // 3 / 6 / 9
+        assert_eq!(crossed_threshold(&cfg, 1, 2), None);
+        assert_eq!(crossed_threshold(&cfg, 2, 3), Some((Escalation::Timeout, 3)));
+        assert_eq!(crossed_threshold(&cfg, 3, 4), None);
+        assert_eq!(crossed_threshold(&cfg, 5, 6), Some((Escalation::Kick, 6)));
+        assert_eq!(crossed_threshold(&cfg, 8, 9), Some((Escalation::Ban, 9)));
+    }
+
+    #[test]
+    fn picks_highest_crossed_and_skips_disabled() {
+        let mut cfg = WarnConfig::default();
+        assert_eq!(crossed_threshold(&cfg, 2, 7), Some((Escalation::Kick, 6)));
+        cfg.kick_pts = 0;
+        assert_eq!(crossed_threshold(&cfg, 5, 6), None);
     }
-    false
 }
@@ -8,9 +8,8 @@ use crate::registry::env_channels;
 pub struct Welcome;
 
 impl Welcome {
-    pub async fn send_welcome(ctx: &Context, app: &AppContext, member: &Member) {
-        let env = app.env();
-        let ch_id = env_channels::global::welcome_id(&env);
+    pub async fn send_welcome(ctx: &Context, _app: &AppContext, member: &Member) {
+        let ch_id = env_channels::global::welcome_id(member.guild_id.get());
         if ch_id == 0 { return; }
 
         let user = &member.user;
@@ -39,9 +38,8 @@ impl Welcome {
             .await;
     }
 
-    pub async fn send_goodbye(ctx: &Context, app: &AppContext, _guild_id: GuildId, user: &User) {
-        let env = app.env();
-        let ch_id = env_channels::global::goodbye_id(&env);
+    pub async fn send_goodbye(ctx: &Context, _app: &AppContext, guild_id: GuildId, user: &User) {
+        let ch_id = env_channels::global::goodbye_id(guild_id.get());
         if ch_id == 0 { return; }
 
         let mention = format!("<@{}>", user.id.get());